@@ -54,6 +54,12 @@ function getPageConfig(route: string, request: any): PageConfig {
                 page: glot.snippetPage(browserContext),
                 status: 200,
             }
+
+        case "DiffSnippet":
+            return {
+                page: glot.diffPage(browserContext),
+                status: 200,
+            }
     }
 
     throw new Error(`Unhandled route: ${route}`);
@@ -0,0 +1,66 @@
+use glot_core::snippet::dictionary;
+use glot_core::snippet::File;
+use glot_core::snippet::Snippet;
+use glot_languages::language;
+
+// Prints the encoded length of each language's example snippet with and without its dictionary.
+// Run with: cargo run --release --example dictionary_benchmark
+fn main() {
+    let mut total_plain = 0;
+    let mut total_dictionary = 0;
+
+    println!(
+        "{:<16} {:>8} {:>8} {:>8}",
+        "language", "plain", "dict", "saved"
+    );
+
+    for language in language::list() {
+        let editor_config = language.config().editor_config();
+
+        let snippet = Snippet {
+            language,
+            title: "Hello World".to_string(),
+            files: vec![File::text(
+                editor_config.default_filename,
+                editor_config.example_code,
+            )],
+            inputs: vec![],
+            instructions: None,
+            env: Default::default(),
+            args: vec![],
+            timeout: None,
+        };
+
+        let plain = snippet
+            .to_encoded_string_with_dictionary(None)
+            .expect("Failed to encode snippet");
+
+        let with_dictionary = snippet
+            .to_encoded_string_with_dictionary(dictionary::for_language(language))
+            .expect("Failed to encode snippet");
+
+        let saved = plain.len() as f64 - with_dictionary.len() as f64;
+
+        println!(
+            "{:<16} {:>8} {:>8} {:>7.1}%",
+            language.config().id(),
+            plain.len(),
+            with_dictionary.len(),
+            saved / plain.len() as f64 * 100.0
+        );
+
+        total_plain += plain.len();
+        total_dictionary += with_dictionary.len();
+    }
+
+    // The dictionary can make a snippet longer, so the saving may be negative
+    let total_saved = total_plain as f64 - total_dictionary as f64;
+
+    println!(
+        "{:<16} {:>8} {:>8} {:>7.1}%",
+        "total",
+        total_plain,
+        total_dictionary,
+        total_saved / total_plain as f64 * 100.0
+    );
+}
@@ -5,12 +5,16 @@ use serde::Deserialize;
 use serde::Serialize;
 use url::Url;
 
+// Longer urls are rejected by Cloudflare with 414 URI Too Long
+pub const MAX_URL_LENGTH: usize = 16000;
+
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum RouteName {
     NotFound,
     Home,
     NewSnippet,
     EditSnippet,
+    DiffSnippet,
 }
 
 impl fmt::Display for RouteName {
@@ -20,6 +24,7 @@ impl fmt::Display for RouteName {
             RouteName::Home => write!(f, "Home"),
             RouteName::NewSnippet => write!(f, "NewSnippet"),
             RouteName::EditSnippet => write!(f, "EditSnippet"),
+            RouteName::DiffSnippet => write!(f, "DiffSnippet"),
         }
     }
 }
@@ -31,6 +36,7 @@ pub enum Route {
     Home,
     NewSnippet(Language),
     EditSnippet(Language, String),
+    DiffSnippet(String, String),
 }
 
 impl Route {
@@ -43,6 +49,7 @@ impl Route {
 
         match parts.as_slice() {
             [""] => Route::Home,
+            ["diff", old, new] => Route::DiffSnippet(old.to_string(), new.to_string()),
             [language] if is_valid_language(language) => {
                 Route::NewSnippet(language.parse().unwrap())
             }
@@ -59,6 +66,7 @@ impl Route {
             Route::Home => "/".to_string(),
             Route::NewSnippet(language) => format!("/{}", language.config().id()),
             Route::EditSnippet(language, id) => format!("/{}/{}", language.config().id(), id),
+            Route::DiffSnippet(old, new) => format!("/diff/{}/{}", old, new),
         }
     }
 
@@ -74,6 +82,7 @@ impl Route {
             Route::Home => RouteName::Home,
             Route::NewSnippet(_) => RouteName::NewSnippet,
             Route::EditSnippet(_, _) => RouteName::EditSnippet,
+            Route::DiffSnippet(_, _) => RouteName::DiffSnippet,
         }
     }
 }
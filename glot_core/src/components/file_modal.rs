@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::snippet::path;
 use crate::view::modal;
 use glot_languages::language::Language;
 use maud::html;
@@ -30,6 +32,10 @@ pub struct Model {
     existing_filenames: Vec<String>,
     filename: String,
     is_new: bool,
+    is_main: bool,
+    read_only: bool,
+    hidden: bool,
+    can_hide: bool,
     error: Option<String>,
 }
 
@@ -43,6 +49,12 @@ impl Model {
             .iter()
             .any(|name| *name == self.filename)
     }
+
+    fn directory_conflict(&self) -> Option<&String> {
+        self.existing_filenames
+            .iter()
+            .find(|name| path::is_directory_conflict(name, &self.filename))
+    }
 }
 
 #[derive(strum_macros::Display, poly_macro::DomId)]
@@ -53,6 +65,9 @@ enum Id {
     AddFileButton,
     UpdateFileButton,
     DeleteFileButton,
+    SetMainFileButton,
+    ReadOnlyCheckbox,
+    HiddenCheckbox,
     FilenameInput,
     FileModalBackdrop,
     FileModalClose,
@@ -63,6 +78,9 @@ pub enum Msg {
     AddFileClicked,
     UpdateFileClicked,
     DeleteFileClicked,
+    SetMainFileClicked,
+    ReadOnlyToggled,
+    HiddenToggled,
     FilenameChanged(Capture<String>),
     Close,
 }
@@ -89,6 +107,12 @@ where
                     Id::DeleteFileButton,
                     to_parent_msg(Msg::DeleteFileClicked),
                 ),
+                event_listener::on_click(
+                    Id::SetMainFileButton,
+                    to_parent_msg(Msg::SetMainFileClicked),
+                ),
+                event_listener::on_click(Id::ReadOnlyCheckbox, to_parent_msg(Msg::ReadOnlyToggled)),
+                event_listener::on_click(Id::HiddenCheckbox, to_parent_msg(Msg::HiddenToggled)),
                 event_listener::on_input(Id::FilenameInput, |captured| {
                     to_parent_msg(Msg::FilenameChanged(captured))
                 }),
@@ -107,13 +131,14 @@ where
 
 pub enum Event {
     None,
-    FilenameChanged(String),
+    FileUpdated(UpdatedFile),
     FileAdded(String),
     FileDeleted,
+    SetAsMainFile,
     ModalClosed,
 }
 
-pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
     match msg {
         Msg::FilenameChanged(captured) => {
             if let State::Open(model) = state {
@@ -124,6 +149,22 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
             Ok(Event::None)
         }
 
+        Msg::ReadOnlyToggled => {
+            if let State::Open(model) = state {
+                model.read_only = !model.read_only;
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::HiddenToggled => {
+            if let State::Open(model) = state {
+                model.hidden = !model.hidden && model.can_hide;
+            }
+
+            Ok(Event::None)
+        }
+
         Msg::AddFileClicked => {
             if let State::Open(model) = state {
                 match validate_filename(model) {
@@ -147,7 +188,11 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
             if let State::Open(model) = state {
                 match validate_filename(model) {
                     Ok(_) => {
-                        let event = Event::FilenameChanged(model.filename.clone());
+                        let event = Event::FileUpdated(UpdatedFile {
+                            filename: model.filename.clone(),
+                            read_only: model.read_only,
+                            hidden: model.hidden,
+                        });
                         *state = Default::default();
                         Ok(event)
                     }
@@ -167,6 +212,11 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
             Ok(Event::FileDeleted)
         }
 
+        Msg::SetMainFileClicked => {
+            *state = Default::default();
+            Ok(Event::SetAsMainFile)
+        }
+
         Msg::Close => {
             *state = State::default();
             Ok(Event::ModalClosed)
@@ -174,10 +224,21 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
     }
 }
 
+pub struct UpdatedFile {
+    pub filename: String,
+    pub read_only: bool,
+    pub hidden: bool,
+}
+
 pub struct EditContext {
     pub language: Language,
     pub existing_filenames: Vec<String>,
     pub filename: String,
+    pub is_main: bool,
+    pub read_only: bool,
+    pub hidden: bool,
+    // At least one file must stay visible
+    pub can_hide: bool,
 }
 
 pub fn open_for_edit<ParentMsg>(state: &mut State, ctx: EditContext) -> Effect<ParentMsg> {
@@ -186,6 +247,10 @@ pub fn open_for_edit<ParentMsg>(state: &mut State, ctx: EditContext) -> Effect<P
         existing_filenames: ctx.existing_filenames,
         filename: ctx.filename,
         is_new: false,
+        is_main: ctx.is_main,
+        read_only: ctx.read_only,
+        hidden: ctx.hidden,
+        can_hide: ctx.can_hide,
         error: None,
     }));
 
@@ -203,6 +268,10 @@ pub fn open_for_add<ParentMsg>(state: &mut State, ctx: AddContext) -> Effect<Par
         existing_filenames: ctx.existing_filenames,
         filename: "".to_string(),
         is_new: true,
+        is_main: false,
+        read_only: false,
+        hidden: false,
+        can_hide: false,
         error: None,
     }));
 
@@ -241,6 +310,9 @@ fn view_modal(model: &Model) -> maud::Markup {
             label class="block text-sm font-medium text-gray-700" for=(Id::FilenameInput) {
                 "Filename"
             }
+            p class="text-sm text-gray-500" {
+                "Use / to put the file in a directory, e.g. src/lib.rs"
+            }
             @match &model.error {
                 Some(err) => {
                     div class="relative mt-1 rounded-md shadow-sm" {
@@ -264,6 +336,10 @@ fn view_modal(model: &Model) -> maud::Markup {
             }
         }
 
+        @if !model.is_new {
+            (view_attributes(model))
+        }
+
         div class="flex mt-4" {
             @if model.is_new {
                 button id=(Id::AddFileButton) class="flex-1 w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
@@ -283,14 +359,59 @@ fn view_modal(model: &Model) -> maud::Markup {
                 }
             }
         }
+
+        @if !model.is_new && !model.is_only_file() && !model.is_main {
+            div class="mt-4" {
+                button id=(Id::SetMainFileButton) class="w-full inline-flex justify-center items-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 shadow-sm hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
+                    "Set as main file"
+                }
+            }
+        }
+    }
+}
+
+fn view_attributes(model: &Model) -> maud::Markup {
+    html! {
+        fieldset class="mt-4 space-y-3" {
+            div class="relative flex items-start" {
+                div class="flex h-5 items-center" {
+                    input id=(Id::ReadOnlyCheckbox) checked[model.read_only] class="h-4 w-4 rounded border-gray-300 text-indigo-600 focus:ring-indigo-500" type="checkbox";
+                }
+                div class="ml-3 text-sm" {
+                    label class="font-medium text-gray-700" for=(Id::ReadOnlyCheckbox) { "Read-only" }
+                    p class="text-gray-500" { "The file can't be edited in the editor" }
+                }
+            }
+
+            div class="relative flex items-start" {
+                div class="flex h-5 items-center" {
+                    input id=(Id::HiddenCheckbox) checked[model.hidden] disabled[!model.can_hide && !model.hidden] class="h-4 w-4 rounded border-gray-300 text-indigo-600 focus:ring-indigo-500 disabled:opacity-50" type="checkbox";
+                }
+                div class="ml-3 text-sm" {
+                    label class="font-medium text-gray-700" for=(Id::HiddenCheckbox) { "Hidden" }
+                    p class="text-gray-500" {
+                        @if model.can_hide || model.hidden {
+                            "The file is not shown, but is included when running the code"
+                        } @else {
+                            "At least one file must be visible"
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
 fn validate_filename(model: &Model) -> Result<(), String> {
-    if model.filename.is_empty() {
-        Err("Filename cannot be empty".to_string())
-    } else if model.filename_already_exists() {
+    path::validate(&model.filename).map_err(|err| err.to_string())?;
+
+    if model.filename_already_exists() {
         Err("Filename is already used by another file".to_string())
+    } else if let Some(other) = model.directory_conflict() {
+        Err(format!(
+            "Conflicts with {}, a file can't also be a directory",
+            other
+        ))
     } else {
         Ok(())
     }
@@ -0,0 +1,276 @@
+use crate::error::Error;
+use crate::snippet::import;
+use crate::snippet::import::ConflictResolution;
+use crate::snippet::import::Imported;
+use crate::snippet::validate;
+use crate::snippet::File;
+use crate::view::dropdown;
+use crate::view::modal;
+use glot_languages::language::Language;
+use maud::html;
+use poly::browser::dom_id::DomId;
+use poly::browser::effect;
+use poly::browser::effect::Effect;
+use poly::browser::subscription;
+use poly::browser::subscription::event_listener;
+use poly::browser::subscription::Subscription;
+use poly::browser::value::Capture;
+use serde::{Deserialize, Serialize};
+
+const MODAL_CONFIG: modal::Config<Id> = modal::Config {
+    backdrop_id: Id::ImportModalBackdrop,
+    close_button_id: Id::ImportModalClose,
+};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum State {
+    #[default]
+    Closed,
+    Open(Model),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    language: Language,
+    existing_files: Vec<File>,
+    imported: Imported,
+    resolution: ConflictResolution,
+}
+
+impl Model {
+    fn merged_files(&self) -> Vec<File> {
+        import::merge(
+            self.existing_files.clone(),
+            self.imported.files.clone(),
+            self.resolution,
+            self.language,
+        )
+    }
+
+    fn conflict_count(&self) -> usize {
+        self.imported
+            .files
+            .iter()
+            .filter(|file| import::has_conflict(&self.existing_files, file))
+            .count()
+    }
+}
+
+#[derive(strum_macros::Display, poly_macro::DomId)]
+#[strum(serialize_all = "kebab-case")]
+enum Id {
+    ImportFilesInput,
+    ImportFolderInput,
+    ConflictResolutionSelect,
+    ConfirmImportButton,
+    ImportModalBackdrop,
+    ImportModalClose,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub enum Msg {
+    ConflictResolutionChanged(Capture<ConflictResolution>),
+    ImportClicked,
+    Close,
+}
+
+pub fn subscriptions<ToParentMsg, ParentMsg>(
+    state: &State,
+    to_parent_msg: ToParentMsg,
+) -> Subscription<ParentMsg>
+where
+    ParentMsg: Clone,
+    ToParentMsg: Fn(Msg) -> ParentMsg,
+{
+    match state {
+        State::Open(_) => {
+            // fmt
+            subscription::batch(vec![
+                event_listener::on_change(Id::ConflictResolutionSelect, |captured| {
+                    to_parent_msg(Msg::ConflictResolutionChanged(captured))
+                }),
+                event_listener::on_click(
+                    Id::ConfirmImportButton,
+                    to_parent_msg(Msg::ImportClicked),
+                ),
+                modal::subscriptions(&MODAL_CONFIG, to_parent_msg(Msg::Close)),
+            ])
+        }
+
+        State::Closed => subscription::none(),
+    }
+}
+
+pub enum Event {
+    None,
+    FilesImported(Vec<File>),
+    ModalClosed,
+}
+
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
+    match msg {
+        Msg::ConflictResolutionChanged(captured) => {
+            if let State::Open(model) = state {
+                model.resolution = captured.value();
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::ImportClicked => {
+            if let State::Open(model) = state {
+                let files = model.merged_files();
+
+                if model.imported.files.is_empty() || validate::validate_files(&files).is_err() {
+                    return Ok(Event::None);
+                }
+
+                *state = State::default();
+                Ok(Event::FilesImported(files))
+            } else {
+                Ok(Event::None)
+            }
+        }
+
+        Msg::Close => {
+            *state = State::default();
+            Ok(Event::ModalClosed)
+        }
+    }
+}
+
+pub fn open<ParentMsg>(
+    state: &mut State,
+    language: Language,
+    existing_files: Vec<File>,
+) -> Effect<ParentMsg> {
+    *state = State::Open(Model {
+        language,
+        existing_files,
+        imported: Imported::default(),
+        resolution: ConflictResolution::default(),
+    });
+
+    effect::none()
+}
+
+// Files can be dropped on the page while the modal is closed, in that case the modal is opened
+pub fn add_imported(
+    state: &mut State,
+    language: Language,
+    existing_files: Vec<File>,
+    imported: Imported,
+) {
+    if imported.files.is_empty() && imported.skipped.is_empty() {
+        return;
+    }
+
+    if let State::Closed = state {
+        *state = State::Open(Model {
+            language,
+            existing_files,
+            imported: Imported::default(),
+            resolution: ConflictResolution::default(),
+        });
+    }
+
+    if let State::Open(model) = state {
+        for file in imported.files {
+            model.imported.files.retain(|f| f.name != file.name);
+            model.imported.files.push(file);
+        }
+
+        model.imported.skipped.extend(imported.skipped);
+    }
+}
+
+pub fn view(state: &State) -> maud::Markup {
+    if let State::Open(model) = state {
+        modal::view(view_modal(model), &MODAL_CONFIG)
+    } else {
+        html! {}
+    }
+}
+
+fn view_modal(model: &Model) -> maud::Markup {
+    let violations = validate::validate_files(&model.merged_files())
+        .err()
+        .unwrap_or_default();
+
+    let can_import = !model.imported.files.is_empty() && violations.is_empty();
+
+    html! {
+        div class="text-center" {
+            h3 class="text-lg leading-6 font-medium text-gray-900" {
+                "Import Files"
+            }
+        }
+
+        div class="mt-4 text-sm text-gray-500" {
+            "Select files, a folder or a zip/tar archive. Files can also be dropped anywhere on the page."
+        }
+
+        div class="flex mt-4" {
+            label class="flex-1 w-full inline-flex items-center justify-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 shadow-sm hover:bg-gray-50 cursor-pointer" {
+                "Choose files"
+                input id=(Id::ImportFilesInput) class="sr-only" type="file" multiple data-import-files;
+            }
+            label class="ml-4 flex-1 w-full inline-flex items-center justify-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 shadow-sm hover:bg-gray-50 cursor-pointer" {
+                "Choose folder"
+                input id=(Id::ImportFolderInput) class="sr-only" type="file" webkitdirectory data-import-files;
+            }
+        }
+
+        @if !model.imported.files.is_empty() {
+            ul class="mt-4 divide-y divide-gray-200 border-t border-b border-gray-200 text-sm" {
+                @for file in &model.imported.files {
+                    li class="flex justify-between py-2" {
+                        span class="font-mono truncate" { (file.name) }
+                        @if import::has_conflict(&model.existing_files, file) {
+                            span class="ml-2 text-yellow-600" { "Conflict" }
+                        } @else {
+                            span class="ml-2 text-gray-500" { "New" }
+                        }
+                    }
+                }
+            }
+        }
+
+        @if !model.imported.skipped.is_empty() {
+            ul class="mt-2 text-sm text-gray-500 list-disc list-inside" {
+                @for skipped in &model.imported.skipped {
+                    li { "Skipped " (skipped.name) ": " (skipped.error) }
+                }
+            }
+        }
+
+        @if model.conflict_count() > 0 {
+            (dropdown::view(&dropdown::Config{
+                id: Id::ConflictResolutionSelect,
+                title: "When a file already exists",
+                selected_value: &model.resolution,
+                options: dropdown::Options::Ungrouped(vec![
+                    (&ConflictResolution::KeepBoth, &ConflictResolution::KeepBoth.label()),
+                    (&ConflictResolution::Replace, &ConflictResolution::Replace.label()),
+                    (&ConflictResolution::Skip, &ConflictResolution::Skip.label()),
+                ]),
+            }))
+        }
+
+        @if !violations.is_empty() {
+            ul class="mt-2 text-sm text-red-500 list-disc list-inside" {
+                @for violation in &violations {
+                    li { (violation) }
+                }
+            }
+        }
+
+        div class="flex mt-4" {
+            button id=(Id::ConfirmImportButton) class="flex-1 w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2 disabled:opacity-50" type="button" disabled[!can_import] {
+                "Import"
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod file_modal;
+pub mod import_modal;
+pub mod run_options_modal;
 pub mod search_modal;
 pub mod settings_modal;
 pub mod sharing_modal;
@@ -0,0 +1,249 @@
+use crate::error::Error;
+use crate::snippet;
+use crate::snippet::validate;
+use crate::view::modal;
+use maud::html;
+use poly::browser::dom_id::DomId;
+use poly::browser::effect::dom;
+use poly::browser::effect::Effect;
+use poly::browser::subscription;
+use poly::browser::subscription::event_listener;
+use poly::browser::subscription::Subscription;
+use poly::browser::value::Capture;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const MODAL_CONFIG: modal::Config<Id> = modal::Config {
+    backdrop_id: Id::RunOptionsModalBackdrop,
+    close_button_id: Id::RunOptionsModalClose,
+};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum State {
+    #[default]
+    Closed,
+    Open(Model),
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    env: String,
+    args: String,
+    error: Option<String>,
+}
+
+#[derive(strum_macros::Display, poly_macro::DomId)]
+#[strum(serialize_all = "kebab-case")]
+enum Id {
+    EnvInput,
+    ArgsInput,
+    SaveRunOptionsButton,
+    RunOptionsModalBackdrop,
+    RunOptionsModalClose,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub enum Msg {
+    EnvChanged(Capture<String>),
+    ArgsChanged(Capture<String>),
+    SaveClicked,
+    Close,
+}
+
+pub fn subscriptions<ToParentMsg, ParentMsg>(
+    state: &State,
+    to_parent_msg: ToParentMsg,
+) -> Subscription<ParentMsg>
+where
+    ParentMsg: Clone,
+    ToParentMsg: Fn(Msg) -> ParentMsg,
+{
+    match state {
+        State::Open(_) => {
+            // fmt
+            subscription::batch(vec![
+                event_listener::on_input(Id::EnvInput, |captured| {
+                    to_parent_msg(Msg::EnvChanged(captured))
+                }),
+                event_listener::on_input(Id::ArgsInput, |captured| {
+                    to_parent_msg(Msg::ArgsChanged(captured))
+                }),
+                event_listener::on_click(Id::SaveRunOptionsButton, to_parent_msg(Msg::SaveClicked)),
+                modal::subscriptions(&MODAL_CONFIG, to_parent_msg(Msg::Close)),
+            ])
+        }
+
+        State::Closed => subscription::none(),
+    }
+}
+
+pub struct RunOptions {
+    pub env: BTreeMap<String, String>,
+    pub args: Vec<String>,
+}
+
+pub enum Event {
+    None,
+    RunOptionsChanged(RunOptions),
+    ModalClosed,
+}
+
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
+    match msg {
+        Msg::EnvChanged(captured) => {
+            if let State::Open(model) = state {
+                model.env = captured.value();
+                model.error = None;
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::ArgsChanged(captured) => {
+            if let State::Open(model) = state {
+                model.args = captured.value();
+                model.error = None;
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::SaveClicked => {
+            if let State::Open(model) = state {
+                match parse_run_options(model) {
+                    Ok(options) => {
+                        *state = State::default();
+                        Ok(Event::RunOptionsChanged(options))
+                    }
+
+                    Err(err) => {
+                        model.error = Some(err);
+                        Ok(Event::None)
+                    }
+                }
+            } else {
+                Ok(Event::None)
+            }
+        }
+
+        Msg::Close => {
+            *state = State::default();
+            Ok(Event::ModalClosed)
+        }
+    }
+}
+
+pub fn open<ParentMsg>(
+    state: &mut State,
+    env: &BTreeMap<String, String>,
+    args: &[String],
+) -> Effect<ParentMsg> {
+    *state = State::Open(Model {
+        env: snippet::env_lines(env).join("\n"),
+        args: args.join("\n"),
+        error: None,
+    });
+
+    dom::focus_element(Id::ArgsInput)
+}
+
+pub fn view(state: &State) -> maud::Markup {
+    if let State::Open(model) = state {
+        modal::view(view_modal(model), &MODAL_CONFIG)
+    } else {
+        html! {}
+    }
+}
+
+fn view_modal(model: &Model) -> maud::Markup {
+    html! {
+        div class="text-center" {
+            h3 class="text-lg leading-6 font-medium text-gray-900" {
+                "Arguments and Environment"
+            }
+        }
+
+        form class="mt-8" {
+            label class="block text-sm font-medium text-gray-700" for=(Id::ArgsInput) {
+                "Program arguments, one per line"
+            }
+            div class="mt-1" {
+                textarea id=(Id::ArgsInput) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono" rows="4" placeholder="--verbose" {
+                    (model.args)
+                }
+            }
+
+            label class="mt-4 block text-sm font-medium text-gray-700" for=(Id::EnvInput) {
+                "Environment variables, one NAME=value per line"
+            }
+            div class="mt-1" {
+                textarea id=(Id::EnvInput) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono" rows="4" placeholder="LOG_LEVEL=debug" {
+                    (model.env)
+                }
+            }
+            @if let Some(err) = &model.error {
+                p class="mt-2 text-sm text-red-600" {
+                    (err)
+                }
+            }
+        }
+
+        div class="flex mt-4" {
+            button id=(Id::SaveRunOptionsButton) class="flex-1 w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
+                "Save"
+            }
+        }
+    }
+}
+
+fn parse_run_options(model: &Model) -> Result<RunOptions, String> {
+    let env = parse_env(&model.env)?;
+
+    // Empty lines are ignored, an argument can't be empty
+    let args: Vec<String> = model
+        .args
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect();
+
+    let size: usize = env
+        .iter()
+        .map(|(name, value)| name.len() + value.len())
+        .chain(args.iter().map(String::len))
+        .sum();
+
+    if size > validate::MAX_RUN_OPTIONS_SIZE {
+        Err(format!(
+            "Arguments and environment variables are too large: {} (max {})",
+            validate::format_size(size),
+            validate::format_size(validate::MAX_RUN_OPTIONS_SIZE)
+        ))
+    } else {
+        Ok(RunOptions { env, args })
+    }
+}
+
+fn parse_env(text: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut env = BTreeMap::new();
+
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Expected NAME=value: {}", line))?;
+
+        let name = name.trim();
+
+        if !validate::is_valid_env_name(name) {
+            return Err(format!("Invalid environment variable name: {}", name));
+        }
+
+        if env.insert(name.to_string(), value.to_string()).is_some() {
+            return Err(format!("{} is defined more than once", name));
+        }
+    }
+
+    Ok(env)
+}
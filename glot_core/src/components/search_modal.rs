@@ -1,4 +1,5 @@
 use crate::common::keyboard_shortcut::KeyboardShortcut;
+use crate::error::Error;
 use crate::util::user_agent::UserAgent;
 use crate::view::modal;
 use itertools::Itertools;
@@ -148,7 +149,7 @@ pub fn update<ToParentMsg, ParentMsg, Action>(
     state: &mut State<Action>,
     entries: Vec<Entry<Action>>,
     _to_parent_msg: ToParentMsg,
-) -> Result<UpdateData<ParentMsg, Action>, String>
+) -> Result<UpdateData<ParentMsg, Action>, Error>
 where
     ToParentMsg: Fn(Msg) -> ParentMsg,
     Action: Clone + Eq + PartialEq + Hash + Display + EntryExtra,
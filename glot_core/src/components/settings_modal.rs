@@ -1,7 +1,12 @@
 use crate::ace_editor::EditorKeyboardBindings;
 use crate::ace_editor::EditorTheme;
+use crate::error::Error;
+use crate::snippet::command_template;
+use crate::snippet::command_template::Variable;
+use crate::snippet::validate;
 use crate::view::dropdown;
 use crate::view::modal;
+use glot_languages::language::RunInstructions;
 use maud::html;
 use poly::browser::dom_id::DomId;
 use poly::browser::effect::dom;
@@ -30,7 +35,12 @@ pub enum State {
 pub struct Model {
     keyboard_bindings: EditorKeyboardBindings,
     theme: EditorTheme,
+    build_commands: String,
     command: String,
+    timeout: String,
+    default_run_instructions: RunInstructions,
+    template_context: command_template::Context,
+    error: Option<String>,
 }
 
 #[derive(strum_macros::Display, poly_macro::DomId)]
@@ -42,14 +52,20 @@ enum Id {
     SettingsSaveButton,
     SettingsModalBackdrop,
     SettingsModalClose,
+    SettingsBuildCommandsInput,
     SettingsCommandInput,
+    SettingsTimeoutInput,
+    ResetCommandsButton,
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub enum Msg {
     KeyboardBindingsChanged(Capture<EditorKeyboardBindings>),
     EditorThemeChanged(Capture<EditorTheme>),
+    BuildCommandsChanged(Capture<String>),
     CommandChanged(Capture<String>),
+    TimeoutChanged(Capture<String>),
+    ResetCommandsClicked,
     Save,
     Close,
 }
@@ -73,9 +89,19 @@ where
                 event_listener::on_change(Id::Theme, |captured| {
                     to_parent_msg(Msg::EditorThemeChanged(captured))
                 }),
+                event_listener::on_input(Id::SettingsBuildCommandsInput, |captured| {
+                    to_parent_msg(Msg::BuildCommandsChanged(captured))
+                }),
                 event_listener::on_input(Id::SettingsCommandInput, |captured| {
                     to_parent_msg(Msg::CommandChanged(captured))
                 }),
+                event_listener::on_input(Id::SettingsTimeoutInput, |captured| {
+                    to_parent_msg(Msg::TimeoutChanged(captured))
+                }),
+                event_listener::on_click(
+                    Id::ResetCommandsButton,
+                    to_parent_msg(Msg::ResetCommandsClicked),
+                ),
                 event_listener::on_click(Id::SettingsSaveButton, to_parent_msg(Msg::Save)),
                 event_listener::on_submit(Id::SettingsForm, to_parent_msg(Msg::Save)),
                 modal::subscriptions(&MODAL_CONFIG, to_parent_msg(Msg::Close)),
@@ -95,10 +121,11 @@ pub enum Event {
 pub struct Settings {
     pub keyboard_bindings: EditorKeyboardBindings,
     pub theme: EditorTheme,
-    pub command: String,
+    pub run_instructions: RunInstructions,
+    pub timeout: Option<u64>,
 }
 
-pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
     match msg {
         Msg::KeyboardBindingsChanged(captured) => {
             if let State::Open(model) = state {
@@ -116,9 +143,38 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
             Ok(Event::None)
         }
 
+        Msg::BuildCommandsChanged(captured) => {
+            if let State::Open(model) = state {
+                model.build_commands = captured.value();
+                model.error = None;
+            }
+
+            Ok(Event::None)
+        }
+
         Msg::CommandChanged(captured) => {
             if let State::Open(model) = state {
                 model.command = captured.value();
+                model.error = None;
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::TimeoutChanged(captured) => {
+            if let State::Open(model) = state {
+                model.timeout = captured.value();
+                model.error = None;
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::ResetCommandsClicked => {
+            if let State::Open(model) = state {
+                model.build_commands = model.default_run_instructions.build_commands.join("\n");
+                model.command = model.default_run_instructions.run_command.clone();
+                model.error = None;
             }
 
             Ok(Event::None)
@@ -126,10 +182,26 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
 
         Msg::Save => {
             if let State::Open(model) = state {
+                let run_instructions = resolved_run_instructions(model);
+
+                if let Err(err) = command_template::validate_instructions(&run_instructions) {
+                    model.error = Some(err.to_string());
+                    return Ok(Event::None);
+                }
+
+                let timeout = match parse_timeout(&model.timeout) {
+                    Ok(timeout) => timeout,
+                    Err(err) => {
+                        model.error = Some(err);
+                        return Ok(Event::None);
+                    }
+                };
+
                 let settings = Settings {
                     keyboard_bindings: model.keyboard_bindings.clone(),
                     theme: model.theme.clone(),
-                    command: model.command.clone(),
+                    run_instructions,
+                    timeout,
                 };
                 *state = State::default();
                 Ok(Event::SettingsChanged(settings))
@@ -145,11 +217,25 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
     }
 }
 
-pub fn open<ParentMsg>(state: &mut State, settings: Settings) -> Effect<ParentMsg> {
+// The current files, used for the default commands and to preview templated commands
+pub struct Context {
+    pub default_run_instructions: RunInstructions,
+    pub template_context: command_template::Context,
+}
+
+pub fn open<ParentMsg>(state: &mut State, settings: Settings, ctx: Context) -> Effect<ParentMsg> {
     *state = State::Open(Model {
         keyboard_bindings: settings.keyboard_bindings,
         theme: settings.theme,
-        command: settings.command,
+        build_commands: settings.run_instructions.build_commands.join("\n"),
+        command: settings.run_instructions.run_command,
+        timeout: settings
+            .timeout
+            .map(|timeout| timeout.to_string())
+            .unwrap_or_default(),
+        default_run_instructions: ctx.default_run_instructions,
+        template_context: ctx.template_context,
+        error: None,
     });
 
     dom::focus_element(Id::KeyboardBindings)
@@ -245,13 +331,47 @@ fn view_modal(model: &Model) -> maud::Markup {
                 }
             }
 
+            label class="mt-4 block text-sm font-medium text-gray-700" for=(Id::SettingsBuildCommandsInput) {
+                "Build commands, one per line"
+            }
+            div class="mt-1" {
+                textarea id=(Id::SettingsBuildCommandsInput) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono sm:text-sm" rows="3" {
+                    (model.build_commands)
+                }
+            }
+
             label class="mt-4 block text-sm font-medium text-gray-700" for=(Id::SettingsCommandInput) {
                 "Run command"
             }
             div class="mt-1" {
-                input id=(Id::SettingsCommandInput) value=(model.command) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 sm:text-sm" type="text";
+                input id=(Id::SettingsCommandInput) value=(model.command) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono sm:text-sm" type="text";
+            }
+
+            p class="mt-2 text-sm text-gray-500" {
+                "Commands can refer to the files with "
+                @for (index, variable) in Variable::all().iter().enumerate() {
+                    @if index > 0 {
+                        ", "
+                    }
+                    code title=(variable.description()) { "{" (variable.name()) "}" }
+                }
+            }
+
+            label class="mt-4 block text-sm font-medium text-gray-700" for=(Id::SettingsTimeoutInput) {
+                "Time limit in seconds"
+            }
+            div class="mt-1" {
+                input id=(Id::SettingsTimeoutInput) value=(model.timeout) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 sm:text-sm" type="number" min="1" max=(validate::MAX_TIMEOUT) placeholder="Default";
             }
 
+            @if let Some(err) = &model.error {
+                p class="mt-2 text-sm text-red-600" {
+                    (err)
+                }
+            }
+
+            (view_preview(model))
+
             div class="flex mt-8" {
                 button id=(Id::SettingsSaveButton) class="flex-1 w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
                     "Save"
@@ -260,3 +380,84 @@ fn view_modal(model: &Model) -> maud::Markup {
         }
     }
 }
+
+fn view_preview(model: &Model) -> maud::Markup {
+    let run_instructions = resolved_run_instructions(model);
+    let is_default =
+        is_default_run_instructions(&run_instructions, &model.default_run_instructions);
+    let rendered =
+        command_template::render_instructions(&run_instructions, &model.template_context);
+
+    html! {
+        div class="mt-4" {
+            div class="flex items-center" {
+                span class="text-sm font-medium text-gray-700" {
+                    @if is_default {
+                        "Default commands for the current files"
+                    } @else {
+                        "Custom commands"
+                    }
+                }
+
+                @if !is_default {
+                    button id=(Id::ResetCommandsButton) class="ml-auto text-sm text-indigo-600 hover:text-indigo-800" type="button" {
+                        "Reset to default"
+                    }
+                }
+            }
+
+            pre class="mt-1 rounded-md border border-gray-200 bg-gray-50 px-3 py-2 text-sm overflow-x-auto" {
+                @for command in &rendered.build_commands {
+                    (command) "\n"
+                }
+                (rendered.run_command)
+            }
+        }
+    }
+}
+
+// An empty time limit means the backend default is used
+fn parse_timeout(s: &str) -> Result<Option<u64>, String> {
+    let s = s.trim();
+
+    if s.is_empty() {
+        return Ok(None);
+    }
+
+    s.parse()
+        .ok()
+        .filter(|timeout| validate::is_valid_timeout(*timeout))
+        .map(Some)
+        .ok_or_else(|| {
+            format!(
+                "The time limit must be a number of seconds from 1 to {}",
+                validate::MAX_TIMEOUT
+            )
+        })
+}
+
+// An empty run command means the default commands are used
+fn resolved_run_instructions(model: &Model) -> RunInstructions {
+    if model.command.trim().is_empty() {
+        model.default_run_instructions.clone()
+    } else {
+        RunInstructions {
+            build_commands: model
+                .build_commands
+                .lines()
+                .map(str::trim)
+                .filter(|command| !command.is_empty())
+                .map(|command| command.to_string())
+                .collect(),
+            run_command: model.command.trim().to_string(),
+        }
+    }
+}
+
+pub fn is_default_run_instructions(
+    run_instructions: &RunInstructions,
+    default_run_instructions: &RunInstructions,
+) -> bool {
+    run_instructions.build_commands == default_run_instructions.build_commands
+        && run_instructions.run_command == default_run_instructions.run_command
+}
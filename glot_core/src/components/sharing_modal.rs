@@ -1,4 +1,11 @@
+use crate::common::route;
 use crate::common::route::Route;
+use crate::error::Error;
+use crate::run::RunResult;
+use crate::snippet::html;
+use crate::snippet::markdown;
+use crate::snippet::validate;
+use crate::snippet::validate::Violation;
 use crate::snippet::Snippet;
 use crate::util::remote_data::RemoteData;
 use crate::view::modal;
@@ -35,7 +42,46 @@ pub enum State {
 #[serde(rename_all = "camelCase")]
 pub struct Model {
     snippet_url: Option<String>,
+    violations: Vec<Violation>,
     copy_state: RemoteData<String, ()>,
+    export_copy_state: RemoteData<String, ExportFormat>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Html => "HTML",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown",
+            ExportFormat::Html => "text/html",
+        }
+    }
+
+    fn render(&self, snippet: &Snippet, run_result: Option<&RunResult>) -> String {
+        match self {
+            ExportFormat::Markdown => markdown::render(snippet, run_result),
+            ExportFormat::Html => html::render(snippet, run_result),
+        }
+    }
 }
 
 #[derive(strum_macros::Display, poly_macro::DomId)]
@@ -43,6 +89,10 @@ pub struct Model {
 enum Id {
     SnippetUrlInput,
     CopyUrlButton,
+    CopyMarkdownButton,
+    DownloadMarkdownButton,
+    CopyHtmlButton,
+    DownloadHtmlButton,
     SharingModalCloseButton,
     SharingModalBackdrop,
     SharingModalClose,
@@ -54,6 +104,10 @@ pub enum Msg {
     ClearCopyStateTimeout,
     CopyUrlClicked,
     GotCopyUrlResult(Capture<clipboard::WriteTextResult>),
+    CopyExportClicked(ExportFormat),
+    GotCopyExportResult(ExportFormat, Capture<clipboard::WriteTextResult>),
+    ClearExportCopyStateTimeout,
+    DownloadExportClicked(ExportFormat),
     Close,
 }
 
@@ -73,6 +127,22 @@ where
                     Id::CopyUrlButton,
                     to_parent_msg(Msg::CopyUrlClicked),
                 ),
+                event_listener::on_click_closest(
+                    Id::CopyMarkdownButton,
+                    to_parent_msg(Msg::CopyExportClicked(ExportFormat::Markdown)),
+                ),
+                event_listener::on_click_closest(
+                    Id::DownloadMarkdownButton,
+                    to_parent_msg(Msg::DownloadExportClicked(ExportFormat::Markdown)),
+                ),
+                event_listener::on_click_closest(
+                    Id::CopyHtmlButton,
+                    to_parent_msg(Msg::CopyExportClicked(ExportFormat::Html)),
+                ),
+                event_listener::on_click_closest(
+                    Id::DownloadHtmlButton,
+                    to_parent_msg(Msg::DownloadExportClicked(ExportFormat::Html)),
+                ),
                 event_listener::on_click(Id::SharingModalCloseButton, to_parent_msg(Msg::Close)),
                 modal::subscriptions(&MODAL_CONFIG, to_parent_msg(Msg::Close)),
             ])
@@ -86,11 +156,19 @@ pub struct Context {
     pub snippet: Snippet,
     pub language: Language,
     pub current_url: Url,
+    pub run_result: Option<RunResult>,
 }
 
 pub enum Event {
     None,
     ModalClosed,
+    DownloadRequested(Download),
+}
+
+pub struct Download {
+    pub filename: String,
+    pub mime_type: String,
+    pub content: String,
 }
 
 pub struct UpdateData<ParentMsg> {
@@ -126,15 +204,23 @@ pub fn update<ToParentMsg, ParentMsg>(
     state: &mut State,
     context: Context,
     to_parent_msg: ToParentMsg,
-) -> Result<UpdateData<ParentMsg>, String>
+) -> Result<UpdateData<ParentMsg>, Error>
 where
     ToParentMsg: Fn(Msg) -> ParentMsg,
 {
     match msg {
         Msg::EncodeSnippetUrl => {
             if let State::Open(model) = state {
-                let snippet_url = get_snippet_url(context)?;
-                model.snippet_url = Some(snippet_url);
+                match validate::validate(&context.snippet) {
+                    Ok(()) => {
+                        let snippet_url = get_snippet_url(context)?;
+                        model.snippet_url = Some(snippet_url);
+                    }
+
+                    Err(violations) => {
+                        model.violations = violations;
+                    }
+                }
             }
 
             Ok(UpdateData::none())
@@ -188,6 +274,62 @@ where
             Ok(UpdateData::none())
         }
 
+        // Exports contain the decoded binary files, which are only correct for a valid snippet
+        Msg::CopyExportClicked(_) | Msg::DownloadExportClicked(_)
+            if validate::validate(&context.snippet).is_err() =>
+        {
+            Ok(UpdateData::none())
+        }
+
+        Msg::CopyExportClicked(format) => {
+            let content = format.render(&context.snippet, context.run_result.as_ref());
+
+            let effect = clipboard::write_text(&content, |captured| {
+                to_parent_msg(Msg::GotCopyExportResult(*format, captured))
+            });
+
+            Ok(UpdateData::with_effect(effect))
+        }
+
+        Msg::GotCopyExportResult(format, captured) => {
+            if let State::Open(model) = state {
+                let result = captured.value();
+
+                model.export_copy_state = if result.success {
+                    RemoteData::Success(*format)
+                } else {
+                    RemoteData::Failure(result.error.unwrap_or_default())
+                };
+
+                let effect = browser::set_timeout(
+                    Duration::from_secs(3),
+                    to_parent_msg(Msg::ClearExportCopyStateTimeout),
+                );
+
+                Ok(UpdateData::with_effect(effect))
+            } else {
+                Ok(UpdateData::none())
+            }
+        }
+
+        Msg::ClearExportCopyStateTimeout => {
+            if let State::Open(model) = state {
+                model.export_copy_state = RemoteData::NotAsked;
+            }
+
+            Ok(UpdateData::none())
+        }
+
+        Msg::DownloadExportClicked(format) => {
+            let download = Download {
+                filename: format!("{}.{}", context.snippet.slug(), format.extension()),
+                mime_type: format.mime_type().to_string(),
+                content: format.render(&context.snippet, context.run_result.as_ref()),
+            };
+
+            Ok(UpdateData::with_event(Event::DownloadRequested(download)))
+        }
+
         Msg::Close => {
             *state = State::default();
             Ok(UpdateData::with_event(Event::ModalClosed))
@@ -219,10 +361,11 @@ pub fn view(state: &State) -> maud::Markup {
 }
 
 fn view_modal(model: &Model) -> maud::Markup {
-    let url_max_length = 16000;
+    let url_max_length = route::MAX_URL_LENGTH;
     let maybe_overlay = SnippetUrlOverlay::from_state(model);
     let snippet_url_value = model.snippet_url.clone().unwrap_or_default();
     let url_length = snippet_url_value.len();
+    let can_export = model.violations.is_empty();
 
     html! {
         div class="text-center" {
@@ -256,6 +399,14 @@ fn view_modal(model: &Model) -> maud::Markup {
                 "The snippet is embedded in the url using brotli compression and base62 encoding."
             }
 
+            @if !model.violations.is_empty() {
+                ul class="mt-2 text-sm text-red-500 list-disc list-inside" {
+                    @for violation in &model.violations {
+                        li { (violation) }
+                    }
+                }
+            }
+
             @if url_length > url_max_length {
                 p class="mt-2 text-sm text-red-500" {
                     (format!("{} / {}", url_length, url_max_length))
@@ -267,6 +418,30 @@ fn view_modal(model: &Model) -> maud::Markup {
             }
         }
 
+        div class="mt-6" {
+            span class="block text-sm font-medium leading-6 text-gray-900" {
+                "Export"
+            }
+            (view_export_row(ExportFormat::Markdown, Id::CopyMarkdownButton, Id::DownloadMarkdownButton, can_export))
+            (view_export_row(ExportFormat::Html, Id::CopyHtmlButton, Id::DownloadHtmlButton, can_export))
+
+            @match &model.export_copy_state {
+                RemoteData::Success(format) => {
+                    p class="mt-2 text-sm text-gray-500" {
+                        "Copied " (format.label()) " to clipboard!"
+                    }
+                }
+
+                RemoteData::Failure(err) => {
+                    p class="mt-2 text-sm text-red-500" {
+                        "Failed to copy: " (err)
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
         div class="flex mt-6" {
             button id=(Id::SharingModalCloseButton) class="flex-1 w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
                 "Close"
@@ -275,8 +450,38 @@ fn view_modal(model: &Model) -> maud::Markup {
     }
 }
 
+fn view_export_row(
+    format: ExportFormat,
+    copy_id: Id,
+    download_id: Id,
+    can_export: bool,
+) -> maud::Markup {
+    html! {
+        div class="mt-2 flex items-center" {
+            span class="flex-1 text-sm text-gray-700" {
+                (format.label())
+            }
+            span class="isolate inline-flex rounded-md shadow-sm" {
+                button id=(copy_id) disabled[!can_export] class="relative inline-flex items-center gap-x-1.5 rounded-l-md px-3 py-2 text-sm font-semibold text-gray-900 ring-1 ring-inset ring-gray-300 hover:bg-gray-50" type="button" {
+                    span class="w-4 h-4" {
+                        (heroicons_maud::clipboard_outline())
+                    }
+                    "Copy"
+                }
+                button id=(download_id) disabled[!can_export] class="relative -ml-px inline-flex items-center gap-x-1.5 rounded-r-md px-3 py-2 text-sm font-semibold text-gray-900 ring-1 ring-inset ring-gray-300 hover:bg-gray-50" type="button" {
+                    span class="w-4 h-4" {
+                        (heroicons_maud::arrow_down_tray_outline())
+                    }
+                    "Download"
+                }
+            }
+        }
+    }
+}
+
 enum SnippetUrlOverlay {
     Encoding,
+    Invalid,
     Copied,
     Failure,
 }
@@ -287,7 +492,9 @@ impl SnippetUrlOverlay {
             RemoteData::Success(_) => Some(Self::Copied),
             RemoteData::Failure(_) => Some(Self::Failure),
             RemoteData::NotAsked => {
-                if model.snippet_url.is_none() {
+                if !model.violations.is_empty() {
+                    Some(Self::Invalid)
+                } else if model.snippet_url.is_none() {
                     Some(Self::Encoding)
                 } else {
                     None
@@ -302,17 +509,15 @@ impl fmt::Display for SnippetUrlOverlay {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Encoding => write!(f, "Encoding snippet..."),
+            Self::Invalid => write!(f, "Snippet can't be shared"),
             Self::Copied => write!(f, "Copied to clipboard!"),
             Self::Failure => write!(f, "Failed to copy url"),
         }
     }
 }
 
-fn get_snippet_url(context: Context) -> Result<String, String> {
-    let encoded_snippet = context
-        .snippet
-        .to_encoded_string()
-        .map_err(|err| format!("Failed to encode snippet: {}", err))?;
+fn get_snippet_url(context: Context) -> Result<String, Error> {
+    let encoded_snippet = context.snippet.to_encoded_string()?;
 
     let route = Route::EditSnippet(context.language, encoded_snippet.clone());
     Ok(route.to_absolute_path(&context.current_url))
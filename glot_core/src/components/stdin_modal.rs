@@ -1,3 +1,9 @@
+use crate::error::Error;
+use crate::snippet::expected_output;
+use crate::snippet::expected_output::Comparison;
+use crate::snippet::expected_output::ExpectedOutput;
+use crate::snippet::StdinInput;
+use crate::view::dropdown;
 use crate::view::modal;
 use maud::html;
 use poly::browser::dom_id::DomId;
@@ -25,14 +31,24 @@ pub enum State {
 #[derive(Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Model {
+    name: String,
     value: String,
+    expected_stdout: String,
+    comparison: Comparison,
+    existing_names: Vec<String>,
+    is_new: bool,
+    error: Option<String>,
+    pattern_error: Option<String>,
 }
 
 #[derive(strum_macros::Display, poly_macro::DomId)]
 #[strum(serialize_all = "kebab-case")]
 enum Id {
+    StdinNameInput,
     StdinInput,
-    ClearStdinButton,
+    ExpectedOutputInput,
+    ComparisonSelect,
+    DeleteStdinButton,
     SaveStdinButton,
     StdinModalBackdrop,
     StdinModalClose,
@@ -40,9 +56,12 @@ enum Id {
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub enum Msg {
+    NameChanged(Capture<String>),
     StdinChanged(Capture<String>),
+    ExpectedOutputChanged(Capture<String>),
+    ComparisonChanged(Capture<Comparison>),
     UpdateStdinClicked,
-    ClearStdinClicked,
+    DeleteStdinClicked,
     Close,
 }
 
@@ -58,12 +77,21 @@ where
         State::Open(_) => {
             // fmt
             subscription::batch(vec![
+                event_listener::on_input(Id::StdinNameInput, |captured| {
+                    to_parent_msg(Msg::NameChanged(captured))
+                }),
                 event_listener::on_input(Id::StdinInput, |captured| {
                     to_parent_msg(Msg::StdinChanged(captured))
                 }),
+                event_listener::on_input(Id::ExpectedOutputInput, |captured| {
+                    to_parent_msg(Msg::ExpectedOutputChanged(captured))
+                }),
+                event_listener::on_change(Id::ComparisonSelect, |captured| {
+                    to_parent_msg(Msg::ComparisonChanged(captured))
+                }),
                 event_listener::on_click(
-                    Id::ClearStdinButton,
-                    to_parent_msg(Msg::ClearStdinClicked),
+                    Id::DeleteStdinButton,
+                    to_parent_msg(Msg::DeleteStdinClicked),
                 ),
                 event_listener::on_click(
                     Id::SaveStdinButton,
@@ -78,12 +106,23 @@ where
 
 pub enum Event {
     None,
-    StdinChanged(String),
+    InputAdded(StdinInput),
+    InputUpdated(StdinInput),
+    InputDeleted,
     ModalClosed,
 }
 
-pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
     match msg {
+        Msg::NameChanged(captured) => {
+            if let State::Open(model) = state {
+                model.name = captured.value();
+                model.error = None;
+            }
+
+            Ok(Event::None)
+        }
+
         Msg::StdinChanged(captured) => {
             if let State::Open(model) = state {
                 model.value = captured.value().trim_start().to_string();
@@ -92,21 +131,70 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
             Ok(Event::None)
         }
 
+        Msg::ExpectedOutputChanged(captured) => {
+            if let State::Open(model) = state {
+                model.expected_stdout = captured.value();
+                model.pattern_error = None;
+            }
+
+            Ok(Event::None)
+        }
+
+        Msg::ComparisonChanged(captured) => {
+            if let State::Open(model) = state {
+                model.comparison = captured.value();
+                model.pattern_error = None;
+            }
+
+            Ok(Event::None)
+        }
+
         Msg::UpdateStdinClicked => {
             if let State::Open(model) = state {
-                let event = Event::StdinChanged(model.value.clone());
-                *state = State::default();
-                Ok(event)
+                let expected_output = to_expected_output(model);
+
+                let pattern_result = expected_output
+                    .as_ref()
+                    .map(expected_output::validate_pattern)
+                    .unwrap_or(Ok(()));
+
+                if let Err(err) = pattern_result {
+                    model.pattern_error = Some(err.to_string());
+                    return Ok(Event::None);
+                }
+
+                match validate_name(model) {
+                    Ok(name) => {
+                        let input = StdinInput {
+                            name,
+                            content: model.value.clone(),
+                            expected_output,
+                        };
+
+                        let event = if model.is_new {
+                            Event::InputAdded(input)
+                        } else {
+                            Event::InputUpdated(input)
+                        };
+
+                        *state = State::default();
+                        Ok(event)
+                    }
+
+                    Err(err) => {
+                        model.error = Some(err);
+                        Ok(Event::None)
+                    }
+                }
             } else {
                 Ok(Event::None)
             }
         }
 
-        Msg::ClearStdinClicked => {
+        Msg::DeleteStdinClicked => {
             if let State::Open(_) = state {
-                let event = Event::StdinChanged("".to_string());
                 *state = State::default();
-                Ok(event)
+                Ok(Event::InputDeleted)
             } else {
                 Ok(Event::None)
             }
@@ -119,9 +207,28 @@ pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
     }
 }
 
-pub fn open<ParentMsg>(state: &mut State, value: &str) -> Effect<ParentMsg> {
+pub struct Context {
+    pub input: StdinInput,
+    // Names of the other inputs
+    pub existing_names: Vec<String>,
+    pub is_new: bool,
+}
+
+pub fn open<ParentMsg>(state: &mut State, ctx: Context) -> Effect<ParentMsg> {
+    let expected_output = ctx.input.expected_output.unwrap_or_else(|| ExpectedOutput {
+        stdout: "".to_string(),
+        comparison: Comparison::default(),
+    });
+
     *state = State::Open(Model {
-        value: value.to_string(),
+        name: ctx.input.name,
+        value: ctx.input.content,
+        expected_stdout: expected_output.stdout,
+        comparison: expected_output.comparison,
+        existing_names: ctx.existing_names,
+        is_new: ctx.is_new,
+        error: None,
+        pattern_error: None,
     });
 
     dom::focus_element(Id::StdinInput)
@@ -144,23 +251,94 @@ fn view_modal(model: &Model) -> maud::Markup {
         }
 
         form class="mt-8" {
-            label class="block text-sm font-medium text-gray-700" for=(Id::StdinInput) {
+            label class="block text-sm font-medium text-gray-700" for=(Id::StdinNameInput) {
+                "Name"
+            }
+            div class="mt-1" {
+                input id=(Id::StdinNameInput) value=(model.name) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 sm:text-sm" type="text";
+            }
+            @if let Some(err) = &model.error {
+                p class="mt-2 text-sm text-red-600" {
+                    (err)
+                }
+            }
+
+            label class="mt-4 block text-sm font-medium text-gray-700" for=(Id::StdinInput) {
                 "Data will be sent to stdin of the program"
             }
             div class="mt-1" {
-                textarea id=(Id::StdinInput) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono" rows="8" {
+                textarea id=(Id::StdinInput) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono" rows="6" {
                     (model.value)
                 }
             }
+
+            label class="mt-4 block text-sm font-medium text-gray-700" for=(Id::ExpectedOutputInput) {
+                "Expected output (optional)"
+            }
+            p class="text-sm text-gray-500" {
+                "The output is checked against this after each run"
+            }
+            div class="mt-1" {
+                textarea id=(Id::ExpectedOutputInput) class="block w-full rounded-md border-gray-300 shadow-sm focus:border-indigo-500 focus:ring-indigo-500 font-mono" rows="4" {
+                    (model.expected_stdout)
+                }
+            }
+            @if let Some(err) = &model.pattern_error {
+                p class="mt-2 text-sm text-red-600" {
+                    (err)
+                }
+            }
+
+            (dropdown::view(&dropdown::Config{
+                id: Id::ComparisonSelect,
+                title: "Comparison",
+                selected_value: &model.comparison,
+                options: dropdown::Options::Ungrouped(
+                    Comparison::all()
+                        .iter()
+                        .map(|comparison| (comparison, comparison.label()))
+                        .collect()
+                ),
+            }))
         }
 
         div class="flex mt-4" {
-            button id=(Id::ClearStdinButton) class="flex-1 w-full inline-flex items-center justify-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 shadow-sm hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
-                "Clear"
+            @if !model.is_new {
+                button id=(Id::DeleteStdinButton) class="mr-4 flex-1 w-full inline-flex items-center justify-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 shadow-sm hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
+                    "Delete"
+                }
             }
-            button id=(Id::SaveStdinButton) class="ml-4 flex-1 w-full w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
-                "Update"
+            button id=(Id::SaveStdinButton) class="flex-1 w-full w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
+                @if model.is_new {
+                    "Add"
+                } @else {
+                    "Update"
+                }
             }
         }
     }
 }
+
+// An empty expected output means the output isn't checked
+fn to_expected_output(model: &Model) -> Option<ExpectedOutput> {
+    if model.expected_stdout.is_empty() {
+        None
+    } else {
+        Some(ExpectedOutput {
+            stdout: model.expected_stdout.clone(),
+            comparison: model.comparison,
+        })
+    }
+}
+
+fn validate_name(model: &Model) -> Result<String, String> {
+    let name = model.name.trim();
+
+    if name.is_empty() {
+        Err("Name cannot be empty".to_string())
+    } else if model.existing_names.iter().any(|existing| existing == name) {
+        Err("Name is already used by another input".to_string())
+    } else {
+        Ok(name.to_string())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::Error;
+use crate::snippet::validate;
 use crate::view::modal;
 use maud::html;
 use poly::browser::dom_id::DomId;
@@ -80,7 +82,7 @@ pub enum Event {
     ModalClosed,
 }
 
-pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
     match msg {
         Msg::TitleChanged(captured) => {
             if let State::Open(model) = state {
@@ -177,15 +179,18 @@ fn view_modal(model: &Model) -> maud::Markup {
 }
 
 fn validate_title(title: &str) -> Result<(), String> {
-    let max_length = 50;
+    let max_length = validate::MAX_TITLE_LENGTH;
+    let length = title.chars().count();
 
     if title.is_empty() {
         Err("Title cannot be empty".to_string())
-    } else if title.len() > max_length {
+    } else if length > max_length {
         Err(format!(
             "Title is {} character(s) too long",
-            title.len() - max_length
+            length - max_length
         ))
+    } else if title.chars().any(char::is_control) {
+        Err("Title cannot contain control characters".to_string())
     } else {
         Ok(())
     }
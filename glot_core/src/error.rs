@@ -0,0 +1,169 @@
+use crate::snippet::validate;
+use crate::snippet::validate::Violation;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Error {
+    EncodeSnippet(EncodeError),
+    DecodeSnippet(DecodeError),
+    InvalidRoute(String),
+    Run(RunError),
+    Import(ImportError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EncodeSnippet(err) => write!(f, "Failed to encode snippet: {}", err),
+            Error::DecodeSnippet(err) => write!(f, "Failed to decode snippet: {}", err),
+            Error::InvalidRoute(path) => write!(f, "Invalid route: {}", path),
+            Error::Run(err) => write!(f, "Failed to run code: {}", err),
+            Error::Import(err) => write!(f, "Failed to import files: {}", err),
+        }
+    }
+}
+
+impl From<EncodeError> for Error {
+    fn from(err: EncodeError) -> Self {
+        Error::EncodeSnippet(err)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Error::DecodeSnippet(err)
+    }
+}
+
+impl From<RunError> for Error {
+    fn from(err: RunError) -> Self {
+        Error::Run(err)
+    }
+}
+
+impl From<ImportError> for Error {
+    fn from(err: ImportError) -> Self {
+        Error::Import(err)
+    }
+}
+
+impl From<Error> for String {
+    fn from(err: Error) -> Self {
+        err.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EncodeError {
+    Serialize(String),
+    Compress(String),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::Serialize(err) => write!(f, "Failed to serialize: {}", err),
+            EncodeError::Compress(err) => write!(f, "Failed to compress: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DecodeError {
+    Envelope(String),
+    Decode(String),
+    Decompress(String),
+    Deserialize(String),
+    Migrate(String),
+}
+
+impl DecodeError {
+    pub fn stage(&self) -> &'static str {
+        match self {
+            DecodeError::Envelope(_) => "Reading the snippet header",
+            DecodeError::Decode(_) => "Base62 decoding",
+            DecodeError::Decompress(_) => "Brotli decompression",
+            DecodeError::Deserialize(_) => "Parsing the snippet",
+            DecodeError::Migrate(_) => "Upgrading the snippet format",
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Envelope(err) => write!(f, "Invalid envelope: {}", err),
+            DecodeError::Decode(err) => write!(f, "Failed to decode: {}", err),
+            DecodeError::Decompress(err) => write!(f, "Failed to decompress: {}", err),
+            DecodeError::Deserialize(err) => write!(f, "Failed to deserialize: {}", err),
+            DecodeError::Migrate(err) => write!(f, "Failed to migrate: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RunError {
+    Transport(String),
+    InvalidResponse(String),
+    InvalidSnippet(Vec<Violation>),
+    Cancelled,
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunError::Transport(message) => write!(f, "{}", message),
+            RunError::InvalidResponse(err) => write!(f, "Invalid response: {}", err),
+            RunError::InvalidSnippet(violations) => {
+                write!(f, "Invalid snippet: {}", format_violations(violations))
+            }
+            RunError::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportError {
+    InvalidMessage(String),
+    InvalidArchive(String),
+    UnsupportedCompression(u16),
+    TooLarge { size: usize, max: usize },
+    TooManyFiles { max: usize },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::InvalidMessage(err) => write!(f, "Invalid message: {}", err),
+            ImportError::InvalidArchive(err) => write!(f, "Invalid archive: {}", err),
+            ImportError::UnsupportedCompression(method) => {
+                write!(f, "Unsupported compression method: {}", method)
+            }
+            ImportError::TooLarge { size, max } => {
+                write!(
+                    f,
+                    "File is too large: {} (max {})",
+                    validate::format_size(*size),
+                    validate::format_size(*max)
+                )
+            }
+            ImportError::TooManyFiles { max } => {
+                write!(f, "Too many files, only the first {} are imported", max)
+            }
+        }
+    }
+}
+
+fn format_violations(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .join(", ")
+}
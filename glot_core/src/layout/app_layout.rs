@@ -1,4 +1,5 @@
 use crate::common::route::Route;
+use crate::error::Error;
 use crate::view::svg;
 use maud::html;
 use maud::Markup;
@@ -85,7 +86,7 @@ pub enum Event {
     OpenSearch,
 }
 
-pub fn update(msg: &Msg, state: &mut State) -> Result<Event, String> {
+pub fn update(msg: &Msg, state: &mut State) -> Result<Event, Error> {
     match msg {
         Msg::OpenSidebarClicked => {
             *state = State::Open;
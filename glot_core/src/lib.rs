@@ -1,6 +1,7 @@
 pub mod ace_editor;
 pub mod common;
 pub mod components;
+pub mod error;
 pub mod layout;
 pub mod page;
 pub mod run;
@@ -0,0 +1,502 @@
+use crate::common::browser_context::BrowserContext;
+use crate::common::quick_action;
+use crate::common::quick_action::LanguageQuickAction;
+use crate::common::route::Route;
+use crate::components::search_modal;
+use crate::error::DecodeError;
+use crate::error::Error;
+use crate::layout::app_layout;
+use crate::snippet;
+use crate::snippet::diff;
+use crate::snippet::diff::Change;
+use crate::snippet::diff::FileDiff;
+use crate::snippet::diff::FileStatus;
+use crate::snippet::diff::SnippetDiff;
+use crate::snippet::Snippet;
+use crate::util::diff::Hunk;
+use crate::util::diff::Line;
+use glot_languages::language::Language;
+use maud::html;
+use maud::Markup;
+use poly::browser::dom_id::DomId;
+use poly::browser::effect;
+use poly::browser::effect::Effect;
+use poly::browser::subscription;
+use poly::browser::subscription::Subscription;
+use poly::page::Page;
+use poly::page::PageMarkup;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    pub browser_ctx: BrowserContext,
+    pub layout_state: app_layout::State,
+    pub search_modal_state: search_modal::State<LanguageQuickAction>,
+    pub comparison: Result<Comparison, DecodeFailure>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    pub old: ComparedSnippet,
+    pub new: ComparedSnippet,
+    pub diff: SnippetDiff,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparedSnippet {
+    pub title: String,
+    pub language: Language,
+    pub encoded_snippet: String,
+}
+
+impl ComparedSnippet {
+    fn new(snippet: &Snippet, encoded_snippet: &str) -> Self {
+        Self {
+            title: snippet.title.clone(),
+            language: snippet.language,
+            encoded_snippet: encoded_snippet.to_string(),
+        }
+    }
+
+    fn path(&self) -> String {
+        Route::EditSnippet(self.language, self.encoded_snippet.clone()).to_path()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeFailure {
+    pub side: Side,
+    pub error: DecodeError,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Side {
+    Old,
+    New,
+}
+
+impl Side {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Side::Old => "original",
+            Side::New => "changed",
+        }
+    }
+}
+
+pub struct DiffPage {
+    pub browser_ctx: BrowserContext,
+}
+
+impl DiffPage {
+    fn get_comparison(&self) -> Result<Result<Comparison, DecodeFailure>, Error> {
+        match self.browser_ctx.current_route() {
+            Route::DiffSnippet(old, new) => Ok(compare_encoded(&old, &new)),
+
+            _ => Err(Error::InvalidRoute(
+                self.browser_ctx.current_url.path().to_string(),
+            )),
+        }
+    }
+}
+
+fn compare_encoded(old: &str, new: &str) -> Result<Comparison, DecodeFailure> {
+    let decode = |side: Side, encoded: &str| {
+        Snippet::from_encoded_string(encoded).map_err(|error| DecodeFailure { side, error })
+    };
+
+    let old_snippet = decode(Side::Old, old)?;
+    let new_snippet = decode(Side::New, new)?;
+
+    Ok(Comparison {
+        old: ComparedSnippet::new(&old_snippet, old),
+        new: ComparedSnippet::new(&new_snippet, new),
+        diff: diff::compare(&old_snippet, &new_snippet),
+    })
+}
+
+impl Page<Model, Msg, Markup> for DiffPage {
+    fn id(&self) -> &'static dyn DomId {
+        &Id::Glot
+    }
+
+    fn init(&self) -> Result<(Model, Effect<Msg>), String> {
+        let model = Model {
+            browser_ctx: self.browser_ctx.clone(),
+            layout_state: Default::default(),
+            search_modal_state: Default::default(),
+            comparison: self.get_comparison()?,
+        };
+
+        Ok((model, effect::none()))
+    }
+
+    fn subscriptions(&self, model: &Model) -> Subscription<Msg> {
+        subscription::batch(vec![
+            app_layout::subscriptions(&model.layout_state, Msg::AppLayoutMsg),
+            search_modal::subscriptions(
+                &model.browser_ctx.user_agent,
+                &model.search_modal_state,
+                Msg::SearchModalMsg,
+            ),
+        ])
+    }
+
+    fn update(&self, msg: &Msg, model: &mut Model) -> Result<Effect<Msg>, String> {
+        match msg {
+            Msg::AppLayoutMsg(child_msg) => {
+                let event = app_layout::update(child_msg, &mut model.layout_state)?;
+                match event {
+                    app_layout::Event::None => Ok(effect::none()),
+                    app_layout::Event::OpenSearch => Ok(model.search_modal_state.open()),
+                }
+            }
+
+            Msg::SearchModalMsg(child_msg) => {
+                let data: search_modal::UpdateData<Msg, LanguageQuickAction> =
+                    search_modal::update(
+                        child_msg,
+                        &mut model.search_modal_state,
+                        quick_action::language_entries(),
+                        Msg::SearchModalMsg,
+                    )?;
+
+                let effect = data
+                    .action
+                    .map(|entry| entry.perform_action(&model.browser_ctx.current_url))
+                    .unwrap_or_else(effect::none);
+
+                Ok(effect::batch(vec![effect, data.effect]))
+            }
+        }
+    }
+
+    fn view(&self, model: &Model) -> PageMarkup<Markup> {
+        PageMarkup {
+            head: view_head(model),
+            body: view_body(model),
+        }
+    }
+
+    fn render(&self, markup: Markup) -> String {
+        markup.into_string()
+    }
+
+    fn render_page(&self, markup: PageMarkup<Markup>) -> String {
+        app_layout::render_page(markup)
+    }
+}
+
+#[derive(strum_macros::Display, poly_macro::DomId)]
+#[strum(serialize_all = "kebab-case")]
+enum Id {
+    Glot,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Msg {
+    AppLayoutMsg(app_layout::Msg),
+    SearchModalMsg(search_modal::Msg),
+}
+
+fn view_head(model: &Model) -> maud::Markup {
+    let title = match &model.comparison {
+        Ok(comparison) => format!("Compare - {}", comparison.new.title),
+        Err(_) => "Compare snippets".to_string(),
+    };
+
+    html! {
+        title { (title) }
+        meta name="viewport" content="width=device-width, initial-scale=1";
+        link rel="stylesheet" href="/static/app.css?hash=checksum";
+        script defer type="module" src="/sw.js?hash=checksum" {}
+        script defer type="module" src="/static/app.js?hash=checksum" {}
+    }
+}
+
+fn view_body(model: &Model) -> maud::Markup {
+    html! {
+        div id=(Id::Glot) class="h-full" {
+            (app_layout::app_shell(
+                view_content(model),
+                None,
+                &model.layout_state,
+                &model.browser_ctx.current_route(),
+            ))
+
+            div class="search-wrapper" {
+                (search_modal::view(&model.browser_ctx.user_agent, &model.search_modal_state))
+            }
+        }
+    }
+}
+
+fn view_content(model: &Model) -> Markup {
+    html! {
+        div class="pt-6 pb-12 max-w-7xl mx-auto px-4 sm:px-6 md:px-8" {
+            @match &model.comparison {
+                Ok(comparison) => (view_comparison(comparison)),
+                Err(failure) => (view_decode_failure(failure)),
+            }
+        }
+    }
+}
+
+fn view_decode_failure(failure: &DecodeFailure) -> Markup {
+    html! {
+        div class="border border-gray-400 shadow-lg bg-white px-6 py-5" {
+            h2 class="text-lg font-semibold text-gray-900" {
+                "The " (failure.side.label()) " snippet could not be opened"
+            }
+            p class="mt-2 text-sm text-gray-700" {
+                "Both snippets are stored in the link itself, so a link that has been truncated or corrupted can't be compared. Make sure the whole link was copied."
+            }
+
+            dl class="mt-4 text-sm" {
+                dt class="font-medium text-gray-900" { "Failed at" }
+                dd class="mt-1 text-gray-700" { (failure.error.stage()) }
+                dt class="mt-2 font-medium text-gray-900" { "Details" }
+                dd class="mt-1 text-gray-700 font-mono break-all" { (failure.error) }
+            }
+        }
+    }
+}
+
+fn view_comparison(comparison: &Comparison) -> Markup {
+    let diff = &comparison.diff;
+
+    html! {
+        div class="flex flex-wrap items-baseline justify-between gap-4" {
+            h1 class="text-2xl font-semibold text-gray-900" {
+                (comparison.new.title)
+            }
+
+            div class="flex gap-3 text-sm" {
+                a href=(comparison.old.path()) class="inline-flex items-center rounded-md border border-gray-300 bg-white px-3 py-1.5 font-medium text-gray-700 shadow-sm hover:bg-gray-50" {
+                    "Open original"
+                }
+                a href=(comparison.new.path()) class="inline-flex items-center rounded-md border border-transparent bg-indigo-600 px-3 py-1.5 font-medium text-white shadow-sm hover:bg-indigo-700" {
+                    "Open changed"
+                }
+            }
+        }
+
+        @if diff.is_empty() {
+            p class="mt-6 text-sm text-gray-700" {
+                "The snippets are identical."
+            }
+        } @else {
+            (view_metadata_changes(diff))
+
+            @for input in &diff.inputs {
+                (view_input(input))
+            }
+
+            @for file in &diff.files {
+                (view_file(file))
+            }
+        }
+    }
+}
+
+fn view_metadata_changes(diff: &SnippetDiff) -> Markup {
+    let language = diff.language.as_ref().map(|change| Change {
+        old: change.old.config().name(),
+        new: change.new.config().name(),
+    });
+
+    let command = diff.command.as_ref().map(|change| Change {
+        old: command_label(&change.old),
+        new: command_label(&change.new),
+    });
+
+    let env = diff.env.as_ref().map(|change| Change {
+        old: env_label(&change.old),
+        new: env_label(&change.new),
+    });
+
+    let args = diff.args.as_ref().map(|change| Change {
+        old: args_label(&change.old),
+        new: args_label(&change.new),
+    });
+
+    let timeout = diff.timeout.as_ref().map(|change| Change {
+        old: timeout_label(&change.old),
+        new: timeout_label(&change.new),
+    });
+
+    let changes: Vec<(&str, Change<String>)> = [
+        ("Title", diff.title.clone()),
+        ("Language", language),
+        ("Commands", command),
+        ("Main file", diff.main_file.clone()),
+        ("Environment", env),
+        ("Arguments", args),
+        ("Time limit", timeout),
+    ]
+    .into_iter()
+    .filter_map(|(label, change)| change.map(|change| (label, change)))
+    .collect();
+
+    html! {
+        @if !changes.is_empty() {
+            dl class="mt-6 border border-gray-400 bg-white text-sm divide-y divide-gray-200" {
+                @for (label, change) in changes {
+                    div class="px-4 py-2 sm:grid sm:grid-cols-5 sm:gap-4" {
+                        dt class="font-medium text-gray-900" { (label) }
+                        dd class="mt-1 sm:mt-0 sm:col-span-4 font-mono break-all" {
+                            del class="text-red-700" { (change.old) }
+                            span class="mx-2 text-gray-400" { "→" }
+                            ins class="text-green-700 no-underline" { (change.new) }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn command_label(command: &Option<String>) -> String {
+    command
+        .clone()
+        .unwrap_or_else(|| "Default commands".to_string())
+}
+
+fn env_label(env: &BTreeMap<String, String>) -> String {
+    if env.is_empty() {
+        "None".to_string()
+    } else {
+        snippet::env_lines(env).join(" ")
+    }
+}
+
+fn args_label(args: &[String]) -> String {
+    if args.is_empty() {
+        "None".to_string()
+    } else {
+        args.join(" ")
+    }
+}
+
+fn timeout_label(timeout: &Option<u64>) -> String {
+    timeout
+        .map(|timeout| format!("{} seconds", timeout))
+        .unwrap_or_else(|| "Default".to_string())
+}
+
+fn view_file(file: &FileDiff) -> Markup {
+    html! {
+        @match file.status {
+            FileStatus::Unchanged => {
+                div class="mt-6 border border-gray-400 px-4 py-1 text-sm text-slate-500 bg-gray-50" {
+                    span class="font-mono font-bold" { (file.name()) }
+                    span class="ml-2" { "Unchanged" }
+                }
+            }
+
+            status if file.binary => {
+                (view_section(&file.name(), Some(status), view_note(binary_note(status))))
+            }
+
+            status if file.hunks.is_empty() => {
+                (view_section(&file.name(), Some(status), view_note("Content unchanged")))
+            }
+
+            status => (view_section(&file.name(), Some(status), view_hunks(&file.hunks))),
+        }
+    }
+}
+
+// Unchanged inputs are left out, they are less interesting than unchanged files
+fn view_input(input: &FileDiff) -> Markup {
+    let name = format!("Stdin: {}", input.name());
+
+    html! {
+        @match input.status {
+            FileStatus::Unchanged => {}
+
+            status if input.hunks.is_empty() => {
+                (view_section(&name, Some(status), view_note("Content unchanged")))
+            }
+
+            status => (view_section(&name, Some(status), view_hunks(&input.hunks))),
+        }
+    }
+}
+
+fn binary_note(status: FileStatus) -> &'static str {
+    match status {
+        FileStatus::Added => "Binary file added",
+        FileStatus::Removed => "Binary file removed",
+        FileStatus::Renamed => "Binary content unchanged",
+        FileStatus::Modified | FileStatus::Unchanged => "Binary content changed",
+    }
+}
+
+fn view_section(name: &str, status: Option<FileStatus>, content: Markup) -> Markup {
+    html! {
+        div class="mt-6 border border-gray-400" {
+            div class="flex items-center px-4 py-1 border-b border-gray-400 text-sm text-slate-700 font-bold bg-blue-400" {
+                pre { (name) }
+
+                @if let Some(status) = status {
+                    span class=(format!("ml-3 rounded px-2 text-xs font-medium {}", status_class(status))) {
+                        (status.label())
+                    }
+                }
+            }
+
+            (content)
+        }
+    }
+}
+
+fn view_note(note: &str) -> Markup {
+    html! {
+        p class="px-4 py-2 text-sm text-gray-500" {
+            (note)
+        }
+    }
+}
+
+fn view_hunks(hunks: &[Hunk]) -> Markup {
+    html! {
+        @for hunk in hunks {
+            div class="overflow-x-auto text-sm" {
+                pre class="px-4 py-1 bg-gray-100 text-gray-500" { (hunk) }
+                @for line in &hunk.lines {
+                    (view_line(line))
+                }
+            }
+        }
+    }
+}
+
+fn view_line(line: &Line) -> Markup {
+    let (prefix, class, text) = match line {
+        Line::Context(text) => (" ", "bg-white", text),
+        Line::Added(text) => ("+", "bg-green-50 text-green-900", text),
+        Line::Removed(text) => ("-", "bg-red-50 text-red-900", text),
+    };
+
+    html! {
+        pre class=(format!("px-4 {}", class)) { (prefix) (text) }
+    }
+}
+
+fn status_class(status: FileStatus) -> &'static str {
+    match status {
+        FileStatus::Added => "bg-green-100 text-green-800",
+        FileStatus::Removed => "bg-red-100 text-red-800",
+        FileStatus::Renamed => "bg-yellow-100 text-yellow-800",
+        FileStatus::Modified => "bg-indigo-100 text-indigo-800",
+        FileStatus::Unchanged => "bg-gray-100 text-gray-800",
+    }
+}
@@ -1,3 +1,4 @@
+pub mod diff_page;
 pub mod home_page;
 pub mod not_found_page;
 pub mod snippet_page;
@@ -2,27 +2,50 @@ use crate::ace_editor::EditorKeyboardBindings;
 use crate::ace_editor::EditorTheme;
 use crate::common::browser_context::BrowserContext;
 use crate::common::keyboard_shortcut::KeyboardShortcut;
+use crate::common::route;
 use crate::common::route::Route;
 use crate::components::file_modal;
+use crate::components::import_modal;
+use crate::components::run_options_modal;
 use crate::components::search_modal;
 use crate::components::settings_modal;
 use crate::components::sharing_modal;
 use crate::components::stdin_modal;
 use crate::components::title_modal;
+use crate::error::DecodeError;
+use crate::error::Error;
+use crate::error::RunError;
 use crate::layout::app_layout;
-use crate::run::FailedRunResult;
+use crate::run::local;
+use crate::run::stream::RunEvent;
+use crate::run::stream::RunStream;
+use crate::run::RunId;
 use crate::run::RunOutcome;
 use crate::run::RunRequest;
 use crate::run::RunRequestPayload;
 use crate::run::RunResult;
+use crate::snippet;
+use crate::snippet::archive;
+use crate::snippet::command_template;
+use crate::snippet::expected_output::ExpectedOutput;
+use crate::snippet::file_tree;
+use crate::snippet::fingerprint::Fingerprint;
+use crate::snippet::import;
+use crate::snippet::path;
+use crate::snippet::recover;
+use crate::snippet::recover::PartialSnippet;
+use crate::snippet::validate;
+use crate::snippet::validate::Violation;
 use crate::snippet::File;
 use crate::snippet::Snippet;
+use crate::snippet::StdinInput;
 use crate::util::remote_data::RemoteData;
 use crate::util::select_list::SelectList;
 use glot_languages::language::Language;
 use glot_languages::language::RunInstructions;
 use maud::html;
 use maud::Markup;
+use output_panel::InputResult;
 use poly::browser::dom_id::DomId;
 use poly::browser::effect;
 use poly::browser::effect::console;
@@ -43,13 +66,18 @@ use poly::page::PageMarkup;
 use quick_action::QuickAction;
 use serde::{Deserialize, Serialize};
 use std::cmp::max;
-use std::path::PathBuf;
+use std::cmp::min;
+use std::collections::BTreeMap;
 use url::Url;
 
+pub mod binary_preview;
 pub mod output_panel;
 pub mod quick_action;
 
 const MIN_EDITOR_HEIGHT: u64 = 300;
+const MAX_CONTENT_WIDTH: u64 = 1280;
+const TAB_BAR_BUTTONS_WIDTH: u64 = 150;
+const AVERAGE_TAB_WIDTH: u64 = 120;
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -57,21 +85,46 @@ pub struct Model {
     pub browser_ctx: BrowserContext,
     pub language: Language,
     pub files: SelectList<File>,
+    pub collapsed_directories: Vec<String>,
+    pub show_hidden_files: bool,
     pub title: String,
     pub editor_keyboard_bindings: EditorKeyboardBindings,
     pub editor_theme: EditorTheme,
-    pub stdin: Option<String>,
-    pub custom_command: Option<String>,
+    pub inputs: Vec<StdinInput>,
+    pub selected_input: usize,
+    pub custom_run_instructions: Option<RunInstructions>,
+    pub env: BTreeMap<String, String>,
+    pub args: Vec<String>,
+    pub timeout: Option<u64>,
     pub layout_state: app_layout::State,
-    pub run_result: RemoteData<FailedRunResult, RunResult>,
-    pub language_version_result: RemoteData<FailedRunResult, RunResult>,
+    pub run_id: RunId,
+    pub run_result: RemoteData<RunError, RunResult>,
+    // Fingerprint of the snippet that produced the run result
+    pub run_fingerprint: Option<Fingerprint>,
+    pub run_stream: RunStream,
+    pub show_raw_output: bool,
+    pub run_expected_output: Option<ExpectedOutput>,
+    pub input_results: Vec<InputResult>,
+    pub language_version_result: RemoteData<RunError, RunResult>,
     pub snippet: Option<Snippet>,
+    pub decode_failure: Option<DecodeFailure>,
+    // Why the last quick action did nothing, cleared on the next edit
+    pub action_error: Option<String>,
     pub search_modal_state: search_modal::State<QuickAction>,
     pub title_modal_state: title_modal::State,
     pub sharing_modal_state: sharing_modal::State,
     pub settings_modal_state: settings_modal::State,
     pub stdin_modal_state: stdin_modal::State,
+    pub run_options_modal_state: run_options_modal::State,
     pub file_modal_state: file_modal::State,
+    pub import_modal_state: import_modal::State,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeFailure {
+    pub error: DecodeError,
+    pub recovered: Option<PartialSnippet>,
 }
 
 #[derive(strum_macros::Display, poly_macro::DomId)]
@@ -81,12 +134,20 @@ enum Id {
     Editor,
     SettingsButton,
     AddFileButton,
+    ImportFilesButton,
     EditFileButton,
     StdinButton,
+    StdinInputSelect,
+    AddStdinInputButton,
+    RunAllInputsButton,
     RunButton,
+    CancelRunButton,
+    RunOptionsButton,
+    RawOutputToggle,
     ShareButton,
     Title,
     TopBarTitle,
+    RestoreRecoveredButton,
 }
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
@@ -94,6 +155,9 @@ pub enum Msg {
     WindowSizeChanged(Capture<WindowSize>),
     EditorContentChanged(Capture<String>),
     RunClicked,
+    CancelRunClicked,
+    RawOutputToggled,
+    RestoreRecoveredClicked,
 
     // Title related
     EditTitleClicked,
@@ -115,14 +179,26 @@ pub enum Msg {
 
     // Stdin related
     StdinButtonClicked,
+    StdinInputSelected(Capture<String>),
+    AddStdinInputClicked,
+    RunAllInputsClicked,
     StdinModalMsg(stdin_modal::Msg),
 
+    // Run options related
+    RunOptionsClicked,
+    RunOptionsModalMsg(run_options_modal::Msg),
+
     // File related
     FileSelected(Capture<String>),
+    DirectoryToggled(Capture<String>),
     AddFileClicked,
     EditFileClicked,
     FileModalMsg(file_modal::Msg),
 
+    // Import related
+    ImportFilesClicked,
+    ImportModalMsg(import_modal::Msg),
+
     // Search modal related
     SearchModalMsg(search_modal::Msg),
 
@@ -135,7 +211,7 @@ pub struct SnippetPage {
 }
 
 impl SnippetPage {
-    fn get_model(&self) -> Result<Model, String> {
+    fn get_model(&self) -> Result<Model, Error> {
         let current_route = self.browser_ctx.current_route();
 
         match &current_route {
@@ -145,17 +221,16 @@ impl SnippetPage {
                 self.model_for_existing_snippet(*language, encoded_snippet)
             }
 
-            _ => Err("Invalid route".to_string()),
+            _ => Err(Error::InvalidRoute(
+                self.browser_ctx.current_url.path().to_string(),
+            )),
         }
     }
 
-    fn model_for_new_snippet(&self, language: Language) -> Result<Model, String> {
+    fn model_for_new_snippet(&self, language: Language) -> Result<Model, Error> {
         let editor_config = language.config().editor_config();
 
-        let file = File {
-            name: editor_config.default_filename,
-            content: editor_config.example_code,
-        };
+        let file = File::text(editor_config.default_filename, editor_config.example_code);
 
         let title = "Hello World".to_string();
 
@@ -163,21 +238,37 @@ impl SnippetPage {
             browser_ctx: self.browser_ctx.clone(),
             language,
             files: SelectList::singleton(file),
+            collapsed_directories: vec![],
+            show_hidden_files: false,
             title,
             editor_keyboard_bindings: Default::default(),
             editor_theme: Default::default(),
-            stdin: None,
-            custom_command: None,
+            inputs: vec![],
+            selected_input: 0,
+            custom_run_instructions: None,
+            env: BTreeMap::new(),
+            args: vec![],
+            timeout: None,
             layout_state: app_layout::State::default(),
+            run_id: RunId::default(),
             run_result: RemoteData::NotAsked,
+            run_fingerprint: None,
+            run_stream: RunStream::default(),
+            show_raw_output: false,
+            run_expected_output: None,
+            input_results: vec![],
             language_version_result: RemoteData::Loading,
             snippet: None,
+            decode_failure: None,
+            action_error: None,
             search_modal_state: Default::default(),
             title_modal_state: Default::default(),
             sharing_modal_state: Default::default(),
             settings_modal_state: Default::default(),
             stdin_modal_state: Default::default(),
+            run_options_modal_state: Default::default(),
             file_modal_state: Default::default(),
+            import_modal_state: Default::default(),
         })
     }
 
@@ -185,52 +276,74 @@ impl SnippetPage {
         &self,
         language: Language,
         encoded_snippet: &str,
-    ) -> Result<Model, String> {
-        let snippet = Snippet::from_encoded_string(encoded_snippet)
-            .map_err(|err| format!("Failed to decode snippet: {}", err))?;
+    ) -> Result<Model, Error> {
+        let snippet = match Snippet::from_encoded_string(encoded_snippet) {
+            Ok(snippet) => snippet,
+            Err(err) => return self.model_for_broken_snippet(language, encoded_snippet, err),
+        };
 
         let snippet_clone = snippet.clone();
 
         let editor_config = language.config().editor_config();
 
-        let default_file = File {
-            name: editor_config.default_filename,
-            content: editor_config.example_code,
-        };
+        let default_file = File::text(editor_config.default_filename, editor_config.example_code);
 
-        let snippet_files: Vec<File> = snippet
-            .files
-            .iter()
-            .map(|file| File {
-                name: file.name.clone(),
-                content: file.content.clone(),
-            })
-            .collect();
-
-        let files = SelectList::from_vec(snippet_files)
+        let files = files_select_list(snippet.files.clone())
             .unwrap_or_else(|| SelectList::singleton(default_file));
 
         Ok(Model {
             browser_ctx: self.browser_ctx.clone(),
             language,
             files,
+            collapsed_directories: vec![],
+            show_hidden_files: false,
             title: snippet.title,
             editor_keyboard_bindings: Default::default(),
             editor_theme: Default::default(),
-            stdin: snippet.stdin.clone(),
-            custom_command: snippet.command,
+            inputs: snippet.inputs.clone(),
+            selected_input: 0,
+            custom_run_instructions: snippet.instructions,
+            env: snippet.env,
+            args: snippet.args,
+            timeout: snippet.timeout,
             layout_state: app_layout::State::default(),
+            run_id: RunId::default(),
             run_result: RemoteData::NotAsked,
+            run_fingerprint: None,
+            run_stream: RunStream::default(),
+            show_raw_output: false,
+            run_expected_output: None,
+            input_results: vec![],
             language_version_result: RemoteData::Loading,
             snippet: Some(snippet_clone),
+            decode_failure: None,
+            action_error: None,
             search_modal_state: Default::default(),
             title_modal_state: Default::default(),
             sharing_modal_state: Default::default(),
             settings_modal_state: Default::default(),
             stdin_modal_state: Default::default(),
+            run_options_modal_state: Default::default(),
             file_modal_state: Default::default(),
+            import_modal_state: Default::default(),
         })
     }
+
+    fn model_for_broken_snippet(
+        &self,
+        language: Language,
+        encoded_snippet: &str,
+        err: DecodeError,
+    ) -> Result<Model, Error> {
+        let mut model = self.model_for_new_snippet(language)?;
+
+        model.decode_failure = Some(DecodeFailure {
+            error: err,
+            recovered: recover::recover(encoded_snippet),
+        });
+
+        Ok(model)
+    }
 }
 
 impl Page<Model, Msg, Markup> for SnippetPage {
@@ -261,16 +374,32 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                 dom::get_target_data_string_value("filename"),
                 Msg::FileSelected,
             ),
+            event_listener::on_click_selector_closest(
+                Selector::data("directory"),
+                dom::get_target_data_string_value("directory"),
+                Msg::DirectoryToggled,
+            ),
             event_listener::on_click_closest(Id::AddFileButton, Msg::AddFileClicked),
+            event_listener::on_click_closest(Id::ImportFilesButton, Msg::ImportFilesClicked),
             event_listener::on_click_closest(Id::SettingsButton, Msg::SettingsButtonClicked),
             event_listener::on_click_closest(Id::StdinButton, Msg::StdinButtonClicked),
+            event_listener::on_change(Id::StdinInputSelect, Msg::StdinInputSelected),
+            event_listener::on_click_closest(Id::AddStdinInputButton, Msg::AddStdinInputClicked),
+            event_listener::on_click_closest(Id::RunAllInputsButton, Msg::RunAllInputsClicked),
             event_listener::on_click_closest(Id::EditFileButton, Msg::EditFileClicked),
             event_listener::on_keydown(run_key_combo.key, run_key_combo.modifier, Msg::RunClicked),
             event_listener::on_window_resize(Msg::WindowSizeChanged),
             event_listener::on_click_closest(Id::RunButton, Msg::RunClicked),
+            event_listener::on_click_closest(Id::CancelRunButton, Msg::CancelRunClicked),
+            event_listener::on_click_closest(Id::RawOutputToggle, Msg::RawOutputToggled),
+            event_listener::on_click_closest(Id::RunOptionsButton, Msg::RunOptionsClicked),
             event_listener::on_click_closest(Id::ShareButton, Msg::ShareClicked),
             event_listener::on_click_closest(Id::Title, Msg::EditTitleClicked),
             event_listener::on_click_closest(Id::TopBarTitle, Msg::EditTitleClicked),
+            event_listener::on_click_closest(
+                Id::RestoreRecoveredButton,
+                Msg::RestoreRecoveredClicked,
+            ),
             search_modal::subscriptions(
                 &model.browser_ctx.user_agent,
                 &model.search_modal_state,
@@ -281,7 +410,12 @@ impl Page<Model, Msg, Markup> for SnippetPage {
             sharing_modal::subscriptions(&model.sharing_modal_state, Msg::SharingModalMsg),
             settings_modal::subscriptions(&model.settings_modal_state, Msg::SettingsModalMsg),
             stdin_modal::subscriptions(&model.stdin_modal_state, Msg::StdinModalMsg),
+            run_options_modal::subscriptions(
+                &model.run_options_modal_state,
+                Msg::RunOptionsModalMsg,
+            ),
             file_modal::subscriptions(&model.file_modal_state, Msg::FileModalMsg),
+            import_modal::subscriptions(&model.import_modal_state, Msg::ImportModalMsg),
         ])
     }
 
@@ -296,37 +430,118 @@ impl Page<Model, Msg, Markup> for SnippetPage {
 
             Msg::EditorContentChanged(captured) => {
                 model.files.update_selected(|file| {
-                    file.content = captured.value();
+                    // The editor is empty while a binary file is selected
+                    if !file.is_binary() && !file.read_only {
+                        file.content = captured.value();
+                    }
                 });
 
+                model.action_error = None;
+
                 Ok(save_session_snippet_effect(model))
             }
 
             Msg::StdinButtonClicked => Ok(open_stdin_modal(model)),
 
+            Msg::StdinInputSelected(captured) => {
+                if let Ok(index) = captured.value().parse::<usize>() {
+                    if index < model.inputs.len() {
+                        model.selected_input = index;
+                    }
+                }
+
+                Ok(effect::none())
+            }
+
+            Msg::AddStdinInputClicked => Ok(open_add_stdin_input_modal(model)),
+
+            Msg::RunAllInputsClicked => Ok(run_all_inputs_effect(model)),
+
             Msg::StdinModalMsg(child_msg) => {
                 let event = stdin_modal::update(child_msg, &mut model.stdin_modal_state)?;
 
                 match event {
-                    stdin_modal::Event::StdinChanged(stdin) => {
-                        model.stdin = if stdin.is_empty() { None } else { Some(stdin) };
+                    stdin_modal::Event::InputAdded(input) => {
+                        model.inputs.push(input);
+                        model.selected_input = model.inputs.len() - 1;
+
+                        Ok(effect::batch(vec![
+                            save_session_snippet_effect(model),
+                            focus_editor_effect(),
+                        ]))
+                    }
+
+                    stdin_modal::Event::InputUpdated(input) => {
+                        if let Some(selected) = model.inputs.get_mut(model.selected_input) {
+                            *selected = input;
+                        }
+
+                        Ok(effect::batch(vec![
+                            save_session_snippet_effect(model),
+                            focus_editor_effect(),
+                        ]))
+                    }
+
+                    stdin_modal::Event::InputDeleted => {
+                        if model.selected_input < model.inputs.len() {
+                            model.inputs.remove(model.selected_input);
+                        }
+
+                        model.selected_input =
+                            min(model.selected_input, model.inputs.len().saturating_sub(1));
 
                         Ok(effect::batch(vec![
                             save_session_snippet_effect(model),
                             focus_editor_effect(),
                         ]))
                     }
+
                     stdin_modal::Event::ModalClosed => Ok(focus_editor_effect()),
                     stdin_modal::Event::None => Ok(effect::none()),
                 }
             }
 
+            Msg::RunOptionsClicked => Ok(open_run_options_modal(model)),
+
+            Msg::RunOptionsModalMsg(child_msg) => {
+                let event =
+                    run_options_modal::update(child_msg, &mut model.run_options_modal_state)?;
+
+                match event {
+                    run_options_modal::Event::RunOptionsChanged(options) => {
+                        model.env = options.env;
+                        model.args = options.args;
+
+                        Ok(effect::batch(vec![
+                            save_session_snippet_effect(model),
+                            focus_editor_effect(),
+                        ]))
+                    }
+
+                    run_options_modal::Event::ModalClosed => Ok(focus_editor_effect()),
+                    run_options_modal::Event::None => Ok(effect::none()),
+                }
+            }
+
             Msg::FileSelected(captured) => {
                 let filename = captured.value();
                 let effect = select_file(model, &filename);
                 Ok(effect)
             }
 
+            Msg::DirectoryToggled(captured) => {
+                let directory = captured.value();
+                let collapsed = &mut model.collapsed_directories;
+
+                if collapsed.contains(&directory) {
+                    collapsed.retain(|d| *d != directory);
+                } else {
+                    collapsed.push(directory);
+                }
+
+                Ok(effect::none())
+            }
+
             Msg::EditFileClicked => Ok(open_edit_file_modal(model)),
 
             Msg::AddFileClicked => Ok(open_add_file_modal(model)),
@@ -335,9 +550,11 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                 let event = file_modal::update(child_msg, &mut model.file_modal_state)?;
 
                 match event {
-                    file_modal::Event::FilenameChanged(filename) => {
+                    file_modal::Event::FileUpdated(updated) => {
                         model.files.update_selected(|file| {
-                            file.name = filename.clone();
+                            file.name = updated.filename;
+                            file.read_only = updated.read_only;
+                            file.hidden = updated.hidden;
                         });
 
                         Ok(effect::batch(vec![
@@ -347,10 +564,9 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                     }
 
                     file_modal::Event::FileAdded(filename) => {
-                        model.files.push(File {
-                            name: filename.clone(),
-                            content: "".to_string(),
-                        });
+                        model
+                            .files
+                            .push(File::text(filename.clone(), "".to_string()));
 
                         model.files.select_last();
                         Ok(effect::batch(vec![
@@ -367,31 +583,60 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                         ]))
                     }
 
+                    file_modal::Event::SetAsMainFile => Ok(set_main_file(model)),
+
                     file_modal::Event::ModalClosed => Ok(focus_editor_effect()),
                     file_modal::Event::None => Ok(effect::none()),
                 }
             }
 
+            Msg::ImportFilesClicked => Ok(open_import_modal(model)),
+
+            Msg::ImportModalMsg(child_msg) => {
+                let event = import_modal::update(child_msg, &mut model.import_modal_state)?;
+
+                match event {
+                    import_modal::Event::FilesImported(files) => {
+                        if let Some(files) = SelectList::from_vec(files) {
+                            model.files = files;
+                        }
+
+                        Ok(effect::batch(vec![
+                            save_session_snippet_effect(model),
+                            focus_editor_effect(),
+                        ]))
+                    }
+
+                    import_modal::Event::ModalClosed => Ok(focus_editor_effect()),
+                    import_modal::Event::None => Ok(effect::none()),
+                }
+            }
+
             Msg::SettingsButtonClicked => Ok(open_settings_modal(model)),
             Msg::SettingsModalMsg(child_msg) => {
                 let event = settings_modal::update(child_msg, &mut model.settings_modal_state)?;
 
                 match event {
                     settings_modal::Event::SettingsChanged(settings) => {
-                        let use_default_instructions = settings.command
-                            == get_default_run_instructions(model).to_string()
-                            || settings.command.is_empty();
+                        let use_default_instructions = settings_modal::is_default_run_instructions(
+                            &settings.run_instructions,
+                            &get_default_run_instructions(model),
+                        );
 
                         model.editor_keyboard_bindings = settings.keyboard_bindings;
                         model.editor_theme = settings.theme;
-                        model.custom_command = if use_default_instructions {
+                        model.timeout = settings.timeout;
+                        model.custom_run_instructions = if use_default_instructions {
                             None
                         } else {
-                            Some(settings.command)
+                            Some(settings.run_instructions)
                         };
 
-                        let effects =
-                            effect::batch(vec![focus_editor_effect(), save_settings_effect(model)]);
+                        let effects = effect::batch(vec![
+                            focus_editor_effect(),
+                            save_settings_effect(model),
+                            save_session_snippet_effect(model),
+                        ]);
 
                         Ok(effects)
                     }
@@ -414,10 +659,16 @@ impl Page<Model, Msg, Markup> for SnippetPage {
 
             Msg::GotSessionSnippet(captured) => {
                 if let Some(snippet) = captured.value() {
+                    model.decode_failure = None;
                     model.title = snippet.title;
-                    model.stdin = snippet.stdin;
-
-                    if let Some(files) = SelectList::from_vec(snippet.files) {
+                    model.inputs = snippet.inputs;
+                    model.selected_input = 0;
+                    model.env = snippet.env;
+                    model.args = snippet.args;
+                    model.timeout = snippet.timeout;
+                    model.custom_run_instructions = snippet.instructions;
+
+                    if let Some(files) = files_select_list(snippet.files) {
                         model.files = files
                     }
                 }
@@ -432,17 +683,59 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                 Ok(effect)
             }
 
+            Msg::RawOutputToggled => {
+                model.show_raw_output = !model.show_raw_output;
+                Ok(effect::none())
+            }
+
+            // The result is set when JS reports the run as cancelled
+            Msg::CancelRunClicked => {
+                Ok(effect::custom(CustomEffect::CancelRun(CancelRunRequest {
+                    run_id: model.run_id,
+                })))
+            }
+
+            Msg::RestoreRecoveredClicked => {
+                let maybe_recovered = model
+                    .decode_failure
+                    .take()
+                    .and_then(|failure| failure.recovered);
+
+                if let Some(recovered) = maybe_recovered {
+                    if let Some(title) = recovered.title {
+                        model.title = title;
+                    }
+
+                    if let Some(files) = SelectList::from_vec(recovered.files) {
+                        model.files = files;
+                    }
+                }
+
+                Ok(effect::batch(vec![
+                    save_session_snippet_effect(model),
+                    focus_editor_effect(),
+                ]))
+            }
+
             Msg::ShareClicked => Ok(open_sharing_modal(model)),
 
             Msg::EditTitleClicked => Ok(open_title_modal(model)),
 
             Msg::SearchModalMsg(child_msg) => {
-                let files = model.files.to_vec();
+                let files = visible_files(model);
+                let has_changes = has_changes(model);
+                let has_hidden_files = model.files.to_vec().iter().any(|file| file.hidden);
+                let has_multiple_inputs = model.inputs.len() > 1;
 
                 let data: search_modal::UpdateData<Msg, QuickAction> = search_modal::update(
                     child_msg,
                     &mut model.search_modal_state,
-                    quick_action::actions(files),
+                    quick_action::actions(
+                        files,
+                        has_changes,
+                        has_hidden_files,
+                        has_multiple_inputs,
+                    ),
                     Msg::SearchModalMsg,
                 )?;
 
@@ -452,9 +745,33 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                         QuickAction::EditTitle => open_title_modal(model),
                         QuickAction::EditFile => open_edit_file_modal(model),
                         QuickAction::EditStdin => open_stdin_modal(model),
+                        QuickAction::AddStdinInput => open_add_stdin_input_modal(model),
+                        QuickAction::RunAllInputs => run_all_inputs_effect(model),
+                        QuickAction::EditRunOptions => open_run_options_modal(model),
                         QuickAction::AddFile => open_add_file_modal(model),
+                        QuickAction::ImportFiles => open_import_modal(model),
                         QuickAction::Share => open_sharing_modal(model),
+                        QuickAction::DownloadArchive(format) => {
+                            download_archive_effect(model, format)
+                        }
+                        QuickAction::DownloadLocalRun(format) => {
+                            download_local_run_effect(model, format)
+                        }
+                        QuickAction::CompareWithOriginal => compare_with_original(model),
                         QuickAction::Settings => open_settings_modal(model),
+                        QuickAction::SetMainFile => set_main_file(model),
+                        QuickAction::ToggleHiddenFiles => {
+                            model.show_hidden_files = !model.show_hidden_files;
+                            effect::none()
+                        }
+                        QuickAction::MoveFileLeft => {
+                            model.files.move_selected_left();
+                            save_session_snippet_effect(model)
+                        }
+                        QuickAction::MoveFileRight => {
+                            model.files.move_selected_right();
+                            save_session_snippet_effect(model)
+                        }
                         QuickAction::SelectFile(name) => select_file(model, &name),
                         QuickAction::GoToFrontPage => go_to_home(model),
 
@@ -501,6 +818,7 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                     current_url: model.browser_ctx.current_url.clone(),
                     language: model.language,
                     snippet: snippet_from_model(model),
+                    run_result: current_run_result(model),
                 };
 
                 let data = sharing_modal::update(
@@ -515,6 +833,16 @@ impl Page<Model, Msg, Markup> for SnippetPage {
                     sharing_modal::Event::ModalClosed => {
                         effect::batch(vec![focus_editor_effect(), data.effect])
                     }
+                    sharing_modal::Event::DownloadRequested(download) => {
+                        let download_effect =
+                            effect::custom(CustomEffect::Download(DownloadRequest {
+                                filename: download.filename,
+                                mime_type: download.mime_type,
+                                bytes: download.content.into_bytes(),
+                            }));
+
+                        effect::batch(vec![download_effect, data.effect])
+                    }
                 };
 
                 Ok(effect)
@@ -524,40 +852,118 @@ impl Page<Model, Msg, Markup> for SnippetPage {
 
     fn update_from_js(&self, msg: JsMsg, model: &mut Model) -> Result<Effect<Msg>, String> {
         match msg.type_.as_ref() {
-            "GotRunResponse" => {
-                let outcome: RunOutcome = serde_json::from_value(msg.data)
-                    .map_err(|err| format!("Failed to decode run response from js: {}", err))?;
+            // The output is shown as it arrives, the result is set when the run is done
+            "GotRunEvent" => {
+                let message: RunEventMessage =
+                    serde_json::from_value(msg.data).map_err(|err| err.to_string())?;
 
-                match outcome {
-                    RunOutcome::Success(run_result) => {
-                        model.run_result = RemoteData::Success(run_result);
-                    }
+                if message.run_id != model.run_id {
+                    return Ok(effect::none());
+                }
 
-                    RunOutcome::Failure(err) => {
-                        model.run_result = RemoteData::Failure(err);
-                    }
+                let result = match RunEvent::from_value(message.event) {
+                    Ok(event) => model.run_stream.push(event),
+                    Err(err) => Some(Err(err)),
+                };
+
+                if let Some(result) = result {
+                    model.run_result = match result {
+                        Ok(run_result) => RemoteData::Success(run_result),
+                        Err(err) => RemoteData::Failure(err),
+                    };
                 }
 
                 Ok(effect::none())
             }
 
-            "GotLanguageVersionResponse" => {
-                let outcome: RunOutcome = serde_json::from_value(msg.data)
-                    .map_err(|err| format!("Failed to decode run response from js: {}", err))?;
+            // Sent when the request fails, is aborted or ends early, the run is finished even
+            // if events are missing
+            "GotRunAborted" => {
+                let message: RunAbortedMessage =
+                    serde_json::from_value(msg.data).map_err(|err| err.to_string())?;
 
-                match outcome {
-                    RunOutcome::Success(run_result) => {
-                        model.language_version_result = RemoteData::Success(run_result);
-                    }
+                if message.run_id != model.run_id {
+                    return Ok(effect::none());
+                }
 
-                    RunOutcome::Failure(err) => {
-                        model.language_version_result = RemoteData::Failure(err);
-                    }
+                let err = if message.cancelled {
+                    RunError::Cancelled
+                } else {
+                    RunError::Transport(message.message)
+                };
+
+                if let Some(Err(err)) = model.run_stream.abort(err) {
+                    model.run_result = RemoteData::Failure(err);
+                }
+
+                Ok(effect::none())
+            }
+
+            "GotInputRunResponse" => {
+                let response: InputRunResponse =
+                    serde_json::from_value(msg.data).map_err(|err| err.to_string())?;
+
+                if response.run_id != model.run_id {
+                    return Ok(effect::none());
                 }
 
+                let result =
+                    RunOutcome::from_value(response.response).and_then(RunOutcome::into_result);
+
+                if let Some(input_result) = model.input_results.get_mut(response.index) {
+                    input_result.result = match result {
+                        Ok(run_result) => RemoteData::Success(run_result),
+                        Err(err) => RemoteData::Failure(err),
+                    };
+                }
+
+                Ok(effect::none())
+            }
+
+            "GotLanguageVersionResponse" => {
+                let result = RunOutcome::from_value(msg.data).and_then(RunOutcome::into_result);
+
+                model.language_version_result = match result {
+                    Ok(run_result) => RemoteData::Success(run_result),
+                    Err(err) => RemoteData::Failure(err),
+                };
+
+                Ok(effect::none())
+            }
+
+            "GotImportFiles" => {
+                let uploads = import::from_js_value(msg.data).map_err(Error::from)?;
+
+                import_modal::add_imported(
+                    &mut model.import_modal_state,
+                    model.language,
+                    model.files.to_vec(),
+                    import::read_uploads(uploads),
+                );
+
                 Ok(effect::none())
             }
 
+            "GotFileMoved" => {
+                let file_move: FileMove =
+                    serde_json::from_value(msg.data).map_err(|err| err.to_string())?;
+
+                // The index is relative to the visible tabs
+                let files = model.files.to_vec();
+                let file_index = |name: &str| files.iter().position(|file| file.name == name);
+                let to_index = visible_files(model)
+                    .get(file_move.index)
+                    .and_then(|file| file_index(&file.name))
+                    .unwrap_or(files.len() - 1);
+
+                if let Some(from_index) = file_index(&file_move.filename) {
+                    model.files.move_item(from_index, to_index);
+                    Ok(save_session_snippet_effect(model))
+                } else {
+                    Ok(effect::none())
+                }
+            }
+
             _ => {
                 let log_effect =
                     console::log(&format!("Got unknown message from JS: {}", msg.type_));
@@ -586,8 +992,75 @@ impl Page<Model, Msg, Markup> for SnippetPage {
 #[serde(tag = "type", content = "config")]
 #[serde(rename_all = "camelCase")]
 pub enum CustomEffect {
-    Run(RunRequest),
+    Run(TaggedRunRequest),
+    RunInput(InputRunRequest),
+    CancelRun(CancelRunRequest),
     GetLanguageVersion(RunRequest),
+    Download(DownloadRequest),
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadRequest {
+    pub filename: String,
+    pub mime_type: String,
+    pub bytes: Vec<u8>,
+}
+
+// The index of the input is sent back with the response
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputRunRequest {
+    pub run_id: RunId,
+    pub index: usize,
+    pub request: RunRequest,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InputRunResponse {
+    run_id: RunId,
+    index: usize,
+    response: serde_json::Value,
+}
+
+// The run id is sent back with every event of the run
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggedRunRequest {
+    pub run_id: RunId,
+    pub request: RunRequest,
+}
+
+// Aborts the requests of the run, both a single run and a run of all inputs
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelRunRequest {
+    pub run_id: RunId,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunEventMessage {
+    run_id: RunId,
+    event: serde_json::Value,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunAbortedMessage {
+    run_id: RunId,
+    cancelled: bool,
+    #[serde(default)]
+    message: String,
+}
+
+// Sent from JS when a tab is dragged or moved with the keyboard
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileMove {
+    filename: String,
+    index: usize,
 }
 
 fn view_head(model: &Model) -> maud::Markup {
@@ -620,7 +1093,9 @@ fn view_body(model: &Model) -> maud::Markup {
             (sharing_modal::view(&model.sharing_modal_state))
             (settings_modal::view(&model.settings_modal_state))
             (stdin_modal::view(&model.stdin_modal_state))
+            (run_options_modal::view(&model.run_options_modal_state))
             (file_modal::view(&model.file_modal_state))
+            (import_modal::view(&model.import_modal_state))
         }
     }
 }
@@ -642,12 +1117,92 @@ fn view_topbar_title(model: &Model) -> maud::Markup {
 }
 
 fn view_content(model: &Model) -> Markup {
+    match &model.decode_failure {
+        Some(failure) => view_decode_failure(model, failure),
+        None => view_editor_content(model),
+    }
+}
+
+fn view_decode_failure(model: &Model, failure: &DecodeFailure) -> Markup {
+    let language_name = model.language.config().name();
+    let new_snippet_path = Route::NewSnippet(model.language).to_path();
+
+    html! {
+        div class="pt-6 max-w-7xl mx-auto px-4 sm:px-6 md:px-8" {
+            div class="border border-gray-400 shadow-lg bg-white px-6 py-5" {
+                h2 class="text-lg font-semibold text-gray-900" {
+                    "This snippet link could not be opened"
+                }
+                p class="mt-2 text-sm text-gray-700" {
+                    "The snippet is stored in the link itself, so a link that has been truncated or corrupted can't be decoded. Make sure the whole link was copied."
+                }
+
+                dl class="mt-4 text-sm" {
+                    dt class="font-medium text-gray-900" { "Failed at" }
+                    dd class="mt-1 text-gray-700" { (failure.error.stage()) }
+                    dt class="mt-2 font-medium text-gray-900" { "Details" }
+                    dd class="mt-1 text-gray-700 font-mono break-all" { (failure.error) }
+                }
+
+                @if let Some(recovered) = &failure.recovered {
+                    div class="mt-6" {
+                        h3 class="text-sm font-medium text-gray-900" {
+                            "Recovered content"
+                        }
+                        p class="mt-1 text-sm text-gray-500" {
+                            "Parts of the snippet could be recovered, the last file may be incomplete."
+                        }
+
+                        @for file in &recovered.files {
+                            div class="mt-3 border border-gray-400" {
+                                dt class="px-4 py-1 border-b border-gray-400 text-sm text-slate-700 font-bold bg-blue-400" {
+                                    pre { (file.name) }
+                                }
+                                dd class="px-4 py-2 max-h-64 overflow-y-auto" {
+                                    @if file.is_binary() {
+                                        p class="text-sm text-gray-500" { "Binary file" }
+                                    } @else {
+                                        pre { (file.content) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div class="flex mt-6" {
+                    a href=(new_snippet_path) class="flex-1 w-full inline-flex justify-center items-center rounded-md border border-gray-300 bg-white px-4 py-2 text-sm font-medium text-gray-700 shadow-sm hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" {
+                        "New " (language_name) " snippet"
+                    }
+
+                    @if failure.recovered.is_some() {
+                        button id=(Id::RestoreRecoveredButton) class="ml-4 flex-1 w-full inline-flex justify-center items-center rounded-md border border-transparent bg-indigo-600 px-4 py-2 text-sm font-medium text-white shadow-sm hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-indigo-500 focus:ring-offset-2" type="button" {
+                            "Restore recovered content"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn view_editor_content(model: &Model) -> Markup {
     let has_real_window_size = model.browser_ctx.window_size.is_some();
     let window_size = model.browser_ctx.window_size.clone().unwrap_or_default();
     let editor_height = calc_editor_height(&window_size);
     let inline_styles = format!("height: {}px;", editor_height);
     let height = format!("{}px", editor_height);
-    let content = model.files.selected().content;
+    let selected_file = model.files.selected();
+    let is_binary = selected_file.is_binary();
+    // Binary files are shown as a preview, the editor is kept so it can be focused when switching files
+    let (content, editor_class) = if is_binary {
+        ("", "hidden")
+    } else {
+        (
+            selected_file.content.as_str(),
+            "relative block w-full text-base whitespace-pre font-mono",
+        )
+    };
     let editor_config = model.language.config().editor_config();
 
     html! {
@@ -667,27 +1222,48 @@ fn view_content(model: &Model) -> Markup {
                 }
 
                 div class="max-w-7xl mx-auto px-4 sm:px-6 md:px-8" {
+                    (view_violations(model))
+
+                    @if let Some(err) = &model.action_error {
+                        div class="mb-3 rounded-md bg-yellow-50 px-4 py-3 text-sm text-yellow-800" {
+                            (err)
+                        }
+                    }
+
                     div .hidden[!has_real_window_size] {
                         div class="border border-gray-400 shadow-lg" {
                             (view_tab_bar(model))
 
-                            poly-ace-editor id=(Id::Editor)
-                                style=(inline_styles)
-                                class="relative block w-full text-base whitespace-pre font-mono"
-                                editor-class="w-full text-base whitespace-pre font-mono"
-                                stylesheet-id="app-styles"
-                                height=(height)
-                                keyboard-handler=(model.editor_keyboard_bindings.ace_keyboard_handler())
-                                mode=(editor_config.mode)
-                                use-soft-tabs=(editor_config.use_soft_tabs)
-                                tab-size=(editor_config.soft_tab_size)
-                                theme=(model.editor_theme.ace_theme())
-                            {
-                                (content)
+                            div class="flex" {
+                                @if use_file_tree(model) {
+                                    (view_file_tree(model, &inline_styles))
+                                }
+
+                                div class="flex-1 min-w-0" {
+                                    poly-ace-editor id=(Id::Editor)
+                                        style=(inline_styles)
+                                        class=(editor_class)
+                                        editor-class="w-full text-base whitespace-pre font-mono"
+                                        stylesheet-id="app-styles"
+                                        height=(height)
+                                        keyboard-handler=(model.editor_keyboard_bindings.ace_keyboard_handler())
+                                        mode=(editor_config.mode)
+                                        use-soft-tabs=(editor_config.use_soft_tabs)
+                                        tab-size=(editor_config.soft_tab_size)
+                                        theme=(model.editor_theme.ace_theme())
+                                        readonly=(selected_file.read_only)
+                                    {
+                                        (content)
+                                    }
+
+                                    @if is_binary {
+                                        (binary_preview::view(&selected_file, &inline_styles))
+                                    }
+                                }
                             }
 
                             (view_stdin_bar(model))
-                            (view_action_bar())
+                            (view_action_bar(model))
                         }
                     }
                 }
@@ -696,7 +1272,13 @@ fn view_content(model: &Model) -> Markup {
             div class="w-full flex-1 max-w-7xl mx-auto pb-4 px-4 sm:px-6 md:px-8" {
                 div ."h-full" ."pt-4" .hidden[!has_real_window_size] {
                     (output_panel::view(output_panel::ViewModel {
+                        run_id: model.run_id,
+                        raw_output_toggle_id: Id::RawOutputToggle,
+                        show_raw_output: model.show_raw_output,
                         run_result: &model.run_result,
+                        run_stream: &model.run_stream,
+                        expected_output: model.run_expected_output.as_ref(),
+                        input_results: &model.input_results,
                         version_result: &model.language_version_result,
                     }))
                 }
@@ -705,8 +1287,34 @@ fn view_content(model: &Model) -> Markup {
     }
 }
 
+// Snippets are opened even if they break the limits, they can't be run or shared until fixed
+fn view_violations(model: &Model) -> Markup {
+    let violations = match validate::validate(&snippet_from_model(model)) {
+        Ok(()) => return html! {},
+        Err(violations) => violations,
+    };
+
+    html! {
+        div class="mb-3 rounded-md bg-red-50 px-4 py-3" {
+            p class="text-sm font-medium text-red-800" {
+                "This snippet can't be run or shared until the following is fixed"
+            }
+            ul class="mt-1 text-sm text-red-700 list-disc list-inside" {
+                @for violation in &violations {
+                    li { (violation) }
+                }
+            }
+        }
+    }
+}
+
 fn view_tab_bar(model: &Model) -> Markup {
-    let files = model.files.to_vec();
+    // Only the selected file gets a tab when the files are listed in the file tree
+    let files = if use_file_tree(model) {
+        vec![model.files.selected()]
+    } else {
+        visible_files(model)
+    };
 
     html! {
         div class="h-10 flex border-b border-gray-400" {
@@ -727,6 +1335,12 @@ fn view_tab_bar(model: &Model) -> Markup {
                     (heroicons_maud::document_plus_outline())
                 }
             }
+
+            button id=(Id::ImportFilesButton) class="inline-flex items-center text-gray-500 hover:text-gray-700 px-3 font-semibold text-sm" type="button"{
+                span class="w-5 h-5" {
+                    (heroicons_maud::arrow_up_tray_outline())
+                }
+            }
         }
     }
 }
@@ -734,12 +1348,20 @@ fn view_tab_bar(model: &Model) -> Markup {
 fn view_file_tab(model: &Model, file: &File) -> Markup {
     let is_selected = model.files.selected().name == file.name;
     let id = is_selected.then_some(Id::EditFileButton);
+    let is_draggable = !use_file_tree(model) && model.files.len() > 1;
 
     html! {
-        button id=[id] data-filename=(file.name) .file .relative ."[min-width:5rem]" ."border-l" ."border-gray-400" ."cursor-pointer" ."inline-flex" ."items-center" ."justify-center" ."px-3" ."bg-indigo-100"[is_selected]  ."cursor-pointer" ."text-gray-500"[!is_selected] ."text-gray-800"[is_selected] ."hover:text-gray-800" ."text-sm" type="button" {
+        button id=[id] data-filename=(file.name) draggable=[is_draggable.then_some("true")] .file .relative ."[min-width:5rem]" ."border-l" ."border-gray-400" ."cursor-pointer" ."inline-flex" ."items-center" ."justify-center" ."px-3" ."bg-indigo-100"[is_selected]  ."cursor-pointer" ."text-gray-500"[!is_selected] ."text-gray-800"[is_selected] ."hover:text-gray-800" ."text-sm" type="button" {
+            @if is_main_file(model, &file.name) {
+                span class="w-3 h-3 mr-1 text-amber-500" title="Main file" {
+                    (heroicons_maud::star_solid())
+                }
+            }
             span class {
                 (file.name)
             }
+            (view_file_attributes(file))
+            (view_filename_problem(model, &file.name))
             @if is_selected {
                 span class="hidden edit-overlay absolute z-10 inset-0 w-full h-full bg-gray-500 bg-opacity-70" {
                     span class="absolute z-20 inset-0 m-auto w-5 h-5 text-slate-50" {
@@ -751,9 +1373,169 @@ fn view_file_tab(model: &Model, file: &File) -> Markup {
     }
 }
 
+// Only marked when there is more than one file, a single file is always the main file
+fn is_main_file(model: &Model, filename: &str) -> bool {
+    let files = model.files.to_vec();
+
+    files.len() > 1
+        && files
+            .get(snippet::main_file_index(&files))
+            .is_some_and(|file| file.name == filename)
+}
+
+// Hidden files are only shown if selected or when the user has asked to see them
+fn visible_files(model: &Model) -> Vec<File> {
+    let selected_index = model.files.selected_index();
+
+    model
+        .files
+        .to_vec()
+        .into_iter()
+        .enumerate()
+        .filter(|(index, file)| !file.hidden || model.show_hidden_files || *index == selected_index)
+        .map(|(_, file)| file)
+        .collect()
+}
+
+// Selects the first visible file
+fn files_select_list(files: Vec<File>) -> Option<SelectList<File>> {
+    let visible_index = files.iter().position(|file| !file.hidden);
+    let mut select_list = SelectList::from_vec(files)?;
+
+    if let Some(index) = visible_index {
+        select_list.select_index(index);
+    }
+
+    Some(select_list)
+}
+
+fn filenames(model: &Model) -> Vec<String> {
+    visible_files(model)
+        .into_iter()
+        .map(|file| file.name)
+        .collect()
+}
+
+fn use_file_tree(model: &Model) -> bool {
+    let filenames = filenames(model);
+
+    let tab_capacity = model
+        .browser_ctx
+        .window_size
+        .as_ref()
+        .map(calc_tab_capacity)
+        .unwrap_or(usize::MAX);
+
+    file_tree::has_directories(&filenames) || filenames.len() > tab_capacity
+}
+
+fn view_file_tree(model: &Model, inline_styles: &str) -> Markup {
+    let nodes = file_tree::build(&filenames(model));
+
+    html! {
+        nav class="w-40 sm:w-56 shrink-0 overflow-y-auto border-r border-gray-400 bg-gray-50 py-1 text-sm" style=(inline_styles) {
+            (view_file_tree_nodes(model, &nodes, 0))
+        }
+    }
+}
+
+fn view_file_tree_nodes(model: &Model, nodes: &[file_tree::Node], depth: usize) -> Markup {
+    let selected_filename = model.files.selected().name;
+    let files = model.files.to_vec();
+    let indent = format!("padding-left: {}rem;", 0.5 + depth as f64 * 0.75);
+
+    html! {
+        @for node in nodes {
+            @match node {
+                file_tree::Node::Directory { name, path, children } => {
+                    @let is_collapsed = model.collapsed_directories.contains(path);
+
+                    button data-directory=(path) style=(indent) class="w-full flex items-center py-1 pr-2 text-gray-700 hover:bg-gray-100" type="button" {
+                        span class="w-4 h-4 shrink-0 text-gray-400" {
+                            @if is_collapsed {
+                                (heroicons_maud::chevron_right_outline())
+                            } @else {
+                                (heroicons_maud::chevron_down_outline())
+                            }
+                        }
+                        span class="ml-1 w-4 h-4 shrink-0 text-gray-500" {
+                            (heroicons_maud::folder_outline())
+                        }
+                        span class="ml-1 truncate" { (name) }
+                    }
+
+                    @if !is_collapsed {
+                        (view_file_tree_nodes(model, children, depth + 1))
+                    }
+                }
+
+                file_tree::Node::File { name, path } => {
+                    @let is_selected = *path == selected_filename;
+
+                    button data-filename=(path) style=(indent) .w-full .flex .items-center .py-1 .pr-2 ."hover:bg-gray-100" ."bg-indigo-100"[is_selected] ."text-gray-900"[is_selected] ."text-gray-600"[!is_selected] type="button" {
+                        span class="ml-5 w-4 h-4 shrink-0 text-gray-400" {
+                            (heroicons_maud::document_outline())
+                        }
+                        span class="ml-1 truncate" { (name) }
+
+                        @if let Some(file) = files.iter().find(|file| file.name == *path) {
+                            span class="ml-auto flex" {
+                                (view_file_attributes(file))
+                                (view_filename_problem(model, path))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn view_file_attributes(file: &File) -> Markup {
+    html! {
+        @if file.read_only {
+            span class="w-3 h-3 ml-1 text-gray-400" title="Read-only" {
+                (heroicons_maud::lock_closed_solid())
+            }
+        }
+        @if file.hidden {
+            span class="w-3 h-3 ml-1 text-gray-400" title="Hidden" {
+                (heroicons_maud::eye_slash_solid())
+            }
+        }
+    }
+}
+
+fn view_filename_problem(model: &Model, filename: &str) -> Markup {
+    html! {
+        @if let Some(problem) = filename_problem(&model.files.to_vec(), filename) {
+            span class="w-3 h-3 ml-1 text-red-500" title=(problem) {
+                (heroicons_maud::exclamation_circle_solid())
+            }
+        }
+    }
+}
+
+// Snippets with invalid filenames are still opened, the files are marked until renamed
+fn filename_problem(files: &[File], filename: &str) -> Option<String> {
+    let duplicate_count = files.iter().filter(|file| file.name == filename).count();
+
+    if let Err(err) = path::validate(filename) {
+        Some(err.to_string())
+    } else if duplicate_count > 1 {
+        Some("Filename is used by more than one file".to_string())
+    } else {
+        files
+            .iter()
+            .map(|file| file.name.as_str())
+            .find(|name| path::is_directory_conflict(filename, name))
+            .map(|name| format!("Conflicts with {}", name))
+    }
+}
+
 fn view_stdin_bar(model: &Model) -> Markup {
     html! {
-        @match &model.stdin {
+        @match selected_input(model) {
             None => {
                 button id=(Id::StdinButton) class="flex justify-center h-10 w-full bg-white hover:bg-gray-50 text-gray-700 inline-flex items-center px-3 font-semibold text-sm border-t border-gray-400" type="button" {
                     span class="w-5 h-5 mr-1" { (heroicons_maud::pencil_square_outline()) }
@@ -761,14 +1543,37 @@ fn view_stdin_bar(model: &Model) -> Markup {
                 }
             }
 
-            Some(stdin) => {
+            Some(input) => {
                 div class="w-full h-24 border-t border-gray-400 overflow-hidden" {
-                    dt class="px-4 py-1 border-b border-gray-400 text-sm text-slate-700 font-bold bg-blue-400" {
+                    dt class="px-4 py-1 flex items-center border-b border-gray-400 text-sm text-slate-700 font-bold bg-blue-400" {
                         pre { "STDIN" }
+
+                        @if model.inputs.len() > 1 {
+                            select id=(Id::StdinInputSelect) class="ml-3 py-0 pl-2 pr-8 rounded border-gray-400 bg-blue-100 text-sm font-normal" {
+                                @for (index, input) in model.inputs.iter().enumerate() {
+                                    option value=(index) selected[index == model.selected_input] {
+                                        (input.name)
+                                    }
+                                }
+                            }
+                        } @else {
+                            pre class="ml-3 font-normal" { (input.name) }
+                        }
+
+                        button id=(Id::AddStdinInputButton) class="ml-auto inline-flex items-center hover:text-slate-900" type="button" title="Add stdin input" {
+                            span class="w-4 h-4" { (heroicons_maud::plus_outline()) }
+                        }
+
+                        @if model.inputs.len() > 1 {
+                            button id=(Id::RunAllInputsButton) class="ml-3 inline-flex items-center hover:text-slate-900" type="button" {
+                                span class="w-4 h-4 mr-1" { (heroicons_maud::play_outline()) }
+                                pre { "RUN ALL" }
+                            }
+                        }
                     }
                     dd id=(Id::StdinButton) class="h-full px-4 py-2 relative cursor-pointer stdin-preview" {
                         pre {
-                            (stdin)
+                            (input.content)
                         }
 
                         span class="hidden stdin-overlay absolute z-10 inset-0 w-full h-full bg-gray-500 bg-opacity-30" {
@@ -783,12 +1588,29 @@ fn view_stdin_bar(model: &Model) -> Markup {
     }
 }
 
-fn view_action_bar() -> Markup {
+fn view_action_bar(model: &Model) -> Markup {
+    let run_options_count = model.env.len() + model.args.len();
+
     html! {
         div class="h-12 flex border-t border-gray-400" {
-            button id=(Id::RunButton) class="bg-white hover:bg-gray-50 text-gray-700 w-full inline-flex items-center justify-center px-3 py-1 font-semibold text-sm" type="button" {
-                span class="w-5 h-5 mr-2" { (heroicons_maud::play_outline()) }
-                span { "RUN" }
+            @if is_running(model) {
+                button id=(Id::CancelRunButton) class="bg-white hover:bg-gray-50 text-red-700 w-full inline-flex items-center justify-center px-3 py-1 font-semibold text-sm" type="button" {
+                    span class="w-5 h-5 mr-2" { (heroicons_maud::stop_outline()) }
+                    span { "CANCEL" }
+                }
+            } @else {
+                button id=(Id::RunButton) class="bg-white hover:bg-gray-50 text-gray-700 w-full inline-flex items-center justify-center px-3 py-1 font-semibold text-sm" type="button" {
+                    span class="w-5 h-5 mr-2" { (heroicons_maud::play_outline()) }
+                    span { "RUN" }
+                }
+            }
+
+            button id=(Id::RunOptionsButton) class="bg-white hover:bg-gray-50 text-gray-700 w-full inline-flex items-center justify-center px-3 py-1 font-semibold text-sm border-l border-gray-400" type="button" title="Arguments and environment variables" {
+                span class="w-5 h-5 mr-2" { (heroicons_maud::command_line_outline()) }
+                span { "ARGS" }
+                @if run_options_count > 0 {
+                    span class="ml-2 rounded-full bg-gray-200 px-2 text-xs" { (run_options_count) }
+                }
             }
 
             button id=(Id::ShareButton) class="bg-white hover:bg-gray-50 text-gray-700 w-full inline-flex items-center justify-center px-3 py-1 font-semibold text-sm border-l border-gray-400" type="button" {
@@ -839,6 +1661,9 @@ fn get_language_version_effect(language: Language) -> Effect<Msg> {
         payload: RunRequestPayload {
             files: vec![],
             stdin: None,
+            env: BTreeMap::new(),
+            args: vec![],
+            timeout: None,
             run_instructions: RunInstructions {
                 build_commands: vec![],
                 run_command: run_config.version_command,
@@ -853,6 +1678,14 @@ fn focus_editor_effect() -> Effect<Msg> {
     dom::dispatch_element_event(Id::Editor, "focus")
 }
 
+// Rough number of file tabs that fit next to the tab bar buttons
+fn calc_tab_capacity(window_size: &WindowSize) -> usize {
+    let content_width = min(window_size.width, MAX_CONTENT_WIDTH).saturating_sub(64);
+    let available_width = content_width.saturating_sub(TAB_BAR_BUTTONS_WIDTH);
+
+    max(available_width / AVERAGE_TAB_WIDTH, 1) as usize
+}
+
 fn calc_editor_height(window_size: &WindowSize) -> u64 {
     let height = if window_size.height < 800 {
         (window_size.height as f64 * 0.4) as u64
@@ -868,55 +1701,204 @@ fn calc_editor_height(window_size: &WindowSize) -> u64 {
 }
 
 fn run_effect(model: &mut Model) -> Effect<Msg> {
-    let language_config = model.language.config();
-    let run_config = language_config.run_config();
+    let snippet = snippet_from_model(model);
 
-    let run_instructions = if let Some(command) = model.custom_command.clone() {
-        RunInstructions {
-            build_commands: vec![],
-            run_command: command,
-        }
-    } else {
-        get_default_run_instructions(model)
-    };
+    // Responses for earlier runs that are still in flight are ignored from now on
+    model.run_id = model.run_id.next();
+    reset_run_output(model);
 
-    let config = RunRequest {
-        image: run_config.container_image.clone(),
-        payload: RunRequestPayload {
-            run_instructions,
-            files: model.files.to_vec(),
-            stdin: model.stdin.clone(),
-        },
-    };
+    if let Err(violations) = validate::validate(&snippet) {
+        model.run_result = RemoteData::Failure(RunError::InvalidSnippet(violations));
+        return effect::none();
+    }
+
+    let stdin = selected_input(model).map(|input| input.content.clone());
+    let config = RunRequest::with_stdin(&snippet, stdin);
+    model.run_fingerprint = Some(snippet.fingerprint());
 
+    // The expectation is kept with the result, later edits don't affect it
+    model.run_expected_output =
+        selected_input(model).and_then(|input| input.expected_output.clone());
     model.run_result = RemoteData::Loading;
 
-    effect::custom(CustomEffect::Run(config))
+    effect::custom(CustomEffect::Run(TaggedRunRequest {
+        run_id: model.run_id,
+        request: config,
+    }))
 }
 
-fn get_default_run_instructions(model: &Model) -> RunInstructions {
-    let files = model.files.to_vec();
+// Output of the previous run, it must not be shown with the result of the next one
+fn reset_run_output(model: &mut Model) {
+    model.run_stream = RunStream::default();
+    model.input_results = vec![];
+    model.run_fingerprint = None;
+    model.run_expected_output = None;
+}
 
-    let main_file = PathBuf::from(model.files.first().name);
-    let other_files = files
-        .into_iter()
-        .skip(1)
-        .map(|f| PathBuf::from(f.name))
+fn is_running(model: &Model) -> bool {
+    matches!(model.run_result, RemoteData::Loading)
+        || model
+            .input_results
+            .iter()
+            .any(|input_result| matches!(input_result.result, RemoteData::Loading))
+}
+
+// One run request per input, the results are shown grouped by input
+fn run_all_inputs_effect(model: &mut Model) -> Effect<Msg> {
+    let snippet = snippet_from_model(model);
+
+    if snippet.inputs.is_empty() {
+        return run_effect(model);
+    }
+
+    model.run_id = model.run_id.next();
+    reset_run_output(model);
+
+    if let Err(violations) = validate::validate(&snippet) {
+        model.run_result = RemoteData::Failure(RunError::InvalidSnippet(violations));
+        return effect::none();
+    }
+
+    model.input_results = snippet
+        .inputs
+        .iter()
+        .map(|input| InputResult {
+            name: input.name.clone(),
+            expected_output: input.expected_output.clone(),
+            result: RemoteData::Loading,
+        })
         .collect();
 
-    model
-        .language
-        .config()
-        .run_instructions(main_file, other_files)
+    let effects = snippet
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| {
+            effect::custom(CustomEffect::RunInput(InputRunRequest {
+                run_id: model.run_id,
+                index,
+                request: RunRequest::with_stdin(&snippet, Some(input.content.clone())),
+            }))
+        })
+        .collect();
+
+    effect::batch(effects)
+}
+
+fn download_local_run_effect(model: &mut Model, format: local::Format) -> Effect<Msg> {
+    let snippet = snippet_from_model(model);
+
+    if let Err(violations) = validate::validate(&snippet) {
+        model.action_error = Some(download_error(&violations));
+        return effect::none();
+    }
+
+    model.action_error = None;
+
+    let request = RunRequest::from_snippet(&snippet);
+
+    effect::custom(CustomEffect::Download(DownloadRequest {
+        filename: format.filename(&snippet),
+        mime_type: format.mime_type().to_string(),
+        bytes: format.render(&snippet.title, &request).into_bytes(),
+    }))
+}
+
+fn get_default_run_instructions(model: &Model) -> RunInstructions {
+    snippet_from_model(model).default_run_instructions()
+}
+
+fn download_archive_effect(model: &mut Model, format: archive::Format) -> Effect<Msg> {
+    let snippet = snippet_from_model(model);
+
+    if let Err(violations) = validate::validate(&snippet) {
+        model.action_error = Some(download_error(&violations));
+        return effect::none();
+    }
+
+    model.action_error = None;
+
+    let archive = archive::create(&snippet, format);
+
+    effect::custom(CustomEffect::Download(DownloadRequest {
+        filename: archive.filename,
+        mime_type: format.mime_type().to_string(),
+        bytes: archive.bytes,
+    }))
+}
+
+// Nothing is downloaded until the snippet is valid, the same as for running and sharing
+fn download_error(violations: &[Violation]) -> String {
+    let problems = violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    format!(
+        "Nothing was downloaded, fix the following first: {}",
+        problems
+    )
+}
+
+fn selected_input(model: &Model) -> Option<&StdinInput> {
+    model.inputs.get(model.selected_input)
 }
 
 fn open_stdin_modal(model: &mut Model) -> Effect<Msg> {
+    let input = match selected_input(model) {
+        Some(input) => input.clone(),
+        None => return open_add_stdin_input_modal(model),
+    };
+
+    let existing_names = model
+        .inputs
+        .iter()
+        .map(|input| input.name.clone())
+        .filter(|name| *name != input.name)
+        .collect();
+
     stdin_modal::open(
         &mut model.stdin_modal_state,
-        &model.stdin.clone().unwrap_or_default(),
+        stdin_modal::Context {
+            input,
+            existing_names,
+            is_new: false,
+        },
     )
 }
 
+fn open_add_stdin_input_modal(model: &mut Model) -> Effect<Msg> {
+    let existing_names: Vec<String> = model
+        .inputs
+        .iter()
+        .map(|input| input.name.clone())
+        .collect();
+
+    // Input 1, Input 2 and so on, skipping names that are already used
+    let name = (model.inputs.len() + 1..)
+        .map(|n| format!("Input {}", n))
+        .find(|name| !existing_names.contains(name))
+        .unwrap_or_default();
+
+    stdin_modal::open(
+        &mut model.stdin_modal_state,
+        stdin_modal::Context {
+            input: StdinInput {
+                name,
+                content: "".to_string(),
+                expected_output: None,
+            },
+            existing_names,
+            is_new: true,
+        },
+    )
+}
+
+fn open_run_options_modal(model: &mut Model) -> Effect<Msg> {
+    run_options_modal::open(&mut model.run_options_modal_state, &model.env, &model.args)
+}
+
 fn open_sharing_modal(model: &mut Model) -> Effect<Msg> {
     sharing_modal::open(&mut model.sharing_modal_state, Msg::SharingModalMsg)
 }
@@ -926,42 +1908,64 @@ fn open_title_modal(model: &mut Model) -> Effect<Msg> {
 }
 
 fn open_settings_modal(model: &mut Model) -> Effect<Msg> {
-    let command = model
-        .custom_command
+    let default_run_instructions = get_default_run_instructions(model);
+
+    let run_instructions = model
+        .custom_run_instructions
         .clone()
-        .unwrap_or_else(|| get_default_run_instructions(model).to_string());
+        .unwrap_or_else(|| default_run_instructions.clone());
 
     settings_modal::open(
         &mut model.settings_modal_state,
         settings_modal::Settings {
             keyboard_bindings: model.editor_keyboard_bindings.clone(),
             theme: model.editor_theme.clone(),
-            command,
+            run_instructions,
+            timeout: model.timeout,
+        },
+        settings_modal::Context {
+            default_run_instructions,
+            template_context: command_template::Context::from_files(&model.files.to_vec()),
         },
     )
 }
 
 fn open_edit_file_modal(model: &mut Model) -> Effect<Msg> {
-    let current_filename = model.files.selected().name.clone();
-
-    let existing_filenames = model
+    let current_file = model.files.selected();
+    let current_filename = current_file.name.clone();
+    let other_files: Vec<File> = model
         .files
         .to_vec()
-        .iter()
-        .map(|file| file.name.clone())
-        .filter(|name| name != &current_filename)
+        .into_iter()
+        .filter(|file| file.name != current_filename)
         .collect();
 
+    let existing_filenames = other_files.iter().map(|file| file.name.clone()).collect();
+    let can_hide = other_files.iter().any(|file| !file.hidden);
+    let is_main = is_main_file(model, &current_filename);
+
     file_modal::open_for_edit(
         &mut model.file_modal_state,
         file_modal::EditContext {
-            filename: model.files.selected().name.clone(),
+            is_main,
+            read_only: current_file.read_only,
+            hidden: current_file.hidden,
+            can_hide,
+            filename: current_filename,
             language: model.language,
             existing_filenames,
         },
     )
 }
 
+fn open_import_modal(model: &mut Model) -> Effect<Msg> {
+    import_modal::open(
+        &mut model.import_modal_state,
+        model.language,
+        model.files.to_vec(),
+    )
+}
+
 fn open_add_file_modal(model: &mut Model) -> Effect<Msg> {
     let existing_filenames = model
         .files
@@ -992,21 +1996,103 @@ fn select_file(model: &mut Model, filename: &str) -> Effect<Msg> {
         model.files.select_index(index);
     }
 
+    // Make sure the selected file is visible in the file tree
+    model
+        .collapsed_directories
+        .retain(|directory| !filename.starts_with(&format!("{}/", directory)));
+
     focus_editor_effect()
 }
 
+fn set_main_file(model: &mut Model) -> Effect<Msg> {
+    let selected_index = model.files.selected_index();
+
+    let files = model
+        .files
+        .to_vec()
+        .into_iter()
+        .enumerate()
+        .map(|(index, file)| File {
+            main: index == selected_index,
+            ..file
+        })
+        .collect();
+
+    if let Some(files) = SelectList::from_vec(files) {
+        model.files = files;
+        model.files.select_index(selected_index);
+    }
+
+    effect::batch(vec![
+        save_session_snippet_effect(model),
+        focus_editor_effect(),
+    ])
+}
+
 fn go_to_home(model: &Model) -> Effect<Msg> {
     let route = Route::Home;
     let url = route.to_absolute_path(&model.browser_ctx.current_url);
     navigation::set_location(&url)
 }
 
+// The original is the snippet from the url, the title is not part of the fingerprint
+fn has_changes(model: &Model) -> bool {
+    let current = snippet_from_model(model);
+
+    model.snippet.as_ref().is_some_and(|original| {
+        original.title != current.title || original.fingerprint() != current.fingerprint()
+    })
+}
+
+// The output is only exported with the snippet if the snippet hasn't changed since the run
+fn current_run_result(model: &Model) -> Option<RunResult> {
+    let fingerprint = snippet_from_model(model).fingerprint();
+
+    model
+        .run_result
+        .success()
+        .filter(|_| model.run_fingerprint.as_ref() == Some(&fingerprint))
+        .cloned()
+}
+
+fn compare_with_original(model: &mut Model) -> Effect<Msg> {
+    let original = match model.browser_ctx.current_route() {
+        Route::EditSnippet(_, encoded_snippet) => encoded_snippet,
+        _ => return effect::none(),
+    };
+
+    match snippet_from_model(model).to_encoded_string() {
+        Ok(current) => {
+            let route = Route::DiffSnippet(original, current);
+            let url = route.to_absolute_path(&model.browser_ctx.current_url);
+
+            // Both snippets are in the url, so it's longer than the sharing url
+            if url.len() > route::MAX_URL_LENGTH {
+                model.action_error = Some(format!(
+                    "The snippets are too large to compare, the comparison url would be {} characters (max {})",
+                    url.len(),
+                    route::MAX_URL_LENGTH
+                ));
+                return effect::none();
+            }
+
+            model.action_error = None;
+            navigation::set_location(&url)
+        }
+
+        Err(err) => console::log(&format!("Failed to encode snippet: {}", err)),
+    }
+}
+
 fn snippet_from_model(model: &Model) -> Snippet {
     Snippet {
         title: model.title.clone(),
         files: model.files.to_vec(),
-        stdin: model.stdin.clone(),
+        inputs: model.inputs.clone(),
         language: model.language,
-        command: model.custom_command.clone(),
+        instructions: model.custom_run_instructions.clone(),
+        env: model.env.clone(),
+        args: model.args.clone(),
+        timeout: model.timeout,
     }
 }
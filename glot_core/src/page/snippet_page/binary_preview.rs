@@ -0,0 +1,60 @@
+use crate::snippet::validate;
+use crate::snippet::File;
+use maud::html;
+use maud::Markup;
+
+// Only the start of the file is shown, the rest can't be edited anyway
+const PREVIEW_SIZE: usize = 512;
+const BYTES_PER_LINE: usize = 16;
+
+pub fn view(file: &File, inline_styles: &str) -> Markup {
+    let bytes = file.bytes();
+    let preview = &bytes[..bytes.len().min(PREVIEW_SIZE)];
+
+    html! {
+        div class="w-full overflow-auto bg-white px-4 py-3" style=(inline_styles) {
+            p class="text-sm text-gray-700" {
+                span class="font-semibold" { "Binary file" }
+                ", " (validate::format_size(bytes.len()))
+                span class="text-gray-500" { " — binary files can't be edited" }
+            }
+
+            pre class="mt-3 text-sm text-gray-700" {
+                (hex_dump(preview))
+            }
+
+            @if bytes.len() > preview.len() {
+                p class="mt-2 text-sm text-gray-500" {
+                    "Showing the first " (preview.len()) " of " (bytes.len()) " bytes"
+                }
+            }
+        }
+    }
+}
+
+// Offset, hex bytes and printable ascii, the same layout as `hexdump -C`
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+            let ascii: String = chunk
+                .iter()
+                .map(|byte| match byte {
+                    0x20..=0x7e => *byte as char,
+                    _ => '.',
+                })
+                .collect();
+
+            format!(
+                "{:08x}  {:<47}  |{}|",
+                index * BYTES_PER_LINE,
+                hex.join(" "),
+                ascii
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,10 +1,19 @@
 use std::time::Duration;
 
-use crate::run::FailedRunResult;
+use crate::error::RunError;
+use crate::run::stream::RunStream;
+use crate::run::RunId;
 use crate::run::RunResult;
+use crate::snippet::expected_output;
+use crate::snippet::expected_output::CheckResult;
+use crate::snippet::expected_output::ExpectedOutput;
+use crate::util::ansi;
+use crate::util::diff::Line;
 use crate::util::remote_data::RemoteData;
 use maud::html;
 use maud::Markup;
+use poly::browser::dom_id::DomId;
+use serde::{Deserialize, Serialize};
 
 const LOADING_TEXT: &str = r#"
 LOAD"*",8,1
@@ -13,38 +22,119 @@ SEARCHING FOR *
 LOADING
 "#;
 
-pub struct ViewModel<'a> {
-    pub run_result: &'a RemoteData<FailedRunResult, RunResult>,
-    pub version_result: &'a RemoteData<FailedRunResult, RunResult>,
+// Result of running the snippet with one of the stdin inputs
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputResult {
+    pub name: String,
+    pub expected_output: Option<ExpectedOutput>,
+    pub result: RemoteData<RunError, RunResult>,
 }
 
-pub fn view(model: ViewModel) -> Markup {
+pub struct ViewModel<'a, Id> {
+    pub run_id: RunId,
+    pub raw_output_toggle_id: Id,
+    // Escape sequences are shown instead of interpreted
+    pub show_raw_output: bool,
+    pub run_result: &'a RemoteData<RunError, RunResult>,
+    pub run_stream: &'a RunStream,
+    pub expected_output: Option<&'a ExpectedOutput>,
+    pub input_results: &'a [InputResult],
+    pub version_result: &'a RemoteData<RunError, RunResult>,
+}
+
+pub fn view<Id: DomId>(model: ViewModel<Id>) -> Markup {
     let ready_info = extract_language_version(&model)
         .map(|version| format!("{}\nREADY.", version))
         .unwrap_or_default();
 
     html! {
         div class="h-full border-b border-x border-gray-400 shadow-lg" {
-            dl {
-                @match &model.run_result {
-                    RemoteData::NotAsked => {
-                        (view_info(&ready_info))
+            @if !model.run_id.is_initial() {
+                (view_run_header(model.run_id, &model.raw_output_toggle_id, model.show_raw_output))
+            }
+
+            @if model.input_results.is_empty() {
+                dl {
+                    (view_result(model.run_result, Some(model.run_stream), model.expected_output, &ready_info, model.show_raw_output))
+                }
+            } @else {
+                @for input_result in model.input_results {
+                    (view_input_result(input_result, model.show_raw_output))
+                }
+            }
+        }
+    }
+}
+
+fn view_result(
+    result: &RemoteData<RunError, RunResult>,
+    stream: Option<&RunStream>,
+    expected_output: Option<&ExpectedOutput>,
+    ready_info: &str,
+    show_raw: bool,
+) -> Markup {
+    html! {
+        @match result {
+            RemoteData::NotAsked => {
+                (view_info(ready_info))
+            }
+
+            RemoteData::Loading => {
+                @match stream.filter(|stream| !stream.is_empty()) {
+                    Some(stream) => {
+                        (view_stream(stream, "RUNNING", show_raw))
                     }
 
-                    RemoteData::Loading => {
+                    None => {
                         (view_info(LOADING_TEXT))
                     }
+                }
+            }
 
-                    RemoteData::Success(run_result) => {
-                        @if run_result.is_empty() {
-                            (view_info("EMPTY OUTPUT"))
-                        } @else {
-                            (view_run_result(run_result))
-                        }
+            RemoteData::Success(run_result) => {
+                @if let Some(expected_output) = expected_output {
+                    (view_check_result(&expected_output::check(expected_output, &run_result.stdout)))
+                }
+
+                @if run_result.is_empty() {
+                    (view_info(&format!("EMPTY OUTPUT\n{}", result_summary(run_result))))
+                } @else {
+                    (view_run_result(run_result, show_raw))
+                }
+            }
+
+            // The output received before the run was cancelled is kept
+            RemoteData::Failure(RunError::Cancelled) => {
+                @match stream.filter(|stream| !stream.is_empty()) {
+                    Some(stream) => {
+                        (view_stream(stream, "CANCELLED", show_raw))
+                    }
+
+                    None => {
+                        (view_info("CANCELLED"))
                     }
+                }
+            }
+
+            RemoteData::Failure(err) => {
+                (view_info(&format!("ERROR: {}", err)))
+            }
+        }
+    }
+}
 
-                    RemoteData::Failure(err) => {
-                        (view_info(&format!("ERROR: {}", err.message)))
+// Shows which run the output belongs to, the output of earlier runs is discarded
+fn view_run_header<Id: DomId>(run_id: RunId, raw_output_toggle_id: &Id, show_raw: bool) -> Markup {
+    html! {
+        div class="px-4 py-1 flex justify-between border-t border-gray-400 text-sm text-slate-700 font-bold bg-gray-100" {
+            pre { "RUN " (run_id) }
+            button id=(raw_output_toggle_id) class="hover:text-slate-900" type="button" {
+                pre {
+                    @if show_raw {
+                        "SHOW FORMATTED"
+                    } @else {
+                        "SHOW RAW"
                     }
                 }
             }
@@ -52,6 +142,35 @@ pub fn view(model: ViewModel) -> Markup {
     }
 }
 
+fn view_input_result(input_result: &InputResult, show_raw: bool) -> Markup {
+    let status = match (&input_result.result, &input_result.expected_output) {
+        (RemoteData::NotAsked, _) => "",
+        (RemoteData::Loading, _) => "RUNNING",
+        (RemoteData::Success(run_result), Some(expected_output)) => {
+            if expected_output::check(expected_output, &run_result.stdout).is_passed() {
+                "PASSED"
+            } else {
+                "FAILED"
+            }
+        }
+        (RemoteData::Success(_), None) => "DONE",
+        (RemoteData::Failure(RunError::Cancelled), _) => "CANCELLED",
+        (RemoteData::Failure(_), _) => "ERROR",
+    };
+
+    html! {
+        details open {
+            summary class="px-4 py-1 flex justify-between border-t border-gray-400 text-sm text-slate-700 font-bold bg-gray-200 cursor-pointer" {
+                pre { "STDIN: " (input_result.name) }
+                pre { (status) }
+            }
+            dl {
+                (view_result(&input_result.result, None, input_result.expected_output.as_ref(), "", show_raw))
+            }
+        }
+    }
+}
+
 fn view_info(text: &str) -> Markup {
     html! {
         dt class="px-4 py-1 border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-blue-400" {
@@ -63,36 +182,82 @@ fn view_info(text: &str) -> Markup {
     }
 }
 
-fn view_run_result(run_result: &RunResult) -> Markup {
-    let duration = format!("{:.2?}", Duration::from_nanos(run_result.duration));
+fn view_check_result(check_result: &CheckResult) -> Markup {
+    html! {
+        @match check_result {
+            CheckResult::Passed => {
+                dt class="px-4 py-1 border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-green-400" {
+                    pre { "PASSED" }
+                }
+            }
+
+            CheckResult::Failed { hunks } => {
+                dt class="px-4 py-1 flex justify-between border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-red-400" {
+                    pre { "FAILED" }
+                    pre { "- expected + actual" }
+                }
+                dd class="py-2 overflow-y-auto" {
+                    @if hunks.is_empty() {
+                        pre class="px-4" { "The output doesn't match the expected output" }
+                    }
+
+                    @for hunk in hunks {
+                        @for line in &hunk.lines {
+                            (view_diff_line(line))
+                        }
+                    }
+                }
+            }
+
+            CheckResult::InvalidPattern(err) => {
+                (view_info(&format!("INVALID EXPECTED OUTPUT PATTERN: {}", err)))
+            }
+        }
+    }
+}
+
+fn view_diff_line(line: &Line) -> Markup {
+    let (prefix, class, text) = match line {
+        Line::Context(text) => (" ", "", text),
+        Line::Added(text) => ("+", "bg-green-50 text-green-900", text),
+        Line::Removed(text) => ("-", "bg-red-50 text-red-900", text),
+    };
 
+    html! {
+        pre class=(format!("px-4 {}", class)) { (prefix) (text) }
+    }
+}
+
+fn view_run_result(run_result: &RunResult, show_raw: bool) -> Markup {
     html! {
         @if !run_result.stdout.is_empty() {
             dt class="px-4 py-1 flex justify-between border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-green-400" {
                 pre { "STDOUT" }
-                pre { (duration) }
+                pre { (result_summary(run_result)) }
             }
             dd class="px-4 py-2 overflow-y-auto" {
-                pre {
-                    (run_result.stdout)
-                }
+                (view_output(&run_result.stdout, show_raw))
             }
         }
 
         @if !run_result.stderr.is_empty() {
-            dt class="px-4 py-1 border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-yellow-400" {
+            dt class="px-4 py-1 flex justify-between border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-yellow-400" {
                 pre { "STDERR" }
+                @if run_result.stdout.is_empty() {
+                    pre { (result_summary(run_result)) }
+                }
             }
             dd class="px-4 py-2 overflow-y-auto" {
-                pre {
-                    (run_result.stderr)
-                }
+                (view_output(&run_result.stderr, show_raw))
             }
         }
 
         @if !run_result.error.is_empty() {
-            dt class="px-4 py-1 border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-red-400" {
+            dt class="px-4 py-1 flex justify-between border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-red-400" {
                 pre { "ERROR" }
+                @if run_result.stdout.is_empty() && run_result.stderr.is_empty() {
+                    pre { (result_summary(run_result)) }
+                }
             }
             dd class="px-4 py-2 overflow-y-auto" {
                 pre {
@@ -103,7 +268,106 @@ fn view_run_result(run_result: &RunResult) -> Markup {
     }
 }
 
-fn extract_language_version(model: &ViewModel) -> Option<String> {
+// Shown in the header of the first output section, the fields are left out when the backend doesn't send them
+fn result_summary(run_result: &RunResult) -> String {
+    let exit_code = run_result
+        .exit_code
+        .map(|exit_code| format!("EXIT {}", exit_code));
+
+    let peak_memory = run_result
+        .peak_memory
+        .map(|bytes| format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)));
+
+    let truncated = run_result.truncated.then(|| "TRUNCATED".to_string());
+
+    let duration = format!("{:.2?}", Duration::from_nanos(run_result.duration));
+
+    [
+        exit_code,
+        run_result.signal.clone(),
+        peak_memory,
+        truncated,
+        Some(duration),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ")
+}
+
+// Output of a run that hasn't finished yet or was cancelled
+fn view_stream(stream: &RunStream, status: &str, show_raw: bool) -> Markup {
+    html! {
+        @if !stream.stdout.is_empty() {
+            dt class="px-4 py-1 flex justify-between border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-green-400" {
+                pre { "STDOUT" }
+                pre { (status) }
+            }
+            dd class="px-4 py-2 overflow-y-auto" {
+                (view_output(&stream.stdout, show_raw))
+            }
+        }
+
+        @if !stream.stderr.is_empty() {
+            dt class="px-4 py-1 border-t border-b border-gray-400 text-sm text-slate-700 font-bold bg-yellow-400" {
+                pre { "STDERR" }
+            }
+            dd class="px-4 py-2 overflow-y-auto" {
+                (view_output(&stream.stderr, show_raw))
+            }
+        }
+    }
+}
+
+// Colors and styles from escape sequences in the program output
+fn view_output(text: &str, show_raw: bool) -> Markup {
+    html! {
+        pre {
+            @if show_raw {
+                (ansi::visible_controls(text))
+            } @else {
+                @for line in ansi::parse(text) {
+                    @for span in line {
+                        @if span.style.is_plain() {
+                            (span.text)
+                        } @else {
+                            (view_styled_span(&span))
+                        }
+                    }
+                    "\n"
+                }
+            }
+        }
+    }
+}
+
+fn view_styled_span(span: &ansi::Span) -> Markup {
+    let style = &span.style;
+    let (foreground, background) = style.colors();
+
+    let inline_style = [
+        foreground.map(|color| format!("color: {}", color.to_hex())),
+        background.map(|color| format!("background-color: {}", color.to_hex())),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join("; ");
+
+    html! {
+        span
+            .font-bold[style.bold]
+            .opacity-75[style.dim]
+            .italic[style.italic]
+            .underline[style.underline]
+            style=[(!inline_style.is_empty()).then_some(&inline_style)]
+        {
+            (span.text)
+        }
+    }
+}
+
+fn extract_language_version<Id>(model: &ViewModel<Id>) -> Option<String> {
     if let RemoteData::Success(run_result) = model.version_result {
         if run_result.stdout.is_empty() {
             None
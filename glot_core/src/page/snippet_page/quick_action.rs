@@ -2,6 +2,8 @@ use crate::common::keyboard_shortcut::KeyboardShortcut;
 use crate::common::quick_action;
 use crate::common::quick_action::LanguageQuickAction;
 use crate::components::search_modal;
+use crate::run::local;
+use crate::snippet::archive;
 use crate::snippet::File;
 use crate::util::user_agent::UserAgent;
 use std::fmt;
@@ -12,9 +14,20 @@ pub enum QuickAction {
     EditTitle,
     EditFile,
     EditStdin,
+    AddStdinInput,
+    RunAllInputs,
+    EditRunOptions,
     AddFile,
+    ImportFiles,
     Settings,
     Share,
+    DownloadArchive(archive::Format),
+    DownloadLocalRun(local::Format),
+    CompareWithOriginal,
+    SetMainFile,
+    MoveFileLeft,
+    MoveFileRight,
+    ToggleHiddenFiles,
     SelectFile(String),
     GoToFrontPage,
     GoToLanguage(LanguageQuickAction),
@@ -27,8 +40,21 @@ impl search_modal::EntryExtra for QuickAction {
             QuickAction::EditTitle => "Edit title".into(),
             QuickAction::EditFile => "Edit file".into(),
             QuickAction::EditStdin => "Edit stdin data".into(),
+            QuickAction::AddStdinInput => "Add stdin input".into(),
+            QuickAction::RunAllInputs => "Run all stdin inputs".into(),
+            QuickAction::EditRunOptions => "Edit arguments and environment variables".into(),
             QuickAction::AddFile => "Add file".into(),
+            QuickAction::ImportFiles => "Import files".into(),
             QuickAction::Share => "Open sharing dialog".into(),
+            QuickAction::DownloadArchive(format) => {
+                format!("Download as {} archive", format.extension())
+            }
+            QuickAction::DownloadLocalRun(format) => format!("Download {}", format.label()),
+            QuickAction::CompareWithOriginal => "Compare with original".into(),
+            QuickAction::SetMainFile => "Set current file as main file".into(),
+            QuickAction::MoveFileLeft => "Move file left".into(),
+            QuickAction::MoveFileRight => "Move file right".into(),
+            QuickAction::ToggleHiddenFiles => "Show or hide hidden files".into(),
             QuickAction::Settings => "Open settings".into(),
             QuickAction::SelectFile(name) => format!("Select {}", name),
             QuickAction::GoToFrontPage => "Go to front page".into(),
@@ -42,8 +68,50 @@ impl search_modal::EntryExtra for QuickAction {
             QuickAction::EditTitle => vec!["edit".into(), "title".into()],
             QuickAction::EditFile => vec!["edit".into(), "file".into()],
             QuickAction::EditStdin => vec!["edit".into(), "stdin".into()],
+            QuickAction::AddStdinInput => vec!["add".into(), "stdin".into(), "input".into()],
+            QuickAction::RunAllInputs => vec!["run".into(), "all".into(), "inputs".into()],
+            QuickAction::EditRunOptions => vec![
+                "edit".into(),
+                "arguments".into(),
+                "args".into(),
+                "environment".into(),
+                "env".into(),
+            ],
             QuickAction::AddFile => vec!["add".into(), "file".into()],
+            QuickAction::ImportFiles => vec![
+                "import".into(),
+                "upload".into(),
+                "files".into(),
+                "archive".into(),
+            ],
             QuickAction::Share => vec!["open".into(), "sharing".into(), "share".into()],
+            QuickAction::DownloadArchive(format) => vec![
+                "download".into(),
+                "export".into(),
+                "archive".into(),
+                format.extension().into(),
+            ],
+            QuickAction::DownloadLocalRun(format) => vec![
+                "download".into(),
+                "export".into(),
+                "local".into(),
+                "docker".into(),
+                format.label().into(),
+            ],
+            QuickAction::CompareWithOriginal => {
+                vec!["compare".into(), "diff".into(), "changes".into()]
+            }
+            QuickAction::SetMainFile => vec!["main".into(), "file".into(), "entry".into()],
+            QuickAction::MoveFileLeft => vec!["move".into(), "file".into(), "left".into()],
+            QuickAction::MoveFileRight => vec!["move".into(), "file".into(), "right".into()],
+            QuickAction::ToggleHiddenFiles => {
+                vec![
+                    "toggle".into(),
+                    "show".into(),
+                    "hidden".into(),
+                    "files".into(),
+                ]
+            }
             QuickAction::Settings => vec!["open".into(), "settings".into()],
             QuickAction::SelectFile(name) => vec!["select".into(), name.clone()],
             QuickAction::GoToFrontPage => vec!["home".into(), "frontpage".into()],
@@ -57,8 +125,19 @@ impl search_modal::EntryExtra for QuickAction {
             QuickAction::EditTitle => heroicons_maud::pencil_square_outline(),
             QuickAction::EditFile => heroicons_maud::pencil_square_outline(),
             QuickAction::EditStdin => heroicons_maud::pencil_square_outline(),
+            QuickAction::AddStdinInput => heroicons_maud::plus_outline(),
+            QuickAction::RunAllInputs => heroicons_maud::play_outline(),
+            QuickAction::EditRunOptions => heroicons_maud::command_line_outline(),
             QuickAction::AddFile => heroicons_maud::document_plus_outline(),
+            QuickAction::ImportFiles => heroicons_maud::arrow_up_tray_outline(),
             QuickAction::Share => heroicons_maud::share_outline(),
+            QuickAction::DownloadArchive(_) => heroicons_maud::arrow_down_tray_outline(),
+            QuickAction::DownloadLocalRun(_) => heroicons_maud::command_line_outline(),
+            QuickAction::CompareWithOriginal => heroicons_maud::arrows_right_left_outline(),
+            QuickAction::SetMainFile => heroicons_maud::star_outline(),
+            QuickAction::MoveFileLeft => heroicons_maud::arrow_left_outline(),
+            QuickAction::MoveFileRight => heroicons_maud::arrow_right_outline(),
+            QuickAction::ToggleHiddenFiles => heroicons_maud::eye_slash_outline(),
             QuickAction::Settings => heroicons_maud::cog_6_tooth_outline(),
             QuickAction::SelectFile(_) => heroicons_maud::document_outline(),
             QuickAction::GoToFrontPage => heroicons_maud::link_outline(),
@@ -84,8 +163,24 @@ impl fmt::Display for QuickAction {
             QuickAction::EditTitle => write!(f, "edit-title"),
             QuickAction::EditFile => write!(f, "edit-file"),
             QuickAction::EditStdin => write!(f, "edit-stdin"),
+            QuickAction::AddStdinInput => write!(f, "add-stdin-input"),
+            QuickAction::RunAllInputs => write!(f, "run-all-inputs"),
+            QuickAction::EditRunOptions => write!(f, "edit-run-options"),
             QuickAction::AddFile => write!(f, "add-file"),
+            QuickAction::ImportFiles => write!(f, "import-files"),
             QuickAction::Share => write!(f, "share"),
+            QuickAction::DownloadArchive(format) => write!(f, "download-{}", format.extension()),
+            QuickAction::DownloadLocalRun(local::Format::ShellScript) => {
+                write!(f, "download-run-script")
+            }
+            QuickAction::DownloadLocalRun(local::Format::Dockerfile) => {
+                write!(f, "download-dockerfile")
+            }
+            QuickAction::CompareWithOriginal => write!(f, "compare-with-original"),
+            QuickAction::SetMainFile => write!(f, "set-main-file"),
+            QuickAction::MoveFileLeft => write!(f, "move-file-left"),
+            QuickAction::MoveFileRight => write!(f, "move-file-right"),
+            QuickAction::ToggleHiddenFiles => write!(f, "toggle-hidden-files"),
             QuickAction::Settings => write!(f, "settings"),
             QuickAction::SelectFile(name) => write!(f, "select-file-{}", name),
             QuickAction::GoToFrontPage => write!(f, "go-to-front-page"),
@@ -94,18 +189,50 @@ impl fmt::Display for QuickAction {
     }
 }
 
-pub fn actions(files: Vec<File>) -> Vec<search_modal::Entry<QuickAction>> {
-    let snippet_actions = vec![
+pub fn actions(
+    files: Vec<File>,
+    has_changes: bool,
+    has_hidden_files: bool,
+    has_multiple_inputs: bool,
+) -> Vec<search_modal::Entry<QuickAction>> {
+    let mut snippet_actions = vec![
         QuickAction::Run,
         QuickAction::EditTitle,
         QuickAction::EditFile,
         QuickAction::EditStdin,
+        QuickAction::AddStdinInput,
+        QuickAction::EditRunOptions,
         QuickAction::AddFile,
+        QuickAction::ImportFiles,
         QuickAction::Share,
+        QuickAction::DownloadArchive(archive::Format::Zip),
+        QuickAction::DownloadArchive(archive::Format::Tar),
+        QuickAction::DownloadLocalRun(local::Format::ShellScript),
+        QuickAction::DownloadLocalRun(local::Format::Dockerfile),
         QuickAction::Settings,
         QuickAction::GoToFrontPage,
     ];
 
+    if has_changes {
+        snippet_actions.push(QuickAction::CompareWithOriginal);
+    }
+
+    if files.len() > 1 {
+        snippet_actions.extend([
+            QuickAction::SetMainFile,
+            QuickAction::MoveFileLeft,
+            QuickAction::MoveFileRight,
+        ]);
+    }
+
+    if has_multiple_inputs {
+        snippet_actions.push(QuickAction::RunAllInputs);
+    }
+
+    if has_hidden_files {
+        snippet_actions.push(QuickAction::ToggleHiddenFiles);
+    }
+
     let file_actions = files
         .iter()
         .map(|file| QuickAction::SelectFile(file.name.clone()))
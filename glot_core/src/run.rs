@@ -1,5 +1,13 @@
+use crate::error::RunError;
+use crate::snippet::command_template;
 use crate::snippet::File;
+use crate::snippet::Snippet;
 use glot_languages::language::RunInstructions;
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub mod local;
+pub mod stream;
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -8,6 +16,68 @@ pub struct RunRequest {
     pub payload: RunRequestPayload,
 }
 
+impl RunRequest {
+    pub fn from_snippet(snippet: &Snippet) -> RunRequest {
+        Self::with_stdin(snippet, snippet.stdin())
+    }
+
+    pub fn with_stdin(snippet: &Snippet, stdin: Option<String>) -> RunRequest {
+        let run_config = snippet.language.config().run_config();
+        let mut files = snippet.files.clone();
+
+        if snippet.uses_stdin_file() {
+            files.push(File::text(
+                command_template::STDIN_FILENAME.to_string(),
+                stdin.clone().unwrap_or_default(),
+            ));
+        }
+
+        RunRequest {
+            image: run_config.container_image,
+            payload: RunRequestPayload {
+                run_instructions: snippet.run_instructions(),
+                files,
+                stdin: stdin.filter(|stdin| !stdin.is_empty()),
+                env: snippet.env.clone(),
+                args: snippet.args.clone(),
+                timeout: snippet.timeout,
+            },
+        }
+    }
+}
+
+// Increases with every run and is sent back with the response, so responses of older runs can be ignored
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct RunId(u64);
+
+impl RunId {
+    pub fn next(&self) -> RunId {
+        RunId(self.0 + 1)
+    }
+
+    // No run has been started yet
+    pub fn is_initial(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for RunId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunResult {
@@ -15,6 +85,18 @@ pub struct RunResult {
     pub stdout: String,
     pub stderr: String,
     pub error: String,
+    // Not sent by older backends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    // Name of the signal that terminated the program, i.e. SIGKILL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+    // Bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory: Option<u64>,
+    // The backend cut off stdout or stderr because it was too large
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
 
 impl RunResult {
@@ -29,17 +111,46 @@ pub struct FailedRunResult {
     pub message: String,
 }
 
+// Sent from JS when the request was aborted
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledRunResult {
+    pub cancelled: bool,
+}
+
 #[derive(Clone, serde::Deserialize)]
 #[serde(untagged)]
 pub enum RunOutcome {
     Success(RunResult),
+    Cancelled(CancelledRunResult),
     Failure(FailedRunResult),
 }
 
+impl RunOutcome {
+    pub fn from_value(value: serde_json::Value) -> Result<RunOutcome, RunError> {
+        serde_json::from_value(value).map_err(|err| RunError::InvalidResponse(err.to_string()))
+    }
+
+    pub fn into_result(self) -> Result<RunResult, RunError> {
+        match self {
+            RunOutcome::Success(run_result) => Ok(run_result),
+            RunOutcome::Cancelled(_) => Err(RunError::Cancelled),
+            RunOutcome::Failure(err) => Err(RunError::Transport(err.message)),
+        }
+    }
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RunRequestPayload {
     pub run_instructions: RunInstructions,
     pub files: Vec<File>,
     pub stdin: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // Seconds, the backend default is used when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
 }
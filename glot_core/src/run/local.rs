@@ -0,0 +1,261 @@
+use crate::run::RunRequest;
+use crate::snippet::Snippet;
+use crate::util::base64;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Files are copied from here into the container's working directory before building
+const FILES_MOUNT_PATH: &str = "/glot-files";
+const DOCKERFILE_STDIN_PATH: &str = "/tmp/glot-stdin";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Format {
+    ShellScript,
+    Dockerfile,
+}
+
+impl Format {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Format::ShellScript => "run script",
+            Format::Dockerfile => "Dockerfile",
+        }
+    }
+
+    pub fn filename(&self, snippet: &Snippet) -> String {
+        match self {
+            Format::ShellScript => format!("run-{}.sh", snippet.slug()),
+            Format::Dockerfile => "Dockerfile".to_string(),
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Format::ShellScript => "text/x-shellscript",
+            Format::Dockerfile => "text/plain",
+        }
+    }
+
+    pub fn render(&self, title: &str, request: &RunRequest) -> String {
+        match self {
+            Format::ShellScript => shell_script(title, request),
+            Format::Dockerfile => dockerfile(title, request),
+        }
+    }
+}
+
+// Shell script that writes the files to a temporary directory and runs them in the same image as glot.io
+pub fn shell_script(title: &str, request: &RunRequest) -> String {
+    let payload = &request.payload;
+    let mut lines = vec![
+        "#!/bin/sh".to_string(),
+        format!(
+            "# Runs the glot.io snippet \"{}\" locally with docker",
+            comment_text(title)
+        ),
+        "set -eu".to_string(),
+        String::new(),
+        format!("image={}", quote(&request.image)),
+        "workdir=\"$(mktemp -d)\"".to_string(),
+        "trap 'rm -rf \"$workdir\"' EXIT".to_string(),
+        "mkdir -p \"$workdir/files\"".to_string(),
+        String::new(),
+    ];
+
+    for file in &payload.files {
+        let path = format!("\"$workdir/files/\"{}", quote(&file.name));
+
+        if let Some((directory, _)) = file.name.rsplit_once('/') {
+            lines.push(format!("mkdir -p \"$workdir/files/\"{}", quote(directory)));
+        }
+
+        if file.is_binary() {
+            lines.push(format!(
+                "printf '%s' {} | base64 -d > {}",
+                quote(&base64::encode(&file.bytes())),
+                path
+            ));
+        } else {
+            lines.push(format!("printf '%s' {} > {}", quote(&file.content), path));
+        }
+    }
+
+    let stdin_redirect = match &payload.stdin {
+        Some(stdin) => {
+            lines.push(format!("printf '%s' {} > \"$workdir/stdin\"", quote(stdin)));
+            "\"$workdir/stdin\""
+        }
+
+        None => "/dev/null",
+    };
+
+    let command = format!("cp -R {}/. . && {}", FILES_MOUNT_PATH, commands(request));
+
+    lines.extend([
+        String::new(),
+        "# The image runs as an unprivileged user that needs to read the files".to_string(),
+        "chmod -R a+rX \"$workdir\"".to_string(),
+        String::new(),
+        "docker pull \"$image\"".to_string(),
+        format!(
+            "docker run --rm -i -v \"$workdir/files:{}:ro\" \"$image\" sh -c {} < {}",
+            FILES_MOUNT_PATH,
+            quote(&command),
+            stdin_redirect
+        ),
+    ]);
+
+    lines.join("\n") + "\n"
+}
+
+// Dockerfile that bakes the files into the image, the build and run commands are executed by `docker run`
+pub fn dockerfile(title: &str, request: &RunRequest) -> String {
+    let payload = &request.payload;
+    let mut lines = vec![
+        format!(
+            "# Runs the glot.io snippet \"{}\" locally",
+            comment_text(title)
+        ),
+        "# Usage: docker build -t glot-snippet . && docker run --rm glot-snippet".to_string(),
+        format!("FROM {}", request.image),
+    ];
+
+    for file in &payload.files {
+        let mkdir = match file.name.rsplit_once('/') {
+            Some((directory, _)) => format!("mkdir -p {} && ", quote(directory)),
+            None => String::new(),
+        };
+
+        if file.is_binary() {
+            lines.push(format!(
+                "RUN {}printf '%s' {} | base64 -d > {}",
+                mkdir,
+                quote(&base64::encode(&file.bytes())),
+                quote(&file.name)
+            ));
+        } else {
+            lines.push(format!(
+                "RUN {}printf '%b' {} > {}",
+                mkdir,
+                quote(&escape_for_printf(&file.content)),
+                quote(&file.name)
+            ));
+        }
+    }
+
+    let stdin = payload.stdin.clone().unwrap_or_default();
+    lines.push(format!(
+        "RUN printf '%b' {} > {}",
+        quote(&escape_for_printf(&stdin)),
+        DOCKERFILE_STDIN_PATH
+    ));
+
+    let command = format!("{} < {}", commands(request), DOCKERFILE_STDIN_PATH);
+    let cmd = serde_json::to_string(&["sh", "-c", &command]).unwrap_or_default();
+    lines.push(format!("CMD {}", cmd));
+
+    lines.join("\n") + "\n"
+}
+
+// Build commands don't get stdin, same as on glot.io
+fn commands(request: &RunRequest) -> String {
+    let payload = &request.payload;
+    let run_instructions = &payload.run_instructions;
+
+    exports(&payload.env)
+        .into_iter()
+        .chain(
+            run_instructions
+                .build_commands
+                .iter()
+                .map(|command| format!("{} < /dev/null", command)),
+        )
+        .chain(std::iter::once(format!(
+            "({})",
+            with_args(&run_instructions.run_command, &payload.args)
+        )))
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+// Environment variable names are validated, so only the values need quoting
+pub(crate) fn exports(env: &BTreeMap<String, String>) -> Vec<String> {
+    env.iter()
+        .map(|(name, value)| format!("export {}={}", name, quote(value)))
+        .collect()
+}
+
+pub(crate) fn with_args(command: &str, args: &[String]) -> String {
+    args.iter().fold(command.to_string(), |command, arg| {
+        format!("{} {}", command, quote(arg))
+    })
+}
+
+// A newline in the title would end the comment and add a command to the script
+fn comment_text(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// Dockerfile instructions must be on a single line, so newlines are encoded as printf escapes
+fn escape_for_printf(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::RunRequestPayload;
+    use crate::snippet::File;
+    use glot_languages::language::RunInstructions;
+
+    fn request() -> RunRequest {
+        RunRequest {
+            image: "glot/python:latest".to_string(),
+            payload: RunRequestPayload {
+                run_instructions: RunInstructions {
+                    build_commands: vec![],
+                    run_command: "python main.py".to_string(),
+                },
+                files: vec![File::text(
+                    "main.py".to_string(),
+                    "print('hi')\n".to_string(),
+                )],
+                stdin: None,
+                env: BTreeMap::new(),
+                args: vec![],
+                timeout: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_title_cannot_add_commands() {
+        let title = "x\"\nrm -rf ~\n# ";
+
+        let script = shell_script(title, &request());
+        assert!(!script.lines().any(|line| line.starts_with("rm ")));
+        assert_eq!(
+            script.lines().nth(1),
+            Some("# Runs the glot.io snippet \"x\" rm -rf ~ # \" locally with docker")
+        );
+
+        let dockerfile = dockerfile(title, &request());
+        assert!(!dockerfile.lines().any(|line| line.starts_with("rm ")));
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(quote("it's"), "'it'\\''s'");
+        assert_eq!(with_args("./main", &["a b".to_string()]), "./main 'a b'");
+    }
+}
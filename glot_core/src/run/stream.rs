@@ -0,0 +1,295 @@
+use crate::error::RunError;
+use crate::run::RunResult;
+use serde::{Deserialize, Serialize};
+
+// One event of a streamed run. Sequence numbers start at 0 and have no gaps,
+// the last event is either finished, failed or cancelled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvent {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub kind: RunEventKind,
+}
+
+impl RunEvent {
+    pub fn from_value(value: serde_json::Value) -> Result<RunEvent, RunError> {
+        serde_json::from_value(value).map_err(|err| RunError::InvalidResponse(err.to_string()))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RunEventKind {
+    Stdout {
+        text: String,
+    },
+    Stderr {
+        text: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Finished {
+        duration: u64,
+        error: String,
+        #[serde(default)]
+        exit_code: Option<i32>,
+        #[serde(default)]
+        signal: Option<String>,
+        #[serde(default)]
+        peak_memory: Option<u64>,
+        #[serde(default)]
+        truncated: bool,
+    },
+    Failed {
+        message: String,
+    },
+    Cancelled,
+}
+
+// Output of a run that is still in progress
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunStream {
+    pub stdout: String,
+    pub stderr: String,
+    next_seq: u64,
+    // Events that arrived before an earlier event, sorted by sequence number
+    pending: Vec<RunEvent>,
+    is_done: bool,
+}
+
+impl RunStream {
+    pub fn is_empty(&self) -> bool {
+        self.stdout.is_empty() && self.stderr.is_empty()
+    }
+
+    // Returns the result when the final event has been applied
+    pub fn push(&mut self, event: RunEvent) -> Option<Result<RunResult, RunError>> {
+        let is_duplicate = event.seq < self.next_seq
+            || self.pending.iter().any(|pending| pending.seq == event.seq);
+
+        if self.is_done || is_duplicate {
+            return None;
+        }
+
+        let index = self
+            .pending
+            .iter()
+            .position(|pending| pending.seq > event.seq)
+            .unwrap_or(self.pending.len());
+        self.pending.insert(index, event);
+
+        while self
+            .pending
+            .first()
+            .is_some_and(|pending| pending.seq == self.next_seq)
+        {
+            let event = self.pending.remove(0);
+            self.next_seq += 1;
+
+            if let Some(result) = self.apply(event.kind) {
+                self.is_done = true;
+                self.pending.clear();
+                return Some(result);
+            }
+        }
+
+        None
+    }
+
+    // Finishes the run without waiting for missing events, i.e. when the request was aborted
+    pub fn abort(&mut self, err: RunError) -> Option<Result<RunResult, RunError>> {
+        if self.is_done {
+            return None;
+        }
+
+        self.is_done = true;
+        self.pending.clear();
+        Some(Err(err))
+    }
+
+    fn apply(&mut self, kind: RunEventKind) -> Option<Result<RunResult, RunError>> {
+        match kind {
+            RunEventKind::Stdout { text } => {
+                self.stdout.push_str(&text);
+                None
+            }
+
+            RunEventKind::Stderr { text } => {
+                self.stderr.push_str(&text);
+                None
+            }
+
+            RunEventKind::Finished {
+                duration,
+                error,
+                exit_code,
+                signal,
+                peak_memory,
+                truncated,
+            } => Some(Ok(RunResult {
+                duration,
+                stdout: self.stdout.clone(),
+                stderr: self.stderr.clone(),
+                error,
+                exit_code,
+                signal,
+                peak_memory,
+                truncated,
+            })),
+
+            RunEventKind::Failed { message } => Some(Err(RunError::Transport(message))),
+
+            RunEventKind::Cancelled => Some(Err(RunError::Cancelled)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn stdout(seq: u64, text: &str) -> RunEvent {
+        RunEvent {
+            seq,
+            kind: RunEventKind::Stdout {
+                text: text.to_string(),
+            },
+        }
+    }
+
+    fn stderr(seq: u64, text: &str) -> RunEvent {
+        RunEvent {
+            seq,
+            kind: RunEventKind::Stderr {
+                text: text.to_string(),
+            },
+        }
+    }
+
+    fn finished(seq: u64) -> RunEvent {
+        RunEvent::from_value(json!({
+            "seq": seq,
+            "type": "finished",
+            "duration": 10,
+            "error": "",
+            "exitCode": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_in_order() {
+        let mut stream = RunStream::default();
+        assert!(stream.is_empty());
+
+        assert!(stream.push(stdout(0, "a")).is_none());
+        assert!(stream.push(stderr(1, "b")).is_none());
+        assert!(stream.push(stdout(2, "c")).is_none());
+        assert_eq!(stream.stdout, "ac");
+        assert_eq!(stream.stderr, "b");
+
+        match stream.push(finished(3)) {
+            Some(Ok(result)) => {
+                assert_eq!(result.stdout, "ac");
+                assert_eq!(result.stderr, "b");
+                assert_eq!(result.duration, 10);
+                assert_eq!(result.exit_code, Some(0));
+                assert!(!result.truncated);
+            }
+            _ => panic!("expected a result"),
+        }
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut stream = RunStream::default();
+
+        assert!(stream.push(stdout(2, "c")).is_none());
+        assert!(stream.push(stdout(1, "b")).is_none());
+        assert!(stream.is_empty());
+
+        // Finished arrives before the output it follows
+        assert!(stream.push(finished(3)).is_none());
+
+        match stream.push(stdout(0, "a")) {
+            Some(Ok(result)) => assert_eq!(result.stdout, "abc"),
+            _ => panic!("expected a result"),
+        }
+    }
+
+    #[test]
+    fn test_duplicates_are_ignored() {
+        let mut stream = RunStream::default();
+
+        stream.push(stdout(0, "a"));
+        stream.push(stdout(0, "a"));
+        stream.push(stdout(2, "c"));
+        stream.push(stdout(2, "c"));
+        stream.push(stdout(1, "b"));
+
+        assert_eq!(stream.stdout, "abc");
+    }
+
+    #[test]
+    fn test_events_after_done_are_ignored() {
+        let mut stream = RunStream::default();
+        stream.push(stdout(0, "a"));
+
+        assert!(matches!(
+            stream.push(RunEvent {
+                seq: 1,
+                kind: RunEventKind::Cancelled
+            }),
+            Some(Err(RunError::Cancelled))
+        ));
+
+        assert!(stream.push(stdout(2, "b")).is_none());
+        assert!(stream.push(finished(3)).is_none());
+        assert_eq!(stream.stdout, "a");
+    }
+
+    #[test]
+    fn test_abort() {
+        let mut stream = RunStream::default();
+        stream.push(stdout(0, "a"));
+
+        // The event after the gap is never applied
+        stream.push(stdout(2, "c"));
+        assert_eq!(
+            stream.abort(RunError::Cancelled).map(|result| result.err()),
+            Some(Some(RunError::Cancelled))
+        );
+        assert_eq!(stream.stdout, "a");
+
+        assert!(stream.push(stdout(1, "b")).is_none());
+        assert!(stream
+            .abort(RunError::Transport("Connection lost".to_string()))
+            .is_none());
+    }
+
+    #[test]
+    fn test_failed() {
+        let mut stream = RunStream::default();
+        let event = RunEvent::from_value(json!({
+            "seq": 0,
+            "type": "failed",
+            "message": "Connection lost",
+        }))
+        .unwrap();
+
+        assert_eq!(
+            stream.push(event).map(|result| result.err()),
+            Some(Some(RunError::Transport("Connection lost".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_invalid_event() {
+        assert!(matches!(
+            RunEvent::from_value(json!({ "seq": 0, "type": "unknown" })),
+            Err(RunError::InvalidResponse(_))
+        ));
+    }
+}
@@ -1,10 +1,48 @@
+use crate::error::DecodeError;
+use crate::error::EncodeError;
+use crate::util::base64;
 use base_62::base62;
 use brotli::enc::BrotliEncoderParams;
-use brotli::BrotliCompress;
-use brotli::BrotliDecompress;
+use brotli::enc::StandardAlloc;
+use brotli::interface;
+use brotli::BrotliCompressCustomIoCustomDict;
+use brotli::Decompressor;
+use brotli::InputReferenceMut;
+use brotli::IoReaderWrapper;
+use brotli::IoWriterWrapper;
+use dictionary::Dictionary;
+use envelope::Envelope;
+use expected_output::ExpectedOutput;
+use fingerprint::Fingerprint;
 use glot_languages::language::Language;
+use glot_languages::language::RunInstructions;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Read;
+use std::path::PathBuf;
+
+pub mod archive;
+pub mod command_template;
+pub mod dictionary;
+pub mod diff;
+pub mod envelope;
+pub mod expected_output;
+pub mod file_tree;
+pub mod fingerprint;
+pub mod html;
+pub mod import;
+pub mod markdown;
+pub mod path;
+pub mod recover;
+pub mod validate;
+
+const BUFFER_SIZE: usize = 4096;
+
+// Name of the input created from the single stdin of older snippets
+pub const DEFAULT_INPUT_NAME: &str = "Input 1";
 
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,43 +50,472 @@ pub struct Snippet {
     pub language: Language,
     pub title: String,
     pub files: Vec<File>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<StdinInput>,
+    // Replaces the language's default build and run commands
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub stdin: Option<String>,
+    pub instructions: Option<RunInstructions>,
+    // Environment variables for the build and run commands
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // Arguments appended to the run command
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // Time limit for the run in seconds, replaces the backend default
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub command: Option<String>,
+    pub timeout: Option<u64>,
 }
 
 impl Snippet {
-    pub fn to_encoded_string(&self) -> Result<String, String> {
-        let json =
-            serde_json::to_vec(self).map_err(|err| format!("Failed to serialize: {}", err))?;
+    // Filesystem friendly name derived from the title, used for exported files
+    pub fn slug(&self) -> String {
+        let slug = self
+            .title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join("-");
+
+        if slug.is_empty() {
+            format!("{}-snippet", self.language.config().id())
+        } else {
+            slug
+        }
+    }
+
+    // Stdin used for a normal run, the first input
+    pub fn stdin(&self) -> Option<String> {
+        self.inputs.first().map(|input| input.content.clone())
+    }
+
+    // Non-empty inputs with a heading for exports, the name is left out if there is only one input
+    pub fn labeled_inputs(&self) -> Vec<(String, &str)> {
+        let has_multiple = self.inputs.len() > 1;
+
+        self.inputs
+            .iter()
+            .filter(|input| !input.content.is_empty())
+            .map(|input| {
+                let heading = if has_multiple {
+                    format!("Stdin: {}", input.name)
+                } else {
+                    "Stdin".to_string()
+                };
+
+                (heading, input.content.as_str())
+            })
+            .collect()
+    }
+
+    // Environment variables as NAME=value, sorted by name
+    pub fn env_lines(&self) -> Vec<String> {
+        env_lines(&self.env)
+    }
 
-        let mut compressed = vec![];
-        BrotliCompress(
-            &mut &*json,
-            &mut compressed,
-            &BrotliEncoderParams::default(),
-        )
-        .map_err(|err| format!("Failed to compress: {}", err))?;
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint::fingerprint(self)
+    }
+
+    pub fn run_instructions(&self) -> RunInstructions {
+        match &self.instructions {
+            Some(instructions) => {
+                let ctx = command_template::Context::from_files(&self.files);
+                command_template::render_instructions(instructions, &ctx)
+            }
+
+            None => self.default_run_instructions(),
+        }
+    }
+
+    // Custom commands can ask for the stdin data as a file
+    pub fn uses_stdin_file(&self) -> bool {
+        self.instructions
+            .as_ref()
+            .map(command_template::uses_stdin_file)
+            .unwrap_or(false)
+    }
+
+    pub fn main_file_index(&self) -> usize {
+        main_file_index(&self.files)
+    }
+
+    pub fn default_run_instructions(&self) -> RunInstructions {
+        let main_index = self.main_file_index();
+        let path = |file: &File| PathBuf::from(&file.name);
 
-        Ok(base62::encode(&compressed))
+        let main_file = self.files.get(main_index).map(path).unwrap_or_default();
+        let other_files = self
+            .files
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != main_index)
+            .map(|(_, file)| path(file))
+            .collect();
+
+        self.language
+            .config()
+            .run_instructions(main_file, other_files)
     }
 
-    pub fn from_encoded_string(encoded: &str) -> Result<Snippet, String> {
+    pub fn to_encoded_string(&self) -> Result<String, EncodeError> {
+        self.to_encoded_string_with_dictionary(dictionary::for_language(self.language))
+    }
+
+    pub fn to_encoded_string_with_dictionary(
+        &self,
+        dictionary: Option<&Dictionary>,
+    ) -> Result<String, EncodeError> {
+        let json =
+            serde_json::to_vec(self).map_err(|err| EncodeError::Serialize(err.to_string()))?;
+
         let compressed =
-            base62::decode(encoded).map_err(|err| format!("Failed to decode: {}", err))?;
+            compress(&json, dictionary).map_err(|err| EncodeError::Compress(err.to_string()))?;
+
+        let envelope = Envelope::new(base62::encode(&compressed), dictionary.map(|d| d.id));
+        Ok(envelope.to_string())
+    }
+
+    pub fn from_encoded_string(encoded: &str) -> Result<Snippet, DecodeError> {
+        let envelope = Envelope::parse(encoded)?;
+
+        let dictionary = match envelope.dictionary {
+            Some(id) => Some(
+                dictionary::get(id)
+                    .ok_or_else(|| DecodeError::Envelope(format!("Unknown dictionary: {}", id)))?,
+            ),
+            None => None,
+        };
+
+        let compressed = base62::decode(&envelope.payload)
+            .map_err(|err| DecodeError::Decode(err.to_string()))?;
 
         let mut json = vec![];
-        BrotliDecompress(&mut &*compressed, &mut json)
-            .map_err(|err| format!("Failed to decompress: {}", err))?;
+        decompressor(&compressed, dictionary)
+            .read_to_end(&mut json)
+            .map_err(|err| DecodeError::Decompress(err.to_string()))?;
+
+        let value: Value = serde_json::from_slice(&json)
+            .map_err(|err| DecodeError::Deserialize(err.to_string()))?;
+
+        let migrated = envelope::migrate(envelope.version, value)?;
 
-        serde_json::from_slice(&json).map_err(|err| format!("Failed to deserialize: {}", err))
+        let snippet: Snippet = serde_json::from_value(migrated)
+            .map_err(|err| DecodeError::Deserialize(err.to_string()))?;
+
+        Ok(snippet)
     }
 }
 
+pub fn main_file_index(files: &[File]) -> usize {
+    files.iter().position(|file| file.main).unwrap_or(0)
+}
+
+pub fn env_lines(env: &BTreeMap<String, String>) -> Vec<String> {
+    env.iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect()
+}
+
+pub fn decompressor<'a>(
+    compressed: &'a [u8],
+    dictionary: Option<&Dictionary>,
+) -> Decompressor<&'a [u8]> {
+    let dictionary_bytes = dictionary.map(|d| d.to_bytes()).unwrap_or_default();
+    Decompressor::new_with_custom_dict(compressed, BUFFER_SIZE, dictionary_bytes.into())
+}
+
+fn compress(json: &[u8], dictionary: Option<&Dictionary>) -> Result<Vec<u8>, io::Error> {
+    let dictionary_bytes = dictionary.map(|d| d.to_bytes()).unwrap_or_default();
+    let mut input_buffer = [0; BUFFER_SIZE];
+    let mut output_buffer = [0; BUFFER_SIZE];
+    let mut compressed = vec![];
+
+    let mut nop_callback = |_: &mut interface::PredictionModeContextMap<InputReferenceMut>,
+                            _: &mut [interface::StaticCommand],
+                            _: interface::InputPair,
+                            _: &mut StandardAlloc| ();
+
+    BrotliCompressCustomIoCustomDict(
+        &mut IoReaderWrapper(&mut &*json),
+        &mut IoWriterWrapper(&mut compressed),
+        &mut input_buffer,
+        &mut output_buffer,
+        &BrotliEncoderParams::default(),
+        StandardAlloc::default(),
+        &mut nop_callback,
+        &dictionary_bytes,
+        io::Error::new(io::ErrorKind::UnexpectedEof, "Unexpected EOF"),
+    )?;
+
+    Ok(compressed)
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StdinInput {
+    pub name: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<ExpectedOutput>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct File {
     pub name: String,
     pub content: String,
+    // Snippets encoded before binary files were supported have no encoding field
+    #[serde(default, skip_serializing_if = "FileEncoding::is_text")]
+    pub encoding: FileEncoding,
+    // The first file is the main file when no file is marked as main
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub main: bool,
+    // Can't be changed in the editor, i.e. test harnesses
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    // Not shown in the editor, but still part of the run
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+}
+
+impl File {
+    pub fn text(name: String, content: String) -> File {
+        File {
+            name,
+            content,
+            encoding: FileEncoding::Text,
+            main: false,
+            read_only: false,
+            hidden: false,
+        }
+    }
+
+    pub fn binary(name: String, bytes: &[u8]) -> File {
+        File {
+            name,
+            content: base64::encode(bytes),
+            encoding: FileEncoding::Base64,
+            main: false,
+            read_only: false,
+            hidden: false,
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.encoding == FileEncoding::Base64
+    }
+
+    // Decoded content, invalid base64 yields no bytes. It is reported by validate::validate,
+    // which has to pass before a snippet is shared, run or exported.
+    pub fn bytes(&self) -> Vec<u8> {
+        match self.encoding {
+            FileEncoding::Text => self.content.as_bytes().to_vec(),
+            FileEncoding::Base64 => base64::decode(&self.content).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileEncoding {
+    #[default]
+    Text,
+    Base64,
+}
+
+impl FileEncoding {
+    pub fn is_text(&self) -> bool {
+        *self == FileEncoding::Text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(files: Vec<File>) -> Snippet {
+        Snippet {
+            language: Language::Python,
+            title: "Hello".to_string(),
+            files,
+            inputs: vec![],
+            instructions: None,
+            env: BTreeMap::new(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    // Snippet json as written by each version, the payload is compressed and encoded at test time
+    const V1_JSON: &str = r#"{"language":"python","title":"Hello","files":[{"name":"main.py","content":"print(input())\n"}],"stdin":"42\n","command":"python3 main.py"}"#;
+    const V2_JSON: &str = r#"{"language":"python","title":"Hello","files":[{"name":"main.py","content":"print(input())\n"}],"inputs":[{"name":"Small","content":"1\n"},{"name":"Large","content":"1000\n"}],"command":"python3 main.py"}"#;
+    const V3_JSON: &str = r#"{"language":"python","title":"Hello","files":[{"name":"main.py","content":"print(input())\n","main":true},{"name":"logo.png","content":"AAEC","encoding":"base64"}],"inputs":[{"name":"Input 1","content":"42\n"}],"instructions":{"buildCommands":["pip install -r requirements.txt"],"runCommand":"python3 main.py"},"env":{"DEBUG":"1"},"args":["-v"],"timeout":30}"#;
+
+    const PYTHON_DICTIONARY: u32 = 11;
+
+    fn encode_fixture(header: &str, json: &str, dictionary: Option<u32>) -> String {
+        let dictionary = dictionary.map(|id| dictionary::get(id).unwrap());
+        let compressed = compress(json.as_bytes(), dictionary).unwrap();
+        format!("{}{}", header, base62::encode(&compressed))
+    }
+
+    fn input(name: &str, content: &str) -> StdinInput {
+        StdinInput {
+            name: name.to_string(),
+            content: content.to_string(),
+            expected_output: None,
+        }
+    }
+
+    fn commands(snippet: &Snippet) -> Option<(Vec<String>, String)> {
+        snippet.instructions.as_ref().map(|instructions| {
+            (
+                instructions.build_commands.clone(),
+                instructions.run_command.clone(),
+            )
+        })
+    }
+
+    fn custom_command(command: &str) -> Option<(Vec<String>, String)> {
+        Some((vec![], command.to_string()))
+    }
+
+    #[test]
+    fn test_decode_legacy() {
+        let encoded = encode_fixture("", V1_JSON, None);
+        let snippet = Snippet::from_encoded_string(&encoded).unwrap();
+
+        assert_eq!(snippet.language, Language::Python);
+        assert_eq!(snippet.title, "Hello");
+        assert_eq!(snippet.files.len(), 1);
+        assert_eq!(snippet.files[0].name, "main.py");
+        assert_eq!(snippet.files[0].content, "print(input())\n");
+        assert!(snippet.inputs == vec![input(DEFAULT_INPUT_NAME, "42\n")]);
+        assert_eq!(commands(&snippet), custom_command("python3 main.py"));
+    }
+
+    #[test]
+    fn test_decode_v1() {
+        let encoded = encode_fixture("b1_", V1_JSON, None);
+        let snippet = Snippet::from_encoded_string(&encoded).unwrap();
+
+        assert!(snippet.inputs == vec![input(DEFAULT_INPUT_NAME, "42\n")]);
+        assert_eq!(commands(&snippet), custom_command("python3 main.py"));
+    }
+
+    #[test]
+    fn test_decode_v1_without_stdin_and_command() {
+        let json = r#"{"language":"python","title":"Hello","files":[],"stdin":""}"#;
+        let snippet = Snippet::from_encoded_string(&encode_fixture("b1_", json, None)).unwrap();
+
+        assert!(snippet.inputs.is_empty());
+        assert_eq!(commands(&snippet), None);
+    }
+
+    #[test]
+    fn test_decode_v2() {
+        let encoded = encode_fixture("b2_", V2_JSON, None);
+        let snippet = Snippet::from_encoded_string(&encoded).unwrap();
+
+        assert!(snippet.inputs == vec![input("Small", "1\n"), input("Large", "1000\n")]);
+        assert_eq!(commands(&snippet), custom_command("python3 main.py"));
+    }
+
+    #[test]
+    fn test_decode_v3_with_dictionary() {
+        let header = format!("b3d{}_", PYTHON_DICTIONARY);
+        let encoded = encode_fixture(&header, V3_JSON, Some(PYTHON_DICTIONARY));
+        let snippet = Snippet::from_encoded_string(&encoded).unwrap();
+
+        assert!(snippet.files[0].main);
+        assert!(snippet.files[1].is_binary());
+        assert_eq!(snippet.files[1].bytes(), vec![0, 1, 2]);
+        assert!(snippet.inputs == vec![input("Input 1", "42\n")]);
+        assert_eq!(
+            commands(&snippet),
+            Some((
+                vec!["pip install -r requirements.txt".to_string()],
+                "python3 main.py".to_string()
+            ))
+        );
+        assert_eq!(snippet.env_lines(), vec!["DEBUG=1".to_string()]);
+        assert_eq!(snippet.args, vec!["-v".to_string()]);
+        assert_eq!(snippet.timeout, Some(30));
+
+        // The current version writes the same json
+        assert_eq!(serde_json::to_string(&snippet).unwrap(), V3_JSON);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let unknown_dictionary = encode_fixture("b3d999_", V3_JSON, None);
+        assert!(matches!(
+            Snippet::from_encoded_string(&unknown_dictionary),
+            Err(DecodeError::Envelope(_))
+        ));
+
+        // Decompressing with the wrong dictionary gives garbage or an error
+        let wrong_dictionary = encode_fixture("b3_", V3_JSON, Some(PYTHON_DICTIONARY));
+        assert!(Snippet::from_encoded_string(&wrong_dictionary).is_err());
+
+        let invalid_migration = encode_fixture("b1_", r#"{"stdin":42}"#, None);
+        assert!(matches!(
+            Snippet::from_encoded_string(&invalid_migration),
+            Err(DecodeError::Migrate(_))
+        ));
+
+        assert!(matches!(
+            Snippet::from_encoded_string("b3_!!!"),
+            Err(DecodeError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut original = snippet(vec![
+            File::text("main.py".to_string(), "print(input())\n".to_string()),
+            File::binary("logo.png".to_string(), &[0, 1, 2]),
+        ]);
+        original.inputs = vec![input("Input 1", "42\n")];
+        original.timeout = Some(10);
+
+        for dictionary in [None, dictionary::get(PYTHON_DICTIONARY)] {
+            let encoded = original
+                .to_encoded_string_with_dictionary(dictionary)
+                .unwrap();
+            let expected_header = match dictionary {
+                Some(dictionary) => format!("b3d{}_", dictionary.id),
+                None => "b3_".to_string(),
+            };
+            assert!(encoded.starts_with(&expected_header));
+
+            let decoded = Snippet::from_encoded_string(&encoded).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&original).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_does_not_validate() {
+        // Links created before the limits were added must still open
+        let mut snippet = snippet(vec![
+            File::text(
+                "main.py".to_string(),
+                "a".repeat(validate::MAX_FILE_SIZE + 1),
+            ),
+            File::text("main.py".to_string(), "".to_string()),
+        ]);
+        snippet.title = "a".repeat(validate::MAX_TITLE_LENGTH + 1);
+        assert!(validate::validate(&snippet).is_err());
+
+        let encoded = snippet.to_encoded_string().unwrap();
+        let decoded = Snippet::from_encoded_string(&encoded).unwrap();
+
+        assert_eq!(decoded.title, snippet.title);
+        assert_eq!(decoded.files.len(), 2);
+        assert_eq!(decoded.files[0].content, snippet.files[0].content);
+    }
 }
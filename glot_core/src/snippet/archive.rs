@@ -0,0 +1,386 @@
+use crate::run::local;
+use crate::snippet::markdown;
+use crate::snippet::Snippet;
+use serde::{Deserialize, Serialize};
+
+// Archives are built without timestamps so that exporting the same snippet twice gives identical bytes
+const ZIP_DOS_DATE: u16 = (1 << 5) | 1;
+const ZIP_VERSION: u16 = 20;
+const ZIP_UTF8_FLAG: u16 = 1 << 11;
+const TAR_BLOCK_SIZE: usize = 512;
+const TAR_NAME_SIZE: usize = 100;
+const README_FILENAME: &str = "README.md";
+const FALLBACK_README_FILENAME: &str = "README.glot.md";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Format {
+    Zip,
+    Tar,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Zip => "zip",
+            Format::Tar => "tar",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Format::Zip => "application/zip",
+            Format::Tar => "application/x-tar",
+        }
+    }
+}
+
+pub struct Archive {
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+struct Entry {
+    path: String,
+    content: Vec<u8>,
+}
+
+pub fn create(snippet: &Snippet, format: Format) -> Archive {
+    let directory = snippet.slug();
+    let entries = entries(snippet, &directory);
+
+    let bytes = match format {
+        Format::Zip => write_zip(&entries),
+        Format::Tar => write_tar(&entries),
+    };
+
+    Archive {
+        filename: format!("{}.{}", directory, format.extension()),
+        bytes,
+    }
+}
+
+pub fn readme(snippet: &Snippet) -> String {
+    let run_instructions = snippet.run_instructions();
+    let commands = local::exports(&snippet.env)
+        .into_iter()
+        .chain(run_instructions.build_commands.iter().cloned())
+        .chain(std::iter::once(local::with_args(
+            &run_instructions.run_command,
+            &snippet.args,
+        )))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut sections = vec![
+        format!("# {}", markdown::escape(&snippet.title)),
+        format!(
+            "{} snippet exported from glot.io.",
+            snippet.language.config().name()
+        ),
+        format!("## Run\n\n{}", markdown::fenced("sh", &commands)),
+    ];
+
+    for (heading, stdin) in snippet.labeled_inputs() {
+        sections.push(format!(
+            "## {}\n\nThe snippet reads the following from stdin:\n\n{}",
+            markdown::escape(&heading),
+            markdown::fenced("", stdin)
+        ));
+    }
+
+    sections.join("\n\n") + "\n"
+}
+
+fn entries(snippet: &Snippet, directory: &str) -> Vec<Entry> {
+    let readme_filename = if snippet.files.iter().any(|f| f.name == README_FILENAME) {
+        FALLBACK_README_FILENAME
+    } else {
+        README_FILENAME
+    };
+
+    let files = snippet.files.iter().map(|file| Entry {
+        path: format!("{}/{}", directory, file.name),
+        content: file.bytes(),
+    });
+
+    let readme = Entry {
+        path: format!("{}/{}", directory, readme_filename),
+        content: readme(snippet).into_bytes(),
+    };
+
+    files.chain(std::iter::once(readme)).collect()
+}
+
+// Stored (uncompressed) zip, the snippet size limits keep archives small enough that compressing
+// them gains little, and stored entries keep the output byte for byte reproducible
+fn write_zip(entries: &[Entry]) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut central_directory = vec![];
+
+    for entry in entries {
+        let offset = bytes.len() as u32;
+        let crc = crc32(&entry.content);
+        let size = entry.content.len() as u32;
+        let name = entry.path.as_bytes();
+
+        put_u32(&mut bytes, 0x04034b50);
+        put_u16(&mut bytes, ZIP_VERSION);
+        put_u16(&mut bytes, ZIP_UTF8_FLAG);
+        put_u16(&mut bytes, 0); // compression method: stored
+        put_u16(&mut bytes, 0); // modification time
+        put_u16(&mut bytes, ZIP_DOS_DATE);
+        put_u32(&mut bytes, crc);
+        put_u32(&mut bytes, size);
+        put_u32(&mut bytes, size);
+        put_u16(&mut bytes, name.len() as u16);
+        put_u16(&mut bytes, 0); // extra field length
+        bytes.extend_from_slice(name);
+        bytes.extend_from_slice(&entry.content);
+
+        put_u32(&mut central_directory, 0x02014b50);
+        put_u16(&mut central_directory, ZIP_VERSION);
+        put_u16(&mut central_directory, ZIP_VERSION);
+        put_u16(&mut central_directory, ZIP_UTF8_FLAG);
+        put_u16(&mut central_directory, 0); // compression method: stored
+        put_u16(&mut central_directory, 0); // modification time
+        put_u16(&mut central_directory, ZIP_DOS_DATE);
+        put_u32(&mut central_directory, crc);
+        put_u32(&mut central_directory, size);
+        put_u32(&mut central_directory, size);
+        put_u16(&mut central_directory, name.len() as u16);
+        put_u16(&mut central_directory, 0); // extra field length
+        put_u16(&mut central_directory, 0); // comment length
+        put_u16(&mut central_directory, 0); // disk number
+        put_u16(&mut central_directory, 0); // internal attributes
+        put_u32(&mut central_directory, 0); // external attributes
+        put_u32(&mut central_directory, offset);
+        central_directory.extend_from_slice(name);
+    }
+
+    let central_directory_offset = bytes.len() as u32;
+    let central_directory_size = central_directory.len() as u32;
+    bytes.extend_from_slice(&central_directory);
+
+    put_u32(&mut bytes, 0x06054b50);
+    put_u16(&mut bytes, 0); // disk number
+    put_u16(&mut bytes, 0); // disk with central directory
+    put_u16(&mut bytes, entries.len() as u16);
+    put_u16(&mut bytes, entries.len() as u16);
+    put_u32(&mut bytes, central_directory_size);
+    put_u32(&mut bytes, central_directory_offset);
+    put_u16(&mut bytes, 0); // comment length
+
+    bytes
+}
+
+fn write_tar(entries: &[Entry]) -> Vec<u8> {
+    let mut bytes = vec![];
+
+    for entry in entries {
+        let name = entry.path.as_bytes();
+
+        if name.len() >= TAR_NAME_SIZE {
+            // Names that don't fit the ustar header are stored in a pax extended header
+            let record = pax_record("path", &entry.path);
+            write_tar_header(&mut bytes, b"././@PaxHeader", record.len(), b'x');
+            write_tar_data(&mut bytes, record.as_bytes());
+            write_tar_header(
+                &mut bytes,
+                &name[..TAR_NAME_SIZE - 1],
+                entry.content.len(),
+                b'0',
+            );
+        } else {
+            write_tar_header(&mut bytes, name, entry.content.len(), b'0');
+        }
+
+        write_tar_data(&mut bytes, &entry.content);
+    }
+
+    // End of archive is marked by two empty blocks
+    bytes.resize(bytes.len() + TAR_BLOCK_SIZE * 2, 0);
+
+    bytes
+}
+
+fn write_tar_header(bytes: &mut Vec<u8>, name: &[u8], size: usize, type_flag: u8) {
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+
+    header[..name.len()].copy_from_slice(name);
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[148..156].copy_from_slice(b"        ");
+    header[156] = type_flag;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+    bytes.extend_from_slice(&header);
+}
+
+fn write_tar_data(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend_from_slice(data);
+
+    let padding = (TAR_BLOCK_SIZE - data.len() % TAR_BLOCK_SIZE) % TAR_BLOCK_SIZE;
+    bytes.resize(bytes.len() + padding, 0);
+}
+
+// A pax record is "<length> <key>=<value>\n" where the length includes its own digits
+fn pax_record(key: &str, value: &str) -> String {
+    let content = format!(" {}={}\n", key, value);
+    let mut length = content.len();
+
+    while (length.to_string().len() + content.len()) != length {
+        length = length.to_string().len() + content.len();
+    }
+
+    format!("{}{}", length, content)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+
+    for byte in data {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+
+    !crc
+}
+
+fn put_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, content: &str) -> Entry {
+        Entry {
+            path: path.to_string(),
+            content: content.as_bytes().to_vec(),
+        }
+    }
+
+    fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+    }
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    }
+
+    #[test]
+    fn test_pax_record() {
+        assert_eq!(pax_record("path", "a"), "9 path=a\n");
+
+        // Going from two to three digits makes the record one byte longer
+        let record = pax_record("path", &"a".repeat(90));
+        assert_eq!(record.len(), 99);
+        assert!(record.starts_with("99 path="));
+
+        let record = pax_record("path", &"a".repeat(91));
+        assert_eq!(record.len(), 101);
+        assert!(record.starts_with("101 path="));
+    }
+
+    #[test]
+    fn test_zip() {
+        let entries = vec![
+            entry("hello/main.rs", "fn main() {}\n"),
+            entry("hello/a", ""),
+        ];
+        let bytes = write_zip(&entries);
+
+        assert_eq!(read_u32(&bytes, 0), 0x04034b50);
+        assert_eq!(read_u32(&bytes, 14), crc32(b"fn main() {}\n"));
+        assert_eq!(read_u32(&bytes, 18), 13);
+        assert_eq!(read_u16(&bytes, 26), 13);
+        assert_eq!(&bytes[30..43], b"hello/main.rs");
+        assert_eq!(&bytes[43..56], b"fn main() {}\n");
+
+        let end = bytes.len() - 22;
+        assert_eq!(read_u32(&bytes, end), 0x06054b50);
+        assert_eq!(read_u16(&bytes, end + 10), 2);
+
+        let central_directory_size = read_u32(&bytes, end + 12) as usize;
+        let central_directory_offset = read_u32(&bytes, end + 16) as usize;
+        assert_eq!(central_directory_offset + central_directory_size, end);
+        assert_eq!(read_u32(&bytes, central_directory_offset), 0x02014b50);
+
+        // The second entry starts right after the first one
+        let second = central_directory_offset + 46 + 13;
+        assert_eq!(read_u32(&bytes, second), 0x02014b50);
+        assert_eq!(read_u32(&bytes, second + 42), 56);
+        assert_eq!(read_u32(&bytes, 56), 0x04034b50);
+    }
+
+    #[test]
+    fn test_tar() {
+        let bytes = write_tar(&[entry("hello/main.rs", "fn main() {}\n")]);
+
+        // Header, one data block and the two end of archive blocks
+        assert_eq!(bytes.len(), TAR_BLOCK_SIZE * 4);
+        assert_eq!(&bytes[..13], b"hello/main.rs");
+        assert_eq!(&bytes[124..136], b"00000000015\0");
+        assert_eq!(bytes[156], b'0');
+        assert_eq!(&bytes[257..263], b"ustar\0");
+        assert_eq!(&bytes[512..525], b"fn main() {}\n");
+        assert!(bytes[525..].iter().all(|b| *b == 0));
+
+        let mut header = bytes[..TAR_BLOCK_SIZE].to_vec();
+        let checksum = std::str::from_utf8(&header[148..154]).unwrap().to_string();
+        header[148..156].copy_from_slice(b"        ");
+        let expected: u32 = header.iter().map(|b| *b as u32).sum();
+        assert_eq!(u32::from_str_radix(&checksum, 8), Ok(expected));
+    }
+
+    #[test]
+    fn test_tar_long_name() {
+        let path = format!("hello/{}.rs", "a".repeat(120));
+        let bytes = write_tar(&[entry(&path, "")]);
+        let record = pax_record("path", &path);
+
+        assert_eq!(&bytes[..14], b"././@PaxHeader");
+        assert_eq!(bytes[156], b'x');
+        assert_eq!(&bytes[512..512 + record.len()], record.as_bytes());
+
+        // The file header follows the padded pax record with a truncated name
+        let header = &bytes[1024..1536];
+        assert_eq!(
+            &header[..TAR_NAME_SIZE - 1],
+            &path.as_bytes()[..TAR_NAME_SIZE - 1]
+        );
+        assert_eq!(header[TAR_NAME_SIZE - 1], 0);
+        assert_eq!(header[156], b'0');
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let entries = vec![entry("hello/main.rs", "fn main() {}\n")];
+
+        assert_eq!(write_zip(&entries), write_zip(&entries));
+        assert_eq!(write_tar(&entries), write_tar(&entries));
+    }
+}
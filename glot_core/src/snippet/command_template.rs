@@ -0,0 +1,359 @@
+use crate::snippet::main_file_index;
+use crate::snippet::File;
+use glot_languages::language::RunInstructions;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// File with the stdin data, only added to the run when a command uses {stdin_file}
+pub const STDIN_FILENAME: &str = ".glot/stdin";
+
+// Variables are written as {name}. Anything else in braces, i.e. ${HOME} or {a,b}, is left as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variable {
+    Main,
+    Files,
+    Others,
+    StdinFile,
+}
+
+impl Variable {
+    pub fn all() -> Vec<Variable> {
+        vec![
+            Variable::Main,
+            Variable::Files,
+            Variable::Others,
+            Variable::StdinFile,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variable::Main => "main",
+            Variable::Files => "files",
+            Variable::Others => "others",
+            Variable::StdinFile => "stdin_file",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Variable::Main => "The main file",
+            Variable::Files => "All files, starting with the main file",
+            Variable::Others => "All files except the main file",
+            Variable::StdinFile => "A file with the stdin data",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Variable> {
+        Variable::all()
+            .into_iter()
+            .find(|variable| variable.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TemplateError {
+    UnknownVariable(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TemplateError::UnknownVariable(name) => {
+                let known = Variable::all()
+                    .iter()
+                    .map(|variable| format!("{{{}}}", variable.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(
+                    f,
+                    "Unknown variable {{{}}}, expected one of {}",
+                    name, known
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Context {
+    pub main_file: String,
+    pub other_files: Vec<String>,
+}
+
+impl Context {
+    pub fn from_files(files: &[File]) -> Context {
+        let main_index = main_file_index(files);
+
+        Context {
+            main_file: files
+                .get(main_index)
+                .map(|file| file.name.clone())
+                .unwrap_or_default(),
+            other_files: files
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != main_index)
+                .map(|(_, file)| file.name.clone())
+                .collect(),
+        }
+    }
+
+    fn value(&self, variable: Variable) -> String {
+        match variable {
+            Variable::Main => shell_word(&self.main_file),
+
+            Variable::Files => std::iter::once(&self.main_file)
+                .chain(&self.other_files)
+                .map(|name| shell_word(name))
+                .collect::<Vec<_>>()
+                .join(" "),
+
+            Variable::Others => self
+                .other_files
+                .iter()
+                .map(|name| shell_word(name))
+                .collect::<Vec<_>>()
+                .join(" "),
+
+            Variable::StdinFile => STDIN_FILENAME.to_string(),
+        }
+    }
+}
+
+pub fn render(template: &str, ctx: &Context) -> String {
+    render_with(template, |name| {
+        Variable::from_name(name).map(|variable| ctx.value(variable))
+    })
+}
+
+pub fn render_instructions(instructions: &RunInstructions, ctx: &Context) -> RunInstructions {
+    RunInstructions {
+        build_commands: instructions
+            .build_commands
+            .iter()
+            .map(|command| render(command, ctx))
+            .collect(),
+        run_command: render(&instructions.run_command, ctx),
+    }
+}
+
+// Unknown variables are kept as is when rendering, so commands saved before templates existed
+// still work. They are rejected when the commands are edited, as they are most likely typos.
+pub fn validate_instructions(instructions: &RunInstructions) -> Result<(), TemplateError> {
+    let mut unknown = vec![];
+
+    for command in commands(instructions) {
+        render_with(command, |name| {
+            if Variable::from_name(name).is_none() {
+                unknown.push(name.to_string());
+            }
+
+            None
+        });
+    }
+
+    match unknown.into_iter().next() {
+        Some(name) => Err(TemplateError::UnknownVariable(name)),
+        None => Ok(()),
+    }
+}
+
+pub fn uses_stdin_file(instructions: &RunInstructions) -> bool {
+    let mut found = false;
+
+    for command in commands(instructions) {
+        render_with(command, |name| {
+            found |= Variable::from_name(name) == Some(Variable::StdinFile);
+            None
+        });
+    }
+
+    found
+}
+
+fn commands(instructions: &RunInstructions) -> impl Iterator<Item = &String> {
+    instructions
+        .build_commands
+        .iter()
+        .chain(std::iter::once(&instructions.run_command))
+}
+
+// The value function gets the name of each variable, None keeps the variable as is
+fn render_with<F>(template: &str, mut value: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let (before, from_brace) = rest.split_at(start);
+        output.push_str(before);
+
+        let is_shell_expansion = before.ends_with('$');
+
+        let replacement = from_brace[1..]
+            .find('}')
+            .map(|end| (end, &from_brace[1..end + 1]))
+            .filter(|(_, name)| !is_shell_expansion && is_variable_name(name))
+            .and_then(|(end, name)| value(name).map(|value| (end, value)));
+
+        match replacement {
+            Some((end, value)) => {
+                output.push_str(&value);
+                rest = &from_brace[end + 2..];
+            }
+
+            None => {
+                output.push('{');
+                rest = &from_brace[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn is_variable_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase() || c == '_')
+}
+
+// Filenames are quoted only when needed, so the rendered commands stay readable
+fn shell_word(s: &str) -> String {
+    let is_safe = !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-/+@%:,".contains(c));
+
+    if is_safe {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> Context {
+        Context {
+            main_file: "main.py".to_string(),
+            other_files: vec!["my lib.py".to_string(), "it's.txt".to_string()],
+        }
+    }
+
+    fn instructions(build_commands: &[&str], run_command: &str) -> RunInstructions {
+        RunInstructions {
+            build_commands: build_commands.iter().map(|s| s.to_string()).collect(),
+            run_command: run_command.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let ctx = ctx();
+
+        assert_eq!(render("python3 {main}", &ctx), "python3 main.py");
+        assert_eq!(
+            render("cat {files}", &ctx),
+            r#"cat main.py 'my lib.py' 'it'\''s.txt'"#
+        );
+        assert_eq!(
+            render("cat {others}", &ctx),
+            r#"cat 'my lib.py' 'it'\''s.txt'"#
+        );
+        assert_eq!(
+            render("./main < {stdin_file}", &ctx),
+            "./main < .glot/stdin"
+        );
+    }
+
+    #[test]
+    fn test_render_leaves_other_braces() {
+        let ctx = ctx();
+
+        assert_eq!(render("echo ${HOME} ${main}", &ctx), "echo ${HOME} ${main}");
+        assert_eq!(render("cp {a,b}.txt {}", &ctx), "cp {a,b}.txt {}");
+        assert_eq!(render("echo {foo} {main}", &ctx), "echo {foo} main.py");
+        assert_eq!(render("echo {main", &ctx), "echo {main");
+        assert_eq!(render("echo {{main}}", &ctx), "echo {main.py}");
+        assert_eq!(render("{", &ctx), "{");
+    }
+
+    #[test]
+    fn test_render_multibyte() {
+        let ctx = ctx();
+
+        assert_eq!(render("é{main}ü", &ctx), "émain.pyü");
+        assert_eq!(render("{ü}{main}", &ctx), "{ü}main.py");
+        assert_eq!(render("ü{", &ctx), "ü{");
+    }
+
+    #[test]
+    fn test_shell_word() {
+        assert_eq!(shell_word("src/main.rs"), "src/main.rs");
+        assert_eq!(shell_word("my file.py"), "'my file.py'");
+        assert_eq!(shell_word("it's.py"), r#"'it'\''s.py'"#);
+        assert_eq!(shell_word("$(rm -rf ~).py"), "'$(rm -rf ~).py'");
+        assert_eq!(shell_word(""), "''");
+    }
+
+    #[test]
+    fn test_context_from_files() {
+        let mut files = vec![
+            File::text("a.py".to_string(), String::new()),
+            File::text("main.py".to_string(), String::new()),
+        ];
+        files[1].main = true;
+
+        let ctx = Context::from_files(&files);
+        assert_eq!(ctx.main_file, "main.py");
+        assert_eq!(ctx.other_files, vec!["a.py"]);
+    }
+
+    #[test]
+    fn test_render_instructions() {
+        let rendered = render_instructions(&instructions(&["gcc {files}"], "./a.out"), &ctx());
+
+        assert_eq!(
+            rendered.build_commands,
+            vec![r#"gcc main.py 'my lib.py' 'it'\''s.txt'"#]
+        );
+        assert_eq!(rendered.run_command, "./a.out");
+    }
+
+    #[test]
+    fn test_validate_instructions() {
+        assert_eq!(
+            validate_instructions(&instructions(&["make {main}"], "./main ${HOME} {a,b}")),
+            Ok(())
+        );
+        assert_eq!(
+            validate_instructions(&instructions(&["make {mian}"], "./main {foo}")),
+            Err(TemplateError::UnknownVariable("mian".to_string()))
+        );
+        assert_eq!(
+            validate_instructions(&instructions(&[], "./main {foo}")),
+            Err(TemplateError::UnknownVariable("foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_uses_stdin_file() {
+        assert!(uses_stdin_file(&instructions(&[], "./main {stdin_file}")));
+        assert!(uses_stdin_file(&instructions(
+            &["cp {stdin_file} in.txt"],
+            "./main"
+        )));
+        assert!(!uses_stdin_file(&instructions(
+            &[],
+            "./main < ${stdin_file}"
+        )));
+        assert!(!uses_stdin_file(&instructions(&[], "./main {main}")));
+    }
+}
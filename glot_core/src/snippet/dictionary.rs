@@ -0,0 +1,234 @@
+use glot_languages::language::Language;
+
+// Dictionaries are part of the encoded snippet format.
+// Never change an existing dictionary, add a new one with a new id instead.
+// The common part has every field name of the current snippet json.
+const COMMON: &str = r#"{"language":"","title":"Hello World","files":[{"name":"","content":"","encoding":"base64","main":true,"readOnly":true,"hidden":true}],"inputs":[{"name":"Input 1","content":"","expectedOutput":{"stdout":"","comparison":"exact"}}],"instructions":{"buildCommands":[""],"runCommand":""},"env":{},"args":[""],"timeout":"#;
+
+pub struct Dictionary {
+    pub id: u32,
+    language_id: &'static str,
+    filename: &'static str,
+    boilerplate: &'static str,
+    keywords: &'static str,
+}
+
+impl Dictionary {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // File content is json escaped in the compressed data
+        let escape = |s: &str| serde_json::to_string(s).unwrap_or_default();
+
+        [
+            COMMON.to_string(),
+            self.filename.to_string(),
+            escape(self.keywords),
+            escape(self.boilerplate),
+        ]
+        .concat()
+        .into_bytes()
+    }
+}
+
+// The boilerplate is copied in rather than read from the language's example code, which
+// belongs to glot_languages and may change without a new dictionary id
+const DICTIONARIES: &[Dictionary] = &[
+    Dictionary {
+        id: 1,
+        language_id: "bash",
+        filename: "main.sh",
+        boilerplate: "#!/bin/bash\n\necho \"Hello World!\"\n",
+        keywords: "if [ ]; then fi for do done while case esac function local \"$1\" \"$@\" $(( )) read -r",
+    },
+    Dictionary {
+        id: 2,
+        language_id: "c",
+        filename: "main.c",
+        boilerplate: "#include <stdio.h>\n#include <stdlib.h>\n#include <string.h>\n\nint main(void) {\n    printf(\"Hello World!\\n\");\n    return 0;\n}\n",
+        keywords: "char *argv[] int argc struct typedef unsigned const static void sizeof malloc free NULL",
+    },
+    Dictionary {
+        id: 3,
+        language_id: "cpp",
+        filename: "main.cpp",
+        boilerplate: "#include <iostream>\n#include <vector>\n#include <string>\n\nusing namespace std;\n\nint main() {\n    cout << \"Hello World!\" << endl;\n    return 0;\n}\n",
+        keywords: "std::vector<int> std::string std::cout auto const class public: private: template<typename T>",
+    },
+    Dictionary {
+        id: 4,
+        language_id: "csharp",
+        filename: "main.cs",
+        boilerplate: "using System;\nusing System.Collections.Generic;\nusing System.Linq;\n\nclass MainClass {\n    static void Main() {\n        Console.WriteLine(\"Hello World!\");\n    }\n}\n",
+        keywords: "public private static string int var new List<int> foreach namespace",
+    },
+    Dictionary {
+        id: 5,
+        language_id: "go",
+        filename: "main.go",
+        boilerplate: "package main\n\nimport (\n    \"fmt\"\n)\n\nfunc main() {\n    fmt.Println(\"Hello World!\")\n}\n",
+        keywords: "fmt.Printf(\"%v\\n\", err := range var type struct interface{} return nil string int",
+    },
+    Dictionary {
+        id: 6,
+        language_id: "haskell",
+        filename: "main.hs",
+        boilerplate: "module Main where\n\nimport Data.List\n\nmain :: IO ()\nmain = putStrLn \"Hello World!\"\n",
+        keywords: "where let in case of do data type instance class deriving (Show, Eq) -> <- :: Maybe Just Nothing",
+    },
+    Dictionary {
+        id: 7,
+        language_id: "java",
+        filename: "Main.java",
+        boilerplate: "import java.util.*;\n\nclass Main {\n    public static void main(String[] args) {\n        System.out.println(\"Hello World!\");\n    }\n}\n",
+        keywords: "public private static final int String new ArrayList<>() List<Integer> Map<String, Integer> for (int i = 0; i < ; i++)",
+    },
+    Dictionary {
+        id: 8,
+        language_id: "javascript",
+        filename: "main.js",
+        boilerplate: "console.log(\"Hello World!\");\n",
+        keywords: "function const let var => return async await new Promise class this .map( .filter( .forEach( require( module.exports undefined",
+    },
+    Dictionary {
+        id: 9,
+        language_id: "kotlin",
+        filename: "main.kt",
+        boilerplate: "fun main(args : Array<String>) {\n    println(\"Hello World!\")\n}\n",
+        keywords: "val var fun class data object when is in listOf( mapOf( String Int return null",
+    },
+    Dictionary {
+        id: 10,
+        language_id: "php",
+        filename: "main.php",
+        boilerplate: "<?php\n\necho \"Hello World!\\n\";\n",
+        keywords: "function $this-> public private static class new array( foreach as => return echo",
+    },
+    Dictionary {
+        id: 11,
+        language_id: "python",
+        filename: "main.py",
+        boilerplate: "print(\"Hello World!\")\n",
+        keywords: "def __init__(self, self. import from class return if __name__ == \"__main__\":\n    for in range(len( elif else: None True False lambda with as",
+    },
+    Dictionary {
+        id: 12,
+        language_id: "ruby",
+        filename: "main.rb",
+        boilerplate: "puts \"Hello World!\"\n",
+        keywords: "def end class module require attr_accessor .each do |x| if elsif unless nil self. return",
+    },
+    Dictionary {
+        id: 13,
+        language_id: "rust",
+        filename: "main.rs",
+        boilerplate: "fn main() {\n    println!(\"Hello World!\");\n}\n",
+        keywords: "let mut fn impl struct enum pub use std:: match Some( None Ok( Err( Vec<String> String::from( &str &self -> .iter() .collect()",
+    },
+    Dictionary {
+        id: 14,
+        language_id: "scala",
+        filename: "main.scala",
+        boilerplate: "object Main extends App {\n    println(\"Hello World!\")\n}\n",
+        keywords: "def val var case class object match => extends with List( Map( Option Some( None",
+    },
+    Dictionary {
+        id: 15,
+        language_id: "swift",
+        filename: "main.swift",
+        boilerplate: "print(\"Hello World!\")\n",
+        keywords: "func let var struct class enum guard else return import Foundation -> String Int self.",
+    },
+    Dictionary {
+        id: 16,
+        language_id: "typescript",
+        filename: "main.ts",
+        boilerplate: "console.log(\"Hello World!\");\n",
+        keywords: "function const let interface type => return async await export import string number boolean Array<string>",
+    },
+];
+
+pub fn for_language(language: Language) -> Option<&'static Dictionary> {
+    let language_id = language.config().id();
+
+    DICTIONARIES
+        .iter()
+        .rev()
+        .find(|dictionary| dictionary.language_id == language_id)
+}
+
+pub fn get(id: u32) -> Option<&'static Dictionary> {
+    DICTIONARIES.iter().find(|dictionary| dictionary.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::expected_output::Comparison;
+    use crate::snippet::expected_output::ExpectedOutput;
+    use crate::snippet::File;
+    use crate::snippet::Snippet;
+    use crate::snippet::StdinInput;
+    use glot_languages::language::RunInstructions;
+    use serde_json::Value;
+
+    fn collect_keys(value: &Value, keys: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    keys.push(key.clone());
+                    collect_keys(value, keys);
+                }
+            }
+
+            Value::Array(values) => values.iter().for_each(|value| collect_keys(value, keys)),
+
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_common_has_every_field() {
+        let mut file = File::binary("logo.png".to_string(), &[0]);
+        file.main = true;
+        file.read_only = true;
+        file.hidden = true;
+
+        let snippet = Snippet {
+            language: Language::Python,
+            title: "Hello".to_string(),
+            files: vec![file],
+            inputs: vec![StdinInput {
+                name: "Input 1".to_string(),
+                content: "42".to_string(),
+                expected_output: Some(ExpectedOutput {
+                    stdout: "42".to_string(),
+                    comparison: Comparison::Exact,
+                }),
+            }],
+            instructions: Some(RunInstructions {
+                build_commands: vec!["make".to_string()],
+                run_command: "./main".to_string(),
+            }),
+            env: [("DEBUG".to_string(), "1".to_string())].into(),
+            args: vec!["-v".to_string()],
+            timeout: Some(30),
+        };
+
+        let mut keys = vec![];
+        collect_keys(&serde_json::to_value(&snippet).unwrap(), &mut keys);
+
+        // Environment variable names are user data
+        for key in keys.iter().filter(|key| *key != "DEBUG") {
+            assert!(COMMON.contains(&format!("\"{}\":", key)), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_unique_ids() {
+        for dictionary in DICTIONARIES {
+            assert_eq!(
+                get(dictionary.id).map(|d| d.language_id),
+                Some(dictionary.language_id)
+            );
+        }
+    }
+}
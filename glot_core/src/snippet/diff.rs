@@ -0,0 +1,390 @@
+use crate::snippet::File;
+use crate::snippet::Snippet;
+use crate::util::diff;
+use crate::util::diff::Hunk;
+use glot_languages::language::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetDiff {
+    pub title: Option<Change<String>>,
+    pub language: Option<Change<Language>>,
+    pub inputs: Vec<FileDiff>,
+    pub command: Option<Change<Option<String>>>,
+    pub main_file: Option<Change<String>>,
+    pub env: Option<Change<BTreeMap<String, String>>>,
+    pub args: Option<Change<Vec<String>>>,
+    pub timeout: Option<Change<Option<u64>>>,
+    pub files: Vec<FileDiff>,
+}
+
+impl SnippetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.language.is_none()
+            && self
+                .inputs
+                .iter()
+                .all(|input| input.status == FileStatus::Unchanged)
+            && self.command.is_none()
+            && self.main_file.is_none()
+            && self.env.is_none()
+            && self.args.is_none()
+            && self.timeout.is_none()
+            && self
+                .files
+                .iter()
+                .all(|file| file.status == FileStatus::Unchanged)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub old_name: Option<String>,
+    pub new_name: Option<String>,
+    pub status: FileStatus,
+    pub binary: bool,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FileDiff {
+    pub fn name(&self) -> String {
+        match (&self.old_name, &self.new_name) {
+            (Some(old_name), Some(new_name)) if old_name != new_name => {
+                format!("{} → {}", old_name, new_name)
+            }
+            (_, Some(name)) | (Some(name), None) => name.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileStatus {
+    Added,
+    Removed,
+    Renamed,
+    Modified,
+    Unchanged,
+}
+
+impl FileStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileStatus::Added => "Added",
+            FileStatus::Removed => "Removed",
+            FileStatus::Renamed => "Renamed",
+            FileStatus::Modified => "Modified",
+            FileStatus::Unchanged => "Unchanged",
+        }
+    }
+}
+
+pub fn compare(old: &Snippet, new: &Snippet) -> SnippetDiff {
+    SnippetDiff {
+        title: change(&old.title, &new.title),
+        language: (old.language != new.language).then_some(Change {
+            old: old.language,
+            new: new.language,
+        }),
+        inputs: compare_files(&input_files(old), &input_files(new)),
+        command: change(&custom_commands(old), &custom_commands(new)),
+        main_file: change(&main_file_name(old), &main_file_name(new)),
+        env: change(&old.env, &new.env),
+        args: change(&old.args, &new.args),
+        timeout: change(&old.timeout, &new.timeout),
+        files: compare_files(&old.files, &new.files),
+    }
+}
+
+// Inputs are compared the same way as files, matched by name
+fn input_files(snippet: &Snippet) -> Vec<File> {
+    snippet
+        .inputs
+        .iter()
+        .map(|input| File::text(input.name.clone(), input.content.clone()))
+        .collect()
+}
+
+fn custom_commands(snippet: &Snippet) -> Option<String> {
+    snippet
+        .instructions
+        .as_ref()
+        .map(|instructions| instructions.to_string())
+}
+
+fn main_file_name(snippet: &Snippet) -> String {
+    snippet
+        .files
+        .get(snippet.main_file_index())
+        .map(|file| file.name.clone())
+        .unwrap_or_default()
+}
+
+fn change<T: Clone + PartialEq>(old: &T, new: &T) -> Option<Change<T>> {
+    (old != new).then(|| Change {
+        old: old.clone(),
+        new: new.clone(),
+    })
+}
+
+// Files are matched by name, a removed and an added file with identical content is a rename
+fn compare_files(old_files: &[File], new_files: &[File]) -> Vec<FileDiff> {
+    let mut added: Vec<&File> = new_files
+        .iter()
+        .filter(|file| !old_files.iter().any(|f| f.name == file.name))
+        .collect();
+
+    let mut diffs = vec![];
+
+    for old_file in old_files {
+        let same_name = new_files.iter().find(|f| f.name == old_file.name);
+        let renamed = || added.iter().position(|f| same_content(f, old_file));
+
+        match same_name {
+            Some(new_file) => diffs.push(file_diff(Some(old_file), Some(new_file))),
+
+            None => match renamed() {
+                Some(index) => {
+                    let new_file = added.remove(index);
+                    diffs.push(file_diff(Some(old_file), Some(new_file)));
+                }
+
+                None => diffs.push(file_diff(Some(old_file), None)),
+            },
+        }
+    }
+
+    for new_file in added {
+        diffs.push(file_diff(None, Some(new_file)));
+    }
+
+    diffs
+}
+
+// Binary files are only compared as a whole, a line diff of base64 isn't useful
+fn file_diff(old_file: Option<&File>, new_file: Option<&File>) -> FileDiff {
+    let binary = old_file.into_iter().chain(new_file).any(File::is_binary);
+
+    let hunks = if binary {
+        vec![]
+    } else {
+        let old_content = old_file.map(|f| f.content.as_str()).unwrap_or_default();
+        let new_content = new_file.map(|f| f.content.as_str()).unwrap_or_default();
+        diff::unified(old_content, new_content)
+    };
+
+    let status = match (old_file, new_file) {
+        (None, _) => FileStatus::Added,
+        (_, None) => FileStatus::Removed,
+        (Some(old), Some(new)) if old.name != new.name => FileStatus::Renamed,
+        (Some(old), Some(new)) if binary && same_content(old, new) => FileStatus::Unchanged,
+        _ if !binary && hunks.is_empty() => FileStatus::Unchanged,
+        _ => FileStatus::Modified,
+    };
+
+    FileDiff {
+        old_name: old_file.map(|f| f.name.clone()),
+        new_name: new_file.map(|f| f.name.clone()),
+        status,
+        binary,
+        hunks,
+    }
+}
+
+fn same_content(a: &File, b: &File) -> bool {
+    a.encoding == b.encoding && a.content == b.content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::StdinInput;
+    use glot_languages::language::RunInstructions;
+
+    fn snippet(files: Vec<File>) -> Snippet {
+        Snippet {
+            language: Language::Rust,
+            title: "Hello".to_string(),
+            files,
+            inputs: vec![],
+            instructions: None,
+            env: BTreeMap::new(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    fn text_file(name: &str, content: &str) -> File {
+        File::text(name.to_string(), content.to_string())
+    }
+
+    fn statuses(diffs: &[FileDiff]) -> Vec<(String, FileStatus)> {
+        diffs
+            .iter()
+            .map(|diff| (diff.name(), diff.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let old = snippet(vec![text_file("main.rs", "fn main() {}\n")]);
+        let diff = compare(&old, &old.clone());
+
+        assert!(diff.is_empty());
+        assert_eq!(
+            statuses(&diff.files),
+            vec![("main.rs".to_string(), FileStatus::Unchanged)]
+        );
+    }
+
+    #[test]
+    fn test_rename() {
+        let old = snippet(vec![
+            text_file("main.rs", "fn main() {}\n"),
+            text_file("util.rs", "pub fn util() {}\n"),
+        ]);
+        let new = snippet(vec![
+            text_file("main.rs", "fn main() {}\n"),
+            text_file("helpers.rs", "pub fn util() {}\n"),
+        ]);
+
+        let diff = compare(&old, &new);
+
+        assert!(!diff.is_empty());
+        assert_eq!(
+            statuses(&diff.files),
+            vec![
+                ("main.rs".to_string(), FileStatus::Unchanged),
+                ("util.rs → helpers.rs".to_string(), FileStatus::Renamed),
+            ]
+        );
+        assert!(diff.files[1].hunks.is_empty());
+    }
+
+    #[test]
+    fn test_added_and_removed_files() {
+        let old = snippet(vec![
+            text_file("main.rs", "fn main() {}\n"),
+            text_file("old.rs", "old\n"),
+        ]);
+        let new = snippet(vec![
+            text_file("main.rs", "fn main() {\n}\n"),
+            text_file("new.rs", "new\n"),
+            File::binary("logo.png".to_string(), &[1, 2, 3]),
+        ]);
+
+        let diff = compare(&old, &new);
+
+        assert_eq!(
+            statuses(&diff.files),
+            vec![
+                ("main.rs".to_string(), FileStatus::Modified),
+                ("old.rs".to_string(), FileStatus::Removed),
+                ("new.rs".to_string(), FileStatus::Added),
+                ("logo.png".to_string(), FileStatus::Added),
+            ]
+        );
+        assert_eq!(diff.files[2].hunks.len(), 1);
+        assert!(diff.files[3].binary);
+        assert!(diff.files[3].hunks.is_empty());
+    }
+
+    #[test]
+    fn test_binary_files() {
+        let old = snippet(vec![File::binary("logo.png".to_string(), &[1, 2, 3])]);
+        let new = snippet(vec![File::binary("logo.png".to_string(), &[1, 2, 4])]);
+
+        assert_eq!(
+            statuses(&compare(&old, &old.clone()).files),
+            vec![("logo.png".to_string(), FileStatus::Unchanged)]
+        );
+        assert_eq!(
+            statuses(&compare(&old, &new).files),
+            vec![("logo.png".to_string(), FileStatus::Modified)]
+        );
+    }
+
+    #[test]
+    fn test_title_and_main_file() {
+        let old = snippet(vec![text_file("a.rs", ""), text_file("b.rs", "")]);
+        let mut new = old.clone();
+        new.title = "Hello world".to_string();
+        new.files[1].main = true;
+
+        let diff = compare(&old, &new);
+
+        let title = diff.title.unwrap();
+        assert_eq!(
+            (title.old.as_str(), title.new.as_str()),
+            ("Hello", "Hello world")
+        );
+        let main_file = diff.main_file.unwrap();
+        assert_eq!(
+            (main_file.old.as_str(), main_file.new.as_str()),
+            ("a.rs", "b.rs")
+        );
+    }
+
+    #[test]
+    fn test_stdin() {
+        let input = |name: &str, content: &str| StdinInput {
+            name: name.to_string(),
+            content: content.to_string(),
+            expected_output: None,
+        };
+
+        let mut old = snippet(vec![text_file("main.rs", "")]);
+        old.inputs = vec![input("Input 1", "1\n2\n"), input("Input 2", "x\n")];
+        let mut new = old.clone();
+        new.inputs = vec![input("Input 1", "1\n3\n")];
+
+        let diff = compare(&old, &new);
+
+        assert!(!diff.is_empty());
+        assert_eq!(
+            statuses(&diff.inputs),
+            vec![
+                ("Input 1".to_string(), FileStatus::Modified),
+                ("Input 2".to_string(), FileStatus::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_and_run_options() {
+        let old = snippet(vec![text_file("main.rs", "")]);
+        let mut new = old.clone();
+        new.instructions = Some(RunInstructions {
+            build_commands: vec!["rustc main.rs".to_string()],
+            run_command: "./main".to_string(),
+        });
+        new.env
+            .insert("RUST_BACKTRACE".to_string(), "1".to_string());
+        new.args = vec!["--verbose".to_string()];
+        new.timeout = Some(30);
+
+        let diff = compare(&old, &new);
+
+        let command = diff.command.unwrap();
+        assert_eq!(command.old, None);
+        assert!(command.new.is_some());
+        assert_eq!(diff.env.unwrap().new.len(), 1);
+        assert_eq!(diff.args.unwrap().new, vec!["--verbose".to_string()]);
+        let timeout = diff.timeout.unwrap();
+        assert_eq!((timeout.old, timeout.new), (None, Some(30)));
+        assert!(diff.title.is_none());
+        assert!(diff.language.is_none());
+    }
+}
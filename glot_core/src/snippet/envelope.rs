@@ -0,0 +1,303 @@
+use crate::error::DecodeError;
+use crate::snippet::DEFAULT_INPUT_NAME;
+use serde_json::json;
+use serde_json::Value;
+use std::fmt;
+
+const SEPARATOR: char = '_';
+const DICTIONARY_MARKER: char = 'd';
+
+// Snippets encoded before the envelope was introduced have no header
+const LEGACY_VERSION: u32 = 1;
+
+pub const CURRENT_VERSION: u32 = 3;
+
+// The first migration upgrades version 1 to 2, the second 2 to 3 and so on.
+// A migration must be added whenever CURRENT_VERSION is bumped.
+type Migration = fn(Value) -> Result<Value, String>;
+const MIGRATIONS: &[Migration] = &[named_inputs, separate_build_commands];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // Brotli compressed json encoded with base62, without header
+    Legacy,
+    // Brotli compressed json encoded with base62
+    Brotli,
+}
+
+impl Format {
+    fn tag(&self) -> Option<char> {
+        match self {
+            Format::Legacy => None,
+            Format::Brotli => Some('b'),
+        }
+    }
+
+    fn from_tag(tag: char) -> Option<Format> {
+        match tag {
+            'b' => Some(Format::Brotli),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub format: Format,
+    pub version: u32,
+    pub dictionary: Option<u32>,
+    pub payload: String,
+}
+
+impl Envelope {
+    pub fn new(payload: String, dictionary: Option<u32>) -> Envelope {
+        Envelope {
+            format: Format::Brotli,
+            version: CURRENT_VERSION,
+            dictionary,
+            payload,
+        }
+    }
+
+    pub fn parse(encoded: &str) -> Result<Envelope, DecodeError> {
+        match encoded.split_once(SEPARATOR) {
+            None => Ok(Envelope {
+                format: Format::Legacy,
+                version: LEGACY_VERSION,
+                dictionary: None,
+                payload: encoded.to_string(),
+            }),
+
+            Some((header, payload)) => {
+                let mut chars = header.chars();
+
+                let format = chars
+                    .next()
+                    .and_then(Format::from_tag)
+                    .ok_or_else(|| DecodeError::Envelope(format!("Unknown format: {}", header)))?;
+
+                let (version_str, dictionary_str) =
+                    match chars.as_str().split_once(DICTIONARY_MARKER) {
+                        Some((version, dictionary)) => (version, Some(dictionary)),
+                        None => (chars.as_str(), None),
+                    };
+
+                let version: u32 = version_str
+                    .parse()
+                    .map_err(|_| DecodeError::Envelope(format!("Invalid version: {}", header)))?;
+
+                let dictionary = dictionary_str
+                    .map(|id| id.parse::<u32>())
+                    .transpose()
+                    .map_err(|_| {
+                        DecodeError::Envelope(format!("Invalid dictionary: {}", header))
+                    })?;
+
+                if version == 0 || version > CURRENT_VERSION {
+                    return Err(DecodeError::Envelope(format!(
+                        "Unsupported version: {}",
+                        version
+                    )));
+                }
+
+                Ok(Envelope {
+                    format,
+                    version,
+                    dictionary,
+                    payload: payload.to_string(),
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Envelope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.format.tag(), self.dictionary) {
+            (Some(tag), Some(dictionary)) => write!(
+                f,
+                "{}{}{}{}{}{}",
+                tag, self.version, DICTIONARY_MARKER, dictionary, SEPARATOR, self.payload
+            ),
+            (Some(tag), None) => write!(f, "{}{}{}{}", tag, self.version, SEPARATOR, self.payload),
+            (None, _) => write!(f, "{}", self.payload),
+        }
+    }
+}
+
+// Version 2 replaced the single stdin string with a list of named inputs
+fn named_inputs(mut value: Value) -> Result<Value, String> {
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| "Expected snippet to be an object".to_string())?;
+
+    match object.remove("stdin") {
+        None | Some(Value::Null) => {}
+
+        Some(Value::String(stdin)) => {
+            if !stdin.is_empty() {
+                object.insert(
+                    "inputs".to_string(),
+                    json!([{ "name": DEFAULT_INPUT_NAME, "content": stdin }]),
+                );
+            }
+        }
+
+        Some(other) => return Err(format!("Expected stdin to be a string, got: {}", other)),
+    }
+
+    Ok(value)
+}
+
+pub fn migrate(version: u32, value: Value) -> Result<Value, DecodeError> {
+    let index = version.saturating_sub(1) as usize;
+
+    MIGRATIONS
+        .iter()
+        .skip(index)
+        .try_fold(value, |value, migration| migration(value))
+        .map_err(DecodeError::Migrate)
+}
+
+// Version 3 replaced the custom run command with separate build and run commands
+fn separate_build_commands(mut value: Value) -> Result<Value, String> {
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| "Expected snippet to be an object".to_string())?;
+
+    match object.remove("command") {
+        None | Some(Value::Null) => {}
+
+        // The build commands were skipped when a custom command was used
+        Some(Value::String(command)) => {
+            object.insert(
+                "instructions".to_string(),
+                json!({ "buildCommands": [], "runCommand": command }),
+            );
+        }
+
+        Some(other) => return Err(format!("Expected command to be a string, got: {}", other)),
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(format: Format, version: u32, dictionary: Option<u32>) -> Envelope {
+        Envelope {
+            format,
+            version,
+            dictionary,
+            payload: "abc123".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Envelope::parse("abc123"),
+            Ok(envelope(Format::Legacy, LEGACY_VERSION, None))
+        );
+        assert_eq!(
+            Envelope::parse("b1_abc123"),
+            Ok(envelope(Format::Brotli, 1, None))
+        );
+        assert_eq!(
+            Envelope::parse("b3d11_abc123"),
+            Ok(envelope(Format::Brotli, 3, Some(11)))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for encoded in [
+            "x1_abc", "_abc", "b_abc", "bx_abc", "b0_abc", "b4_abc", "b3dx_abc",
+        ] {
+            assert!(
+                matches!(Envelope::parse(encoded), Err(DecodeError::Envelope(_))),
+                "{}",
+                encoded
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(envelope(Format::Legacy, 1, None).to_string(), "abc123");
+        assert_eq!(envelope(Format::Brotli, 2, None).to_string(), "b2_abc123");
+        assert_eq!(
+            envelope(Format::Brotli, 3, Some(11)).to_string(),
+            "b3d11_abc123"
+        );
+        assert_eq!(
+            Envelope::new("abc123".to_string(), None).to_string(),
+            format!("b{}_abc123", CURRENT_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_one_migration_per_version() {
+        assert_eq!(MIGRATIONS.len() as u32, CURRENT_VERSION - 1);
+    }
+
+    #[test]
+    fn test_named_inputs() {
+        assert_eq!(
+            named_inputs(json!({ "title": "a", "stdin": "42" })),
+            Ok(
+                json!({ "title": "a", "inputs": [{ "name": DEFAULT_INPUT_NAME, "content": "42" }] })
+            )
+        );
+        assert_eq!(
+            named_inputs(json!({ "title": "a", "stdin": "" })),
+            Ok(json!({ "title": "a" }))
+        );
+        assert_eq!(
+            named_inputs(json!({ "title": "a", "stdin": null })),
+            Ok(json!({ "title": "a" }))
+        );
+        assert!(named_inputs(json!({ "stdin": 42 })).is_err());
+        assert!(named_inputs(json!([])).is_err());
+    }
+
+    #[test]
+    fn test_separate_build_commands() {
+        assert_eq!(
+            separate_build_commands(json!({ "title": "a", "command": "make run" })),
+            Ok(json!({
+                "title": "a",
+                "instructions": { "buildCommands": [], "runCommand": "make run" }
+            }))
+        );
+        assert_eq!(
+            separate_build_commands(json!({ "title": "a" })),
+            Ok(json!({ "title": "a" }))
+        );
+        assert!(separate_build_commands(json!({ "command": ["make"] })).is_err());
+    }
+
+    #[test]
+    fn test_migrate() {
+        let v1 = json!({ "stdin": "42", "command": "make run" });
+        let v3 = json!({
+            "inputs": [{ "name": DEFAULT_INPUT_NAME, "content": "42" }],
+            "instructions": { "buildCommands": [], "runCommand": "make run" }
+        });
+
+        assert_eq!(migrate(1, v1.clone()), Ok(v3.clone()));
+        assert_eq!(migrate(3, v3.clone()), Ok(v3.clone()));
+
+        // Version 2 snippets already have inputs, a stdin field is not expected
+        let v2 = json!({ "inputs": [], "command": "make run" });
+        assert_eq!(
+            migrate(2, v2),
+            Ok(json!({
+                "inputs": [],
+                "instructions": { "buildCommands": [], "runCommand": "make run" }
+            }))
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::util::diff;
+use crate::util::diff::Hunk;
+use crate::util::regex;
+use crate::util::regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedOutput {
+    pub stdout: String,
+    #[serde(default)]
+    pub comparison: Comparison,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Comparison {
+    // Trailing whitespace on each line and leading/trailing blank lines are ignored
+    #[default]
+    Trimmed,
+    Exact,
+    // The pattern must match the whole output, trailing newlines are ignored
+    Regex,
+}
+
+impl Comparison {
+    pub fn all() -> Vec<Comparison> {
+        vec![Comparison::Trimmed, Comparison::Exact, Comparison::Regex]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Comparison::Trimmed => "Ignore surrounding whitespace",
+            Comparison::Exact => "Exact",
+            Comparison::Regex => "Regular expression",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckResult {
+    Passed,
+    // The hunks are empty for regex comparisons and when only line endings differ
+    Failed { hunks: Vec<Hunk> },
+    InvalidPattern(String),
+}
+
+impl CheckResult {
+    pub fn is_passed(&self) -> bool {
+        matches!(self, CheckResult::Passed)
+    }
+}
+
+pub fn validate_pattern(expected: &ExpectedOutput) -> Result<(), regex::Error> {
+    match expected.comparison {
+        Comparison::Regex => Regex::new(&expected.stdout).map(|_| ()),
+        Comparison::Trimmed | Comparison::Exact => Ok(()),
+    }
+}
+
+pub fn check(expected: &ExpectedOutput, stdout: &str) -> CheckResult {
+    match expected.comparison {
+        Comparison::Exact => compare(&expected.stdout, stdout),
+
+        Comparison::Trimmed => compare(&trim(&expected.stdout), &trim(stdout)),
+
+        Comparison::Regex => match Regex::new(&expected.stdout) {
+            Ok(regex) => {
+                let output = stdout.replace("\r\n", "\n");
+
+                if regex.is_full_match(output.trim_end_matches('\n')) {
+                    CheckResult::Passed
+                } else {
+                    CheckResult::Failed { hunks: vec![] }
+                }
+            }
+
+            Err(err) => CheckResult::InvalidPattern(err.to_string()),
+        },
+    }
+}
+
+fn compare(expected: &str, actual: &str) -> CheckResult {
+    if expected == actual {
+        CheckResult::Passed
+    } else {
+        CheckResult::Failed {
+            hunks: diff::unified(expected, actual),
+        }
+    }
+}
+
+fn trim(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::diff::Line;
+
+    fn expected(stdout: &str, comparison: Comparison) -> ExpectedOutput {
+        ExpectedOutput {
+            stdout: stdout.to_string(),
+            comparison,
+        }
+    }
+
+    #[test]
+    fn test_exact() {
+        let expected = expected("Hello\n", Comparison::Exact);
+
+        assert!(check(&expected, "Hello\n").is_passed());
+        assert!(!check(&expected, "Hello").is_passed());
+        assert!(!check(&expected, "Hello \n").is_passed());
+
+        // Only the line endings differ, there are no lines to show
+        assert_eq!(
+            check(&expected, "Hello\r\n"),
+            CheckResult::Failed { hunks: vec![] }
+        );
+    }
+
+    #[test]
+    fn test_trimmed() {
+        let expected = expected("a\nb\n", Comparison::Trimmed);
+
+        assert!(check(&expected, "a\nb\n").is_passed());
+        assert!(check(&expected, "a  \nb\t\n").is_passed());
+        assert!(check(&expected, "\n\na\nb\n\n\n").is_passed());
+        assert!(check(&expected, "a\r\nb\r\n").is_passed());
+        assert!(!check(&expected, "  a\nb\n").is_passed());
+        assert!(!check(&expected, "a\n\nb\n").is_passed());
+    }
+
+    #[test]
+    fn test_regex() {
+        let expected = expected(r"Result: \d+", Comparison::Regex);
+
+        assert!(check(&expected, "Result: 42").is_passed());
+        assert!(check(&expected, "Result: 42\n").is_passed());
+        assert!(check(&expected, "Result: 42\r\n\r\n").is_passed());
+        assert!(!check(&expected, "Result: 42!").is_passed());
+        assert!(!check(&expected, "The Result: 42").is_passed());
+        assert_eq!(
+            check(&expected, "Result: x"),
+            CheckResult::Failed { hunks: vec![] }
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let expected = expected("(a", Comparison::Regex);
+
+        assert!(matches!(
+            check(&expected, "a"),
+            CheckResult::InvalidPattern(_)
+        ));
+        assert!(validate_pattern(&expected).is_err());
+
+        // Only regex comparisons are patterns
+        assert!(validate_pattern(&ExpectedOutput {
+            comparison: Comparison::Exact,
+            ..expected
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_failed_hunks() {
+        let expected = expected("1\n2\n3\n", Comparison::Trimmed);
+
+        match check(&expected, "1\n2\n4\n  \n") {
+            CheckResult::Failed { hunks } => {
+                assert_eq!(hunks.len(), 1);
+                assert_eq!(
+                    hunks[0].lines,
+                    vec![
+                        Line::Context("1".to_string()),
+                        Line::Context("2".to_string()),
+                        Line::Removed("3".to_string()),
+                        Line::Added("4".to_string()),
+                    ]
+                );
+            }
+
+            result => panic!("expected a failure, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::snippet::path;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Directory {
+        name: String,
+        path: String,
+        children: Vec<Node>,
+    },
+    File {
+        name: String,
+        path: String,
+    },
+}
+
+pub fn has_directories(paths: &[String]) -> bool {
+    paths.iter().any(|p| path::directory(p).is_some())
+}
+
+// Directories are listed before files, both sorted by name. File nodes keep the full
+// filename, so files with invalid names like "/main.rs" can still be selected.
+pub fn build(paths: &[String]) -> Vec<Node> {
+    build_level("", paths.iter().map(|p| (p.as_str(), p.as_str())).collect())
+}
+
+// Each entry is the full path and the part below the parent directory
+fn build_level<'a>(parent: &str, paths: Vec<(&'a str, &'a str)>) -> Vec<Node> {
+    let mut directories: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+    let mut files = vec![];
+
+    for (full_path, p) in paths {
+        match p.split_once(path::SEPARATOR) {
+            Some((directory, rest)) => directories
+                .entry(directory)
+                .or_default()
+                .push((full_path, rest)),
+            None => files.push((p, full_path)),
+        }
+    }
+
+    files.sort_unstable();
+
+    let join = |name: &str| {
+        if parent.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}{}{}", parent, path::SEPARATOR, name)
+        }
+    };
+
+    let directory_nodes = directories.into_iter().map(|(name, children)| {
+        let path = join(name);
+
+        Node::Directory {
+            name: name.to_string(),
+            children: build_level(&path, children),
+            path,
+        }
+    });
+
+    let file_nodes = files.into_iter().map(|(name, full_path)| Node::File {
+        name: name.to_string(),
+        path: full_path.to_string(),
+    });
+
+    directory_nodes.chain(file_nodes).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, path: &str) -> Node {
+        Node::File {
+            name: name.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_build() {
+        let nodes = build(&paths(&["main.rs", "src/b.rs", "src/a.rs", "Cargo.toml"]));
+
+        assert_eq!(
+            nodes,
+            vec![
+                Node::Directory {
+                    name: "src".to_string(),
+                    path: "src".to_string(),
+                    children: vec![file("a.rs", "src/a.rs"), file("b.rs", "src/b.rs")],
+                },
+                file("Cargo.toml", "Cargo.toml"),
+                file("main.rs", "main.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_keeps_invalid_filenames() {
+        let nodes = build(&paths(&["/main.rs", "a//b.rs"]));
+
+        assert_eq!(
+            nodes,
+            vec![
+                Node::Directory {
+                    name: "".to_string(),
+                    path: "".to_string(),
+                    children: vec![file("main.rs", "/main.rs")],
+                },
+                Node::Directory {
+                    name: "a".to_string(),
+                    path: "a".to_string(),
+                    children: vec![Node::Directory {
+                        name: "".to_string(),
+                        path: "a/".to_string(),
+                        children: vec![file("b.rs", "a//b.rs")],
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_has_directories() {
+        assert!(has_directories(&paths(&["main.rs", "src/lib.rs"])));
+        assert!(!has_directories(&paths(&["main.rs", "lib.rs"])));
+    }
+}
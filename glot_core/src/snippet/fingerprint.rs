@@ -0,0 +1,224 @@
+use crate::snippet::Snippet;
+use crate::util::sha256;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Part of the hashed data, bump when the canonical form changes
+const CANONICAL_FORMAT: &str = "glot-snippet-v1";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint(String);
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Hash of everything that affects how the snippet runs. The title is left out and
+// line endings are normalized, so the fingerprint is independent of the url encoding.
+pub fn fingerprint(snippet: &Snippet) -> Fingerprint {
+    Fingerprint(sha256::hex_digest(&canonical_bytes(snippet)))
+}
+
+fn canonical_bytes(snippet: &Snippet) -> Vec<u8> {
+    let mut bytes = vec![];
+
+    put_field(&mut bytes, "format", CANONICAL_FORMAT.as_bytes());
+    put_field(
+        &mut bytes,
+        "language",
+        snippet.language.config().id().as_bytes(),
+    );
+
+    for file in &snippet.files {
+        put_field(&mut bytes, "file.name", file.name.as_bytes());
+
+        // Binary files are hashed by their decoded bytes, so line wrapped base64 gives the same result
+        if file.is_binary() {
+            put_field(&mut bytes, "file.binary", &file.bytes());
+        } else {
+            let content = normalize_line_endings(&file.content);
+            put_field(&mut bytes, "file.content", content.as_bytes());
+        }
+    }
+
+    // Only included when it differs from the implicit default, so existing fingerprints stay valid
+    let main_index = snippet.main_file_index();
+    if main_index > 0 {
+        if let Some(file) = snippet.files.get(main_index) {
+            put_field(&mut bytes, "main", file.name.as_bytes());
+        }
+    }
+
+    // A single input is hashed by content only, the same as the stdin of older snippets
+    match &snippet.inputs[..] {
+        [input] => {
+            let stdin = normalize_line_endings(&input.content);
+
+            if !stdin.is_empty() {
+                put_field(&mut bytes, "stdin", stdin.as_bytes());
+            }
+        }
+
+        inputs => {
+            for input in inputs {
+                let stdin = normalize_line_endings(&input.content);
+                put_field(&mut bytes, "stdin.name", input.name.as_bytes());
+                put_field(&mut bytes, "stdin.content", stdin.as_bytes());
+            }
+        }
+    }
+
+    // Hashed the same way as the single custom command of older snippets when there are no build commands
+    if let Some(instructions) = &snippet.instructions {
+        let build_commands = instructions
+            .build_commands
+            .iter()
+            .map(|command| command.trim())
+            .filter(|command| !command.is_empty());
+
+        for command in build_commands {
+            put_field(&mut bytes, "build", command.as_bytes());
+        }
+
+        let command = instructions.run_command.trim();
+        if !command.is_empty() {
+            put_field(&mut bytes, "command", command.as_bytes());
+        }
+    }
+
+    // The env is a sorted map, so the order is stable
+    for (name, value) in &snippet.env {
+        put_field(&mut bytes, "env.name", name.as_bytes());
+        put_field(&mut bytes, "env.value", value.as_bytes());
+    }
+
+    for arg in &snippet.args {
+        put_field(&mut bytes, "arg", arg.as_bytes());
+    }
+
+    if let Some(timeout) = snippet.timeout {
+        put_field(&mut bytes, "timeout", timeout.to_string().as_bytes());
+    }
+
+    bytes
+}
+
+// Length prefixed, so no two different snippets have the same canonical bytes
+fn put_field(bytes: &mut Vec<u8>, name: &str, value: &[u8]) {
+    for part in [name.as_bytes(), value] {
+        bytes.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        bytes.extend_from_slice(part);
+    }
+}
+
+fn normalize_line_endings(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::File;
+    use crate::snippet::StdinInput;
+    use glot_languages::language::Language;
+    use glot_languages::language::RunInstructions;
+    use std::collections::BTreeMap;
+
+    fn snippet(files: Vec<File>) -> Snippet {
+        Snippet {
+            language: Language::Rust,
+            title: "Hello".to_string(),
+            files,
+            inputs: vec![],
+            instructions: None,
+            env: BTreeMap::new(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    fn text_file(name: &str, content: &str) -> File {
+        File::text(name.to_string(), content.to_string())
+    }
+
+    fn input(name: &str, content: &str) -> StdinInput {
+        StdinInput {
+            name: name.to_string(),
+            content: content.to_string(),
+            expected_output: None,
+        }
+    }
+
+    #[test]
+    fn test_stable() {
+        let snippet = snippet(vec![text_file("main.rs", "fn main() {}\n")]);
+        let first = fingerprint(&snippet);
+
+        assert_eq!(first.to_string().len(), 64);
+        assert_eq!(first, fingerprint(&snippet.clone()));
+    }
+
+    #[test]
+    fn test_ignores_title_and_line_endings() {
+        let old = snippet(vec![text_file("main.rs", "fn main() {}\n")]);
+        let mut new = snippet(vec![text_file("main.rs", "fn main() {}\r\n")]);
+        new.title = "Other".to_string();
+
+        assert_eq!(fingerprint(&old), fingerprint(&new));
+    }
+
+    #[test]
+    fn test_content_changes() {
+        let base = snippet(vec![text_file("main.rs", "fn main() {}\n")]);
+        let changed = |change: &dyn Fn(&mut Snippet)| {
+            let mut snippet = base.clone();
+            change(&mut snippet);
+            fingerprint(&snippet)
+        };
+
+        let fingerprints = vec![
+            fingerprint(&base),
+            changed(&|s| s.files[0].content.push(' ')),
+            changed(&|s| s.files[0].name = "lib.rs".to_string()),
+            changed(&|s| s.files.push(text_file("lib.rs", ""))),
+            changed(&|s| s.inputs = vec![input("Input 1", "1")]),
+            changed(&|s| s.args = vec!["-v".to_string()]),
+            changed(&|s| {
+                s.env.insert("A".to_string(), "1".to_string());
+            }),
+            changed(&|s| s.timeout = Some(5)),
+            changed(&|s| {
+                s.instructions = Some(RunInstructions {
+                    build_commands: vec![],
+                    run_command: "./main".to_string(),
+                })
+            }),
+        ];
+
+        for (index, fingerprint) in fingerprints.iter().enumerate() {
+            assert!(!fingerprints[index + 1..].contains(fingerprint));
+        }
+    }
+
+    #[test]
+    fn test_field_boundaries() {
+        // Moving text between fields gives a different fingerprint
+        let a = snippet(vec![text_file("ab", "c")]);
+        let b = snippet(vec![text_file("a", "bc")]);
+
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+
+    #[test]
+    fn test_compatible_with_older_snippets() {
+        // A single empty input is the same as no stdin, and the first file is the implicit main file
+        let old = snippet(vec![text_file("main.rs", ""), text_file("lib.rs", "")]);
+        let mut new = old.clone();
+        new.inputs = vec![input("Input 1", "")];
+        new.files[0].main = true;
+
+        assert_eq!(fingerprint(&old), fingerprint(&new));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::run::RunResult;
+use crate::snippet::markdown;
+use crate::snippet::Snippet;
+use maud::html;
+use maud::PreEscaped;
+use maud::DOCTYPE;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #111827; }
+h1 { margin-bottom: 0.25rem; }
+h2 { font-size: 1rem; margin-top: 2rem; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+.language { color: #6b7280; margin-top: 0; }
+pre { background: #f9fafb; border: 1px solid #e5e7eb; border-radius: 0.375rem; padding: 1rem; overflow-x: auto; }
+pre.error { color: #b91c1c; }
+.binary { color: #6b7280; font-style: italic; }
+"#;
+
+pub fn render(snippet: &Snippet, run_result: Option<&RunResult>) -> String {
+    let language_name = snippet.language.config().name();
+    let inputs = snippet.labeled_inputs();
+
+    let markup = html! {
+        (DOCTYPE)
+        html lang="en" {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { (snippet.title) " - " (language_name) " snippet" }
+                style { (PreEscaped(STYLE)) }
+            }
+            body {
+                h1 { (snippet.title) }
+                p class="language" { (language_name) }
+
+                @for file in &snippet.files {
+                    h2 { (file.name) }
+                    @if file.is_binary() {
+                        p class="binary" { (markdown::binary_description(file)) }
+                    } @else {
+                        pre {
+                            code class=(format!("language-{}", markdown::code_language(snippet, file))) {
+                                (file.content)
+                            }
+                        }
+                    }
+                }
+
+                @for (heading, stdin) in &inputs {
+                    h2 { (heading) }
+                    pre { (stdin) }
+                }
+
+                @if !snippet.env.is_empty() {
+                    h2 { "Environment" }
+                    pre { (snippet.env_lines().join("\n")) }
+                }
+
+                @if !snippet.args.is_empty() {
+                    h2 { "Arguments" }
+                    pre { (snippet.args.join("\n")) }
+                }
+
+                @if let Some(run_result) = run_result {
+                    @if !run_result.stdout.is_empty() {
+                        h2 { "Output" }
+                        pre { (run_result.stdout) }
+                    }
+
+                    @if !run_result.stderr.is_empty() {
+                        h2 { "Stderr" }
+                        pre class="error" { (run_result.stderr) }
+                    }
+
+                    @if !run_result.error.is_empty() {
+                        h2 { "Error" }
+                        pre class="error" { (run_result.error) }
+                    }
+                }
+            }
+        }
+    };
+
+    markup.into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::File;
+    use glot_languages::language::Language;
+
+    fn snippet(title: &str, files: Vec<File>) -> Snippet {
+        Snippet {
+            language: Language::Rust,
+            title: title.to_string(),
+            files,
+            inputs: vec![],
+            instructions: None,
+            env: Default::default(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_escaping() {
+        let snippet = snippet(
+            "<script>alert(1)</script>",
+            vec![File::text(
+                "<b>.rs".to_string(),
+                "if a < b && b > c {}".to_string(),
+            )],
+        );
+        let run_result = RunResult {
+            duration: 0,
+            stdout: "<i>out</i>".to_string(),
+            stderr: String::new(),
+            error: String::new(),
+            exit_code: None,
+            signal: None,
+            peak_memory: None,
+            truncated: false,
+        };
+
+        let html = render(&snippet, Some(&run_result));
+
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<h1>&lt;script&gt;alert(1)&lt;/script&gt;</h1>"));
+        assert!(html.contains("<h2>&lt;b&gt;.rs</h2>"));
+        assert!(html.contains("if a &lt; b &amp;&amp; b &gt; c {}"));
+        assert!(html.contains("<pre>&lt;i&gt;out&lt;/i&gt;</pre>"));
+    }
+
+    #[test]
+    fn test_binary_file() {
+        let snippet = snippet(
+            "Hello",
+            vec![File::binary("logo.png".to_string(), &[0, 1, 2])],
+        );
+
+        let html = render(&snippet, None);
+
+        assert!(html.contains(r#"<p class="binary">Binary file, 3 B</p>"#));
+        assert!(!html.contains("AAEC"));
+        assert!(!html.contains("<h2>Output</h2>"));
+    }
+}
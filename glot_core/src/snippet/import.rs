@@ -0,0 +1,793 @@
+use crate::error::ImportError;
+use crate::snippet::path;
+use crate::snippet::validate;
+use crate::snippet::File;
+use glot_languages::language::Language;
+use miniz_oxide::inflate;
+use serde::{Deserialize, Serialize};
+
+// Upper bound for a decompressed archive, protects against compression bombs
+const MAX_EXTRACTED_SIZE: usize = 4 * 1024 * 1024;
+// Larger files are not read by the browser
+pub const MAX_UPLOAD_SIZE: usize = 4 * 1024 * 1024;
+const TAR_BLOCK_SIZE: usize = 512;
+const IGNORED_DIRECTORIES: &[&str] = &["__MACOSX", ".git"];
+const IGNORED_FILES: &[&str] = &[".DS_Store", "Thumbs.db"];
+
+// Raw file as read by the browser, either a regular file or an archive
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Upload {
+    pub name: String,
+    pub size: usize,
+    pub bytes: Vec<u8>,
+}
+
+struct Entry {
+    name: String,
+    content: Result<Vec<u8>, ImportError>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Skipped {
+    pub name: String,
+    pub error: ImportError,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Imported {
+    pub files: Vec<File>,
+    pub skipped: Vec<Skipped>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictResolution {
+    #[default]
+    KeepBoth,
+    Replace,
+    Skip,
+}
+
+impl ConflictResolution {
+    pub fn label(&self) -> String {
+        match self {
+            ConflictResolution::KeepBoth => "Keep both (rename imported file)".into(),
+            ConflictResolution::Replace => "Replace existing file".into(),
+            ConflictResolution::Skip => "Skip imported file".into(),
+        }
+    }
+}
+
+pub fn from_js_value(value: serde_json::Value) -> Result<Vec<Upload>, ImportError> {
+    serde_json::from_value(value).map_err(|err| ImportError::InvalidMessage(err.to_string()))
+}
+
+pub fn read_uploads(uploads: Vec<Upload>) -> Imported {
+    let mut entries = vec![];
+    let mut skipped = vec![];
+
+    for upload in uploads {
+        match read_upload(&upload) {
+            Ok(upload_entries) => entries.extend(upload_entries),
+            Err(error) => skipped.push(Skipped {
+                name: upload.name,
+                error,
+            }),
+        }
+    }
+
+    let entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|entry| !is_ignored(&entry.name))
+        .collect();
+
+    let prefix = common_directory(&entries);
+    let mut files = vec![];
+
+    for entry in entries {
+        let name = entry.name[prefix.len()..].to_string();
+
+        match entry.content.and_then(|bytes| to_file(name.clone(), bytes)) {
+            Ok(file) => files.push(file),
+            Err(error) => skipped.push(Skipped { name, error }),
+        }
+    }
+
+    Imported { files, skipped }
+}
+
+// Merges imported files into the existing ones. Unless a file is explicitly marked as main,
+// the detected main file is marked as main. The order of the files is kept.
+pub fn merge(
+    existing: Vec<File>,
+    imported: Vec<File>,
+    resolution: ConflictResolution,
+    language: Language,
+) -> Vec<File> {
+    let mut files = existing;
+
+    for file in imported {
+        let conflict = files.iter().position(|f| f.name == file.name);
+
+        match (conflict, resolution) {
+            (None, _) => files.push(file),
+
+            // The replaced file keeps its attributes, i.e. main and read-only
+            (Some(index), ConflictResolution::Replace) => {
+                files[index] = File {
+                    content: file.content,
+                    encoding: file.encoding,
+                    ..files[index].clone()
+                };
+            }
+
+            (Some(_), ConflictResolution::KeepBoth) => {
+                let name = unique_filename(&files, &file.name);
+                files.push(File { name, ..file });
+            }
+
+            (Some(_), ConflictResolution::Skip) => {}
+        }
+    }
+
+    let has_explicit_main = files.iter().any(|file| file.main);
+
+    if let Some(index) = detect_main_file(&files, language).filter(|_| !has_explicit_main) {
+        files[index].main = true;
+    }
+
+    files
+}
+
+pub fn has_conflict(existing: &[File], file: &File) -> bool {
+    existing.iter().any(|f| f.name == file.name)
+}
+
+// The main file is the one matching the language's default filename, either at the top level or in a directory
+pub fn detect_main_file(files: &[File], language: Language) -> Option<usize> {
+    let default_filename = language.config().editor_config().default_filename;
+
+    let exact_match = files.iter().position(|file| file.name == default_filename);
+
+    let nested_match = || {
+        files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| path::basename(&file.name) == default_filename)
+            .min_by_key(|(_, file)| file.name.matches('/').count())
+            .map(|(index, _)| index)
+    };
+
+    exact_match.or_else(nested_match)
+}
+
+fn read_upload(upload: &Upload) -> Result<Vec<Entry>, ImportError> {
+    let bytes = &upload.bytes;
+
+    if upload.size > MAX_UPLOAD_SIZE {
+        return Err(ImportError::TooLarge {
+            size: upload.size,
+            max: MAX_UPLOAD_SIZE,
+        });
+    }
+
+    if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06") {
+        read_zip(bytes)
+    } else if bytes.starts_with(&[0x1f, 0x8b]) {
+        let tar = gunzip(bytes)?;
+        read_tar(&tar)
+    } else if is_tar(bytes) {
+        read_tar(bytes)
+    } else {
+        let name =
+            path::normalize(&upload.name).unwrap_or_else(|| path::basename(&upload.name).into());
+
+        Ok(vec![Entry {
+            name,
+            content: Ok(bytes.clone()),
+        }])
+    }
+}
+
+// Files that are not valid UTF-8 text are imported as binary files
+fn to_file(name: String, bytes: Vec<u8>) -> Result<File, ImportError> {
+    let file = match String::from_utf8(bytes) {
+        Ok(content) if !content.contains('\0') => File::text(name, content),
+        Ok(content) => File::binary(name, content.as_bytes()),
+        Err(err) => File::binary(name, err.as_bytes()),
+    };
+
+    // The limit applies to the stored content, which is larger than the original for binary files
+    if file.content.len() > validate::MAX_FILE_SIZE {
+        return Err(ImportError::TooLarge {
+            size: file.content.len(),
+            max: validate::MAX_FILE_SIZE,
+        });
+    }
+
+    Ok(file)
+}
+
+fn unique_filename(files: &[File], name: &str) -> String {
+    // The extension is only looked for in the basename, directories may contain dots
+    let basename_start = name.len() - path::basename(name).len();
+
+    let (stem, extension) = match name[basename_start..].rfind('.') {
+        Some(index) if index > 0 => name.split_at(basename_start + index),
+        _ => (name, ""),
+    };
+
+    (2..)
+        .map(|n| format!("{}-{}{}", stem, n, extension))
+        .find(|candidate| !files.iter().any(|f| &f.name == candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+fn is_ignored(path: &str) -> bool {
+    let mut components = path.split('/');
+
+    components.any(|component| IGNORED_DIRECTORIES.contains(&component))
+        || IGNORED_FILES.contains(&path::basename(path))
+}
+
+// Archives and dropped folders usually wrap everything in a single top level directory
+fn common_directory(entries: &[Entry]) -> String {
+    let first_directory = entries
+        .first()
+        .and_then(|entry| entry.name.split_once('/'))
+        .map(|(directory, _)| format!("{}/", directory));
+
+    match first_directory {
+        Some(prefix) if entries.iter().all(|entry| entry.name.starts_with(&prefix)) => prefix,
+        _ => String::new(),
+    }
+}
+
+fn read_zip(bytes: &[u8]) -> Result<Vec<Entry>, ImportError> {
+    let eocd = (0..bytes.len().saturating_sub(21))
+        .rev()
+        .find(|offset| bytes[*offset..].starts_with(b"PK\x05\x06"))
+        .ok_or_else(|| invalid_archive("Missing end of central directory"))?;
+
+    let entry_count = read_u16(bytes, eocd + 10)? as usize;
+    let mut offset = read_u32(bytes, eocd + 16)? as usize;
+    let mut entries = vec![];
+    let mut extracted_size = 0;
+
+    for _ in 0..entry_count {
+        // Fields are read from the fixed size part of the header, offsets from the archive are
+        // only added with an overflow check
+        let header = read_slice(bytes, offset, 46)?;
+
+        if read_u32(header, 0)? != 0x02014b50 {
+            return Err(invalid_archive("Invalid central directory entry"));
+        }
+
+        let method = read_u16(header, 10)?;
+        let compressed_size = read_u32(header, 20)? as usize;
+        let size = read_u32(header, 24)? as usize;
+        let name_length = read_u16(header, 28)? as usize;
+        let extra_length = read_u16(header, 30)? as usize;
+        let comment_length = read_u16(header, 32)? as usize;
+        let local_offset = read_u32(header, 42)? as usize;
+        let name = read_slice(bytes, add_offset(offset, 46)?, name_length)?;
+        let name = String::from_utf8_lossy(name).to_string();
+
+        offset = add_offset(offset, 46 + name_length + extra_length + comment_length)?;
+
+        let path = match path::normalize(&name) {
+            Some(path) if !name.ends_with('/') => path,
+            _ => continue,
+        };
+
+        if entries.len() == validate::MAX_FILES {
+            entries.push(too_many_files(path));
+            break;
+        }
+
+        let local_header = read_slice(bytes, local_offset, 30)?;
+        let local_name_length = read_u16(local_header, 26)? as usize;
+        let local_extra_length = read_u16(local_header, 28)? as usize;
+        let data_offset = add_offset(local_offset, 30 + local_name_length + local_extra_length)?;
+        let data = read_slice(bytes, data_offset, compressed_size)?;
+
+        let content = if size > validate::MAX_FILE_SIZE {
+            Err(ImportError::TooLarge {
+                size,
+                max: validate::MAX_FILE_SIZE,
+            })
+        } else {
+            match method {
+                0 => Ok(data.to_vec()),
+                8 => inflate::decompress_to_vec_with_limit(data, validate::MAX_FILE_SIZE)
+                    .map_err(|err| invalid_archive(&err.to_string())),
+                _ => Err(ImportError::UnsupportedCompression(method)),
+            }
+        };
+
+        extracted_size += content.as_ref().map(Vec::len).unwrap_or(0);
+
+        if extracted_size > MAX_EXTRACTED_SIZE {
+            return Err(ImportError::TooLarge {
+                size: extracted_size,
+                max: MAX_EXTRACTED_SIZE,
+            });
+        }
+
+        entries.push(Entry {
+            name: path,
+            content,
+        });
+    }
+
+    Ok(entries)
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, ImportError> {
+    let flags = *bytes
+        .get(3)
+        .ok_or_else(|| invalid_archive("Truncated gzip"))?;
+    let mut offset = 10;
+
+    if flags & 0x04 != 0 {
+        offset += 2 + read_u16(bytes, offset)? as usize;
+    }
+
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            let end = bytes[offset.min(bytes.len())..]
+                .iter()
+                .position(|b| *b == 0)
+                .ok_or_else(|| invalid_archive("Truncated gzip"))?;
+            offset += end + 1;
+        }
+    }
+
+    if flags & 0x02 != 0 {
+        offset += 2;
+    }
+
+    let data = bytes
+        .get(offset..)
+        .ok_or_else(|| invalid_archive("Truncated gzip"))?;
+
+    inflate::decompress_to_vec_with_limit(data, MAX_EXTRACTED_SIZE)
+        .map_err(|err| invalid_archive(&err.to_string()))
+}
+
+fn is_tar(bytes: &[u8]) -> bool {
+    bytes.len() >= TAR_BLOCK_SIZE && &bytes[257..262] == b"ustar"
+}
+
+fn read_tar(bytes: &[u8]) -> Result<Vec<Entry>, ImportError> {
+    let mut offset = 0;
+    let mut long_name: Option<String> = None;
+    let mut entries = vec![];
+
+    while offset + TAR_BLOCK_SIZE <= bytes.len() {
+        let header = &bytes[offset..offset + TAR_BLOCK_SIZE];
+
+        if header.iter().all(|b| *b == 0) {
+            break;
+        }
+
+        let size = parse_octal(&header[124..136])?;
+        let data = read_slice(bytes, offset + TAR_BLOCK_SIZE, size)?;
+        let type_flag = header[156];
+
+        // The data was read, so the padded size is at most one block past the end of the archive
+        offset = add_offset(
+            offset,
+            TAR_BLOCK_SIZE + size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE,
+        )?;
+
+        match type_flag {
+            // Pax extended header
+            b'x' => {
+                long_name = String::from_utf8_lossy(data)
+                    .lines()
+                    .find_map(|line| line.split_once(" path="))
+                    .map(|(_, path)| path.to_string());
+            }
+
+            // GNU long name
+            b'L' => {
+                long_name = Some(read_tar_string(data));
+            }
+
+            // Regular file
+            b'0' | b'\0' => {
+                let name = long_name.take().unwrap_or_else(|| {
+                    let prefix = read_tar_string(&header[345..500]);
+                    let name = read_tar_string(&header[0..100]);
+
+                    if prefix.is_empty() {
+                        name
+                    } else {
+                        format!("{}/{}", prefix, name)
+                    }
+                });
+
+                if let Some(path) = path::normalize(&name) {
+                    if entries.len() == validate::MAX_FILES {
+                        entries.push(too_many_files(path));
+                        break;
+                    }
+
+                    entries.push(Entry {
+                        name: path,
+                        content: Ok(data.to_vec()),
+                    });
+                }
+            }
+
+            // Directories, links and other special entries are not imported
+            _ => {
+                long_name = None;
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+// Marks where reading stopped, the remaining entries are not read
+fn too_many_files(name: String) -> Entry {
+    Entry {
+        name,
+        content: Err(ImportError::TooManyFiles {
+            max: validate::MAX_FILES,
+        }),
+    }
+}
+
+fn read_tar_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+fn parse_octal(bytes: &[u8]) -> Result<usize, ImportError> {
+    let value = read_tar_string(bytes);
+    let value = value.trim_matches(|c: char| c == ' ' || c == '\0');
+
+    if value.is_empty() {
+        Ok(0)
+    } else {
+        usize::from_str_radix(value, 8).map_err(|_| invalid_archive("Invalid tar header"))
+    }
+}
+
+fn read_slice(bytes: &[u8], offset: usize, length: usize) -> Result<&[u8], ImportError> {
+    let end = add_offset(offset, length)?;
+
+    bytes
+        .get(offset..end)
+        .ok_or_else(|| invalid_archive("Unexpected end of archive"))
+}
+
+// Offsets and sizes come from the archive, usize is only 32 bits on wasm
+fn add_offset(offset: usize, length: usize) -> Result<usize, ImportError> {
+    offset
+        .checked_add(length)
+        .ok_or_else(|| invalid_archive("Invalid offset"))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, ImportError> {
+    let slice = read_slice(bytes, offset, 2)?;
+    Ok(u16::from_le_bytes([slice[0], slice[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, ImportError> {
+    let slice = read_slice(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn invalid_archive(message: &str) -> ImportError {
+    ImportError::InvalidArchive(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::main_file_index;
+
+    fn upload(name: &str, bytes: &[u8]) -> Upload {
+        Upload {
+            name: name.to_string(),
+            size: bytes.len(),
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    fn names(files: &[File]) -> Vec<&str> {
+        files.iter().map(|file| file.name.as_str()).collect()
+    }
+
+    fn text_file(name: &str) -> File {
+        File::text(name.to_string(), name.to_string())
+    }
+
+    fn push_u16(bytes: &mut Vec<u8>, n: u16) {
+        bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn push_u32(bytes: &mut Vec<u8>, n: u32) {
+        bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    // Entries are stored without compression
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut central_directory = vec![];
+
+        for (name, data) in entries {
+            let local_offset = bytes.len() as u32;
+
+            push_u32(&mut bytes, 0x04034b50);
+            bytes.extend_from_slice(&[0; 14]);
+            push_u32(&mut bytes, data.len() as u32);
+            push_u32(&mut bytes, data.len() as u32);
+            push_u16(&mut bytes, name.len() as u16);
+            push_u16(&mut bytes, 0);
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(data);
+
+            push_u32(&mut central_directory, 0x02014b50);
+            central_directory.extend_from_slice(&[0; 16]);
+            push_u32(&mut central_directory, data.len() as u32);
+            push_u32(&mut central_directory, data.len() as u32);
+            push_u16(&mut central_directory, name.len() as u16);
+            central_directory.extend_from_slice(&[0; 12]);
+            push_u32(&mut central_directory, local_offset);
+            central_directory.extend_from_slice(name.as_bytes());
+        }
+
+        let central_directory_offset = bytes.len() as u32;
+        let central_directory_size = central_directory.len() as u32;
+        bytes.extend(central_directory);
+
+        push_u32(&mut bytes, 0x06054b50);
+        bytes.extend_from_slice(&[0; 6]);
+        push_u16(&mut bytes, entries.len() as u16);
+        push_u32(&mut bytes, central_directory_size);
+        push_u32(&mut bytes, central_directory_offset);
+        push_u16(&mut bytes, 0);
+
+        bytes
+    }
+
+    fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        for (name, data) in entries {
+            let mut header = [0; TAR_BLOCK_SIZE];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            let size = format!("{:011o}", data.len());
+            header[124..135].copy_from_slice(size.as_bytes());
+            header[156] = b'0';
+            header[257..262].copy_from_slice(b"ustar");
+
+            bytes.extend_from_slice(&header);
+            bytes.extend_from_slice(data);
+            bytes.resize(bytes.len().div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE, 0);
+        }
+
+        bytes.extend_from_slice(&[0; 2 * TAR_BLOCK_SIZE]);
+        bytes
+    }
+
+    #[test]
+    fn test_read_plain_files() {
+        let imported = read_uploads(vec![
+            upload("main.rs", b"fn main() {}"),
+            upload("logo.png", &[0x89, 0x50, 0xff, 0x00]),
+            upload(".DS_Store", b""),
+        ]);
+
+        assert_eq!(names(&imported.files), vec!["main.rs", "logo.png"]);
+        assert!(!imported.files[0].is_binary());
+        assert!(imported.files[1].is_binary());
+        assert_eq!(imported.files[1].bytes(), vec![0x89, 0x50, 0xff, 0x00]);
+        assert!(imported.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_read_zip() {
+        let bytes = zip(&[
+            ("project/", b""),
+            ("project/src/main.rs", b"fn main() {}"),
+            ("project/Cargo.toml", b"[package]"),
+            ("project/.git/HEAD", b"ref"),
+            ("__MACOSX/project/._Cargo.toml", b""),
+        ]);
+
+        let imported = read_uploads(vec![upload("project.zip", &bytes)]);
+
+        assert_eq!(names(&imported.files), vec!["src/main.rs", "Cargo.toml"]);
+        assert_eq!(imported.files[0].content, "fn main() {}");
+    }
+
+    #[test]
+    fn test_read_tar() {
+        let bytes = tar(&[
+            ("src/main.rs", b"fn main() {}"),
+            ("Cargo.toml", b"[package]"),
+            ("../evil.rs", b""),
+        ]);
+        let imported = read_uploads(vec![upload("project.tar", &bytes)]);
+
+        assert_eq!(names(&imported.files), vec!["src/main.rs", "Cargo.toml"]);
+        assert_eq!(imported.files[0].content, "fn main() {}");
+    }
+
+    #[test]
+    fn test_zip_file_limit() {
+        let entries: Vec<(String, &[u8])> = (0..validate::MAX_FILES + 5)
+            .map(|n| (format!("{}.txt", n), b"x" as &[u8]))
+            .collect();
+        let entries: Vec<(&str, &[u8])> = entries
+            .iter()
+            .map(|(name, data)| (name.as_str(), *data))
+            .collect();
+
+        let imported = read_uploads(vec![upload("files.zip", &zip(&entries))]);
+
+        assert_eq!(imported.files.len(), validate::MAX_FILES);
+        assert_eq!(imported.skipped.len(), 1);
+        assert_eq!(
+            imported.skipped[0].error,
+            ImportError::TooManyFiles {
+                max: validate::MAX_FILES
+            }
+        );
+    }
+
+    #[test]
+    fn test_zip_extracted_size_limit() {
+        // Central directory entries can point to the same data and understate its size, the
+        // second entry is changed to point to the data of the first
+        let data = vec![b'a'; MAX_EXTRACTED_SIZE / 2 + 1];
+        let mut bytes = zip(&[("a.txt", &data), ("b.txt", b"")]);
+        let eocd = bytes.len() - 22;
+        let first_entry = read_u32(&bytes, eocd + 16).unwrap() as usize;
+        let second_entry = first_entry + 46 + "a.txt".len();
+        bytes.copy_within(18..22, second_entry + 20);
+        bytes[second_entry + 42..second_entry + 46].copy_from_slice(&[0; 4]);
+
+        for entry in [first_entry, second_entry] {
+            bytes[entry + 24..entry + 28].copy_from_slice(&1u32.to_le_bytes());
+        }
+
+        let imported = read_uploads(vec![upload("files.zip", &bytes)]);
+
+        assert!(imported.files.is_empty());
+        assert_eq!(
+            imported.skipped[0].error,
+            ImportError::TooLarge {
+                size: 2 * data.len(),
+                max: MAX_EXTRACTED_SIZE
+            }
+        );
+    }
+
+    #[test]
+    fn test_too_large() {
+        let large = Upload {
+            name: "video.mp4".to_string(),
+            size: MAX_UPLOAD_SIZE + 1,
+            bytes: vec![],
+        };
+        let content = vec![b'a'; validate::MAX_FILE_SIZE + 1];
+
+        let imported = read_uploads(vec![large, upload("big.txt", &content)]);
+
+        assert!(imported.files.is_empty());
+        assert_eq!(
+            imported.skipped[0].error,
+            ImportError::TooLarge {
+                size: MAX_UPLOAD_SIZE + 1,
+                max: MAX_UPLOAD_SIZE
+            }
+        );
+        assert_eq!(imported.skipped[1].name, "big.txt");
+    }
+
+    #[test]
+    fn test_invalid_archive() {
+        let mut bytes = zip(&[("main.rs", b"fn main() {}")]);
+        bytes.truncate(bytes.len() - 30);
+
+        let imported = read_uploads(vec![upload("broken.zip", &bytes)]);
+
+        assert!(imported.files.is_empty());
+        assert!(matches!(
+            imported.skipped[0].error,
+            ImportError::InvalidArchive(_)
+        ));
+    }
+
+    #[test]
+    fn test_offset_overflow() {
+        let mut bytes = zip(&[("main.rs", b"fn main() {}")]);
+        let eocd = bytes.len() - 22;
+        let entry = read_u32(&bytes, eocd + 16).unwrap() as usize;
+        bytes[entry + 42..entry + 46].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+
+        let imported = read_uploads(vec![upload("broken.zip", &bytes)]);
+
+        assert!(imported.files.is_empty());
+        assert!(matches!(
+            imported.skipped[0].error,
+            ImportError::InvalidArchive(_)
+        ));
+        assert!(read_slice(&bytes, usize::MAX - 1, 4).is_err());
+        assert!(add_offset(usize::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let existing = vec![text_file("lib.rs"), text_file("util.rs")];
+        let imported = vec![text_file("util.rs"), text_file("src/main.rs")];
+
+        let keep_both = merge(
+            existing.clone(),
+            imported.clone(),
+            ConflictResolution::KeepBoth,
+            Language::Rust,
+        );
+        assert_eq!(
+            names(&keep_both),
+            vec!["lib.rs", "util.rs", "util-2.rs", "src/main.rs"]
+        );
+        assert_eq!(main_file_index(&keep_both), 3);
+
+        let skip = merge(
+            existing.clone(),
+            imported.clone(),
+            ConflictResolution::Skip,
+            Language::Rust,
+        );
+        assert_eq!(names(&skip), vec!["lib.rs", "util.rs", "src/main.rs"]);
+        assert!(skip[2].main);
+
+        let mut main = text_file("lib.rs");
+        main.main = true;
+        let mut replaced = File::text("util.rs".to_string(), "new".to_string());
+        replaced.read_only = true;
+        let replace = merge(
+            vec![main, text_file("util.rs")],
+            vec![replaced],
+            ConflictResolution::Replace,
+            Language::Rust,
+        );
+        assert_eq!(names(&replace), vec!["lib.rs", "util.rs"]);
+        assert_eq!(main_file_index(&replace), 0);
+        assert!(!replace[1].main);
+        assert_eq!(replace[1].content, "new");
+        assert!(!replace[1].read_only);
+    }
+
+    #[test]
+    fn test_unique_filename() {
+        let files = vec![text_file("main.rs"), text_file("main-2.rs")];
+
+        assert_eq!(unique_filename(&files, "main.rs"), "main-3.rs");
+        assert_eq!(unique_filename(&files, "v1.0/Makefile"), "v1.0/Makefile-2");
+        assert_eq!(unique_filename(&files, ".gitignore"), ".gitignore-2");
+    }
+
+    #[test]
+    fn test_detect_main_file() {
+        let files = vec![
+            text_file("lib.rs"),
+            text_file("examples/demo/main.rs"),
+            text_file("src/main.rs"),
+        ];
+
+        assert_eq!(detect_main_file(&files, Language::Rust), Some(2));
+        assert_eq!(detect_main_file(&files[..1], Language::Rust), None);
+    }
+}
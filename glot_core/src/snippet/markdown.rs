@@ -0,0 +1,209 @@
+use crate::run::RunResult;
+use crate::snippet::path;
+use crate::snippet::validate;
+use crate::snippet::File;
+use crate::snippet::Snippet;
+
+pub fn render(snippet: &Snippet, run_result: Option<&RunResult>) -> String {
+    let mut sections = vec![
+        format!("# {}", escape(&snippet.title)),
+        format!("**Language:** {}", snippet.language.config().name()),
+    ];
+
+    for file in &snippet.files {
+        let body = if file.is_binary() {
+            format!("_{}_", binary_description(file))
+        } else {
+            fenced(&code_language(snippet, file), &file.content)
+        };
+
+        sections.push(format!("### {}\n\n{}", escape(&file.name), body));
+    }
+
+    for (heading, stdin) in snippet.labeled_inputs() {
+        sections.push(format!("### {}\n\n{}", escape(&heading), fenced("", stdin)));
+    }
+
+    if !snippet.env.is_empty() {
+        let env = snippet.env_lines().join("\n");
+
+        sections.push(format!("### Environment\n\n{}", fenced("", &env)));
+    }
+
+    if !snippet.args.is_empty() {
+        let args = snippet.args.join("\n");
+        sections.push(format!("### Arguments\n\n{}", fenced("", &args)));
+    }
+
+    if let Some(run_result) = run_result {
+        let outputs = [
+            ("Output", &run_result.stdout),
+            ("Stderr", &run_result.stderr),
+            ("Error", &run_result.error),
+        ];
+
+        for (heading, output) in outputs {
+            if !output.is_empty() {
+                sections.push(format!("### {}\n\n{}", heading, fenced("text", output)));
+            }
+        }
+    }
+
+    sections.join("\n\n") + "\n"
+}
+
+// Characters that could start inline markup are backslash escaped, so titles and filenames,
+// i.e. __init__.py, are shown as written
+pub(crate) fn escape(text: &str) -> String {
+    text.chars().fold(String::new(), |mut escaped, c| {
+        if "\\`*_[]<>#!|~&".contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+        escaped
+    })
+}
+
+// Binary content isn't included, it can't be shown or copied as text
+pub(crate) fn binary_description(file: &File) -> String {
+    format!("Binary file, {}", validate::format_size(file.bytes().len()))
+}
+
+// Fenced code block with a fence longer than any backtick run in the content
+pub(crate) fn fenced(info: &str, content: &str) -> String {
+    let longest_backtick_run = content
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+
+    let fence = "`".repeat(longest_backtick_run.max(2) + 1);
+    let newline = if content.ends_with('\n') { "" } else { "\n" };
+
+    format!("{}{}\n{}{}{}", fence, info, content, newline, fence)
+}
+
+// Files with the same extension as the language's default file are highlighted as that language
+pub(crate) fn code_language(snippet: &Snippet, file: &File) -> String {
+    let config = snippet.language.config();
+    let default_filename = config.editor_config().default_filename;
+
+    match (extension(&file.name), extension(&default_filename)) {
+        (Some(ext), Some(default_ext)) if ext == default_ext => config.id(),
+        (Some(ext), _) => ext.to_lowercase(),
+        (None, _) => String::new(),
+    }
+}
+
+fn extension(filename: &str) -> Option<&str> {
+    path::basename(filename)
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::StdinInput;
+    use glot_languages::language::Language;
+
+    fn snippet(title: &str, files: Vec<File>) -> Snippet {
+        Snippet {
+            language: Language::Rust,
+            title: title.to_string(),
+            files,
+            inputs: vec![],
+            instructions: None,
+            env: Default::default(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    fn text_file(name: &str, content: &str) -> File {
+        File::text(name.to_string(), content.to_string())
+    }
+
+    fn run_result(stdout: &str, stderr: &str, error: &str) -> RunResult {
+        RunResult {
+            duration: 0,
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            error: error.to_string(),
+            exit_code: None,
+            signal: None,
+            peak_memory: None,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_fenced() {
+        assert_eq!(
+            fenced("rust", "fn main() {}\n"),
+            "```rust\nfn main() {}\n```"
+        );
+        assert_eq!(fenced("", "no newline"), "```\nno newline\n```");
+        assert_eq!(fenced("", "```\ncode\n```"), "````\n```\ncode\n```\n````");
+        assert_eq!(fenced("", "a ````` b"), "``````\na ````` b\n``````");
+    }
+
+    #[test]
+    fn test_code_language() {
+        let snippet = snippet("Hello", vec![]);
+        let language = |name: &str| code_language(&snippet, &text_file(name, ""));
+
+        assert_eq!(language("src/main.rs"), snippet.language.config().id());
+        assert_eq!(language("data.JSON"), "json");
+        assert_eq!(language("v1.0/Makefile"), "");
+        assert_eq!(language("file."), "");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("Hello World"), "Hello World");
+        assert_eq!(escape("*x*"), "\\*x\\*");
+        assert_eq!(escape("<b>"), "\\<b\\>");
+        assert_eq!(escape("__init__.py"), "\\_\\_init\\_\\_.py");
+        assert_eq!(escape("a\\b # c"), "a\\\\b \\# c");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut snippet = snippet(
+            "*x* <b>",
+            vec![
+                text_file("main.rs", "fn main() {}\n"),
+                File::binary("logo.png".to_string(), &[0; 2048]),
+            ],
+        );
+        snippet.inputs = vec![StdinInput {
+            name: "Input 1".to_string(),
+            content: "42\n".to_string(),
+            expected_output: None,
+        }];
+        snippet.args = vec!["-v".to_string()];
+
+        let markdown = render(&snippet, None);
+
+        assert!(markdown.starts_with("# \\*x\\* \\<b\\>\n"));
+        assert!(markdown.contains("### main.rs\n\n```rust\nfn main() {}\n```"));
+        assert!(markdown.contains("### logo.png\n\n_Binary file, 2.0 KB_"));
+        assert!(markdown.contains("### Stdin\n\n```\n42\n```"));
+        assert!(markdown.contains("### Arguments\n\n```\n-v\n```"));
+        assert!(!markdown.contains("### Output"));
+    }
+
+    #[test]
+    fn test_render_run_result() {
+        let snippet = snippet("Hello", vec![text_file("main.rs", "")]);
+
+        let markdown = render(&snippet, Some(&run_result("hi\n", "", "exit status 1")));
+
+        assert!(markdown.contains("### Output\n\n```text\nhi\n```"));
+        assert!(!markdown.contains("### Stderr"));
+        assert!(markdown.ends_with("### Error\n\n```text\nexit status 1\n```\n"));
+    }
+}
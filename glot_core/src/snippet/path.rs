@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const SEPARATOR: char = '/';
+pub const MAX_PATH_LENGTH: usize = 255;
+pub const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathError {
+    Empty,
+    Absolute,
+    Traversal,
+    EmptyComponent,
+    Backslash,
+    ControlCharacter,
+    TooLong { length: usize, max: usize },
+    TooDeep { depth: usize, max: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "Filename cannot be empty"),
+            PathError::Absolute => write!(f, "Filename must be a relative path"),
+            PathError::Traversal => {
+                write!(f, "Filename cannot contain '.' or '..' as a directory")
+            }
+            PathError::EmptyComponent => {
+                write!(f, "Filename cannot contain empty directory names")
+            }
+            PathError::Backslash => write!(f, "Use '/' to separate directories"),
+            PathError::ControlCharacter => {
+                write!(f, "Filename cannot contain control characters")
+            }
+            PathError::TooLong { length, max } => {
+                write!(
+                    f,
+                    "Filename is too long: {} characters (max {})",
+                    length, max
+                )
+            }
+            PathError::TooDeep { depth, max } => {
+                write!(f, "Too many nested directories: {} (max {})", depth, max)
+            }
+        }
+    }
+}
+
+// Filenames are relative paths using '/' as separator, they must stay inside the snippet directory
+pub fn validate(path: &str) -> Result<(), PathError> {
+    let length = path.chars().count();
+    let depth = components(path).count();
+
+    if path.is_empty() {
+        Err(PathError::Empty)
+    } else if path.starts_with(SEPARATOR) {
+        Err(PathError::Absolute)
+    } else if path.contains('\\') {
+        Err(PathError::Backslash)
+    } else if path.chars().any(char::is_control) {
+        Err(PathError::ControlCharacter)
+    } else if components(path).any(|component| component == "." || component == "..") {
+        Err(PathError::Traversal)
+    } else if components(path).any(str::is_empty) {
+        Err(PathError::EmptyComponent)
+    } else if length > MAX_PATH_LENGTH {
+        Err(PathError::TooLong {
+            length,
+            max: MAX_PATH_LENGTH,
+        })
+    } else if depth > MAX_DEPTH {
+        Err(PathError::TooDeep {
+            depth,
+            max: MAX_DEPTH,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+// Lenient version of validate used for paths from archives and the file system
+pub fn normalize(path: &str) -> Option<String> {
+    let components: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect();
+
+    if components.is_empty() || components.contains(&"..") {
+        None
+    } else {
+        Some(components.join("/"))
+    }
+}
+
+pub fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split(SEPARATOR)
+}
+
+pub fn basename(path: &str) -> &str {
+    path.rsplit(SEPARATOR).next().unwrap_or(path)
+}
+
+pub fn directory(path: &str) -> Option<&str> {
+    path.rsplit_once(SEPARATOR).map(|(directory, _)| directory)
+}
+
+// True if one path is used as a directory by the other, i.e. "src" and "src/main.rs"
+pub fn is_directory_conflict(a: &str, b: &str) -> bool {
+    let is_parent = |parent: &str, child: &str| {
+        child.len() > parent.len()
+            && child.starts_with(parent)
+            && child[parent.len()..].starts_with(SEPARATOR)
+    };
+
+    is_parent(a, b) || is_parent(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate("main.rs"), Ok(()));
+        assert_eq!(validate("src/lib/util.rs"), Ok(()));
+        assert_eq!(validate(".gitignore"), Ok(()));
+        assert_eq!(validate(""), Err(PathError::Empty));
+        assert_eq!(validate("/etc/passwd"), Err(PathError::Absolute));
+        assert_eq!(validate("src\\main.rs"), Err(PathError::Backslash));
+        assert_eq!(validate("a\nb"), Err(PathError::ControlCharacter));
+        assert_eq!(validate("../main.rs"), Err(PathError::Traversal));
+        assert_eq!(validate("src/./main.rs"), Err(PathError::Traversal));
+        assert_eq!(validate("src//main.rs"), Err(PathError::EmptyComponent));
+        assert_eq!(validate("src/"), Err(PathError::EmptyComponent));
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let long = "a".repeat(MAX_PATH_LENGTH + 1);
+        assert_eq!(
+            validate(&long),
+            Err(PathError::TooLong {
+                length: MAX_PATH_LENGTH + 1,
+                max: MAX_PATH_LENGTH
+            })
+        );
+
+        // Characters are counted, not bytes
+        assert_eq!(validate(&"ä".repeat(MAX_PATH_LENGTH)), Ok(()));
+
+        let deep = vec!["a"; MAX_DEPTH + 1].join("/");
+        assert_eq!(
+            validate(&deep),
+            Err(PathError::TooDeep {
+                depth: MAX_DEPTH + 1,
+                max: MAX_DEPTH
+            })
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("src/main.rs"), Some("src/main.rs".to_string()));
+        assert_eq!(normalize("/src//main.rs"), Some("src/main.rs".to_string()));
+        assert_eq!(normalize("./src\\main.rs"), Some("src/main.rs".to_string()));
+        assert_eq!(normalize("src/../main.rs"), None);
+        assert_eq!(normalize("/"), None);
+    }
+
+    #[test]
+    fn test_basename_and_directory() {
+        assert_eq!(basename("src/lib/util.rs"), "util.rs");
+        assert_eq!(basename("main.rs"), "main.rs");
+        assert_eq!(directory("src/lib/util.rs"), Some("src/lib"));
+        assert_eq!(directory("main.rs"), None);
+    }
+
+    #[test]
+    fn test_directory_conflict() {
+        assert!(is_directory_conflict("src", "src/main.rs"));
+        assert!(is_directory_conflict("src/main.rs", "src"));
+        assert!(!is_directory_conflict("src", "src.rs"));
+        assert!(!is_directory_conflict("src", "srcs/main.rs"));
+        assert!(!is_directory_conflict("src", "src"));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::snippet;
+use crate::snippet::dictionary;
+use crate::snippet::dictionary::Dictionary;
+use crate::snippet::envelope::Envelope;
+use crate::snippet::File;
+use crate::snippet::FileEncoding;
+use base_62::base62;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+const BINARY_ENCODING_FIELD: &str = r#","encoding":"base64""#;
+const FILES_FIELD: &str = r#""files":["#;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialSnippet {
+    pub title: Option<String>,
+    pub files: Vec<File>,
+}
+
+// Salvages whatever the brotli stream produced before it failed.
+// File contents may be cut short if the stream ended in the middle of a file.
+//
+// The base62 payload is decoded as one big number, so a link that was cut short decodes to
+// unrelated bytes and nothing can be recovered from it. What can be recovered is a link where
+// only the last characters were changed, which corrupts the end of the brotli stream, and a
+// snippet that decompresses but can't be parsed or migrated.
+pub fn recover(encoded: &str) -> Option<PartialSnippet> {
+    let (payload, dictionary) = match Envelope::parse(encoded) {
+        Ok(envelope) => (
+            envelope.payload,
+            envelope.dictionary.and_then(dictionary::get),
+        ),
+        Err(_) => {
+            let payload = encoded
+                .split_once('_')
+                .map(|(_, payload)| payload)
+                .unwrap_or(encoded);
+
+            (payload.to_string(), None)
+        }
+    };
+
+    let compressed = base62::decode(&payload).ok()?;
+    let json = decompress_partial(&compressed, dictionary);
+    let text = String::from_utf8_lossy(&json);
+
+    let title = find_string_value(&text, "title").map(|(title, _)| title);
+    let files = find_files(&text);
+
+    if title.is_none() && files.is_empty() {
+        None
+    } else {
+        Some(PartialSnippet { title, files })
+    }
+}
+
+fn decompress_partial(compressed: &[u8], dictionary: Option<&Dictionary>) -> Vec<u8> {
+    let mut decompressor = snippet::decompressor(compressed, dictionary);
+    let mut buffer = [0; 4096];
+    let mut output = vec![];
+
+    while let Ok(count) = decompressor.read(&mut buffer) {
+        if count == 0 {
+            break;
+        }
+
+        output.extend_from_slice(&buffer[..count]);
+    }
+
+    output
+}
+
+// Only the files array is scanned, stdin inputs also have a name and content
+fn find_files(text: &str) -> Vec<File> {
+    let mut files = vec![];
+
+    let mut rest = match text.find(FILES_FIELD) {
+        Some(start) => &text[start + FILES_FIELD.len()..],
+        None => return files,
+    };
+
+    if rest.starts_with(']') {
+        return files;
+    }
+
+    while let Some((name, after_name)) = find_string_value(rest, "name") {
+        match find_string_value(after_name, "content") {
+            Some((content, after_content)) => {
+                // The encoding is serialized after the content and only for binary files
+                let file = if after_content.starts_with(BINARY_ENCODING_FIELD) {
+                    File {
+                        encoding: FileEncoding::Base64,
+                        ..File::text(name, content)
+                    }
+                } else {
+                    File::text(name, content)
+                };
+
+                files.push(file);
+
+                match skip_object(after_content) {
+                    Some(after_file) if !after_file.starts_with(']') => rest = after_file,
+                    _ => break,
+                }
+            }
+
+            None => {
+                files.push(File::text(name, "".to_string()));
+                break;
+            }
+        }
+    }
+
+    files
+}
+
+// Skips the remaining fields of an object, returns the text after the closing brace
+fn skip_object(text: &str) -> Option<&str> {
+    let mut rest = text;
+
+    loop {
+        let index = rest.find(['"', '}'])?;
+
+        if rest[index..].starts_with('}') {
+            return Some(&rest[index + 1..]);
+        }
+
+        let (_, after_string) = read_string(&rest[index + 1..]);
+        rest = after_string;
+    }
+}
+
+fn find_string_value<'a>(text: &'a str, key: &str) -> Option<(String, &'a str)> {
+    let pattern = format!("\"{}\":\"", key);
+    let start = text.find(&pattern)? + pattern.len();
+    Some(read_string(&text[start..]))
+}
+
+// Reads a json string up to the closing quote, or to the end of the input if it was truncated
+fn read_string(text: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return (value, &text[index + 1..]),
+
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, 'b')) => value.push('\u{8}'),
+                Some((_, 'f')) => value.push('\u{c}'),
+                Some((_, 'u')) => {
+                    let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+
+                    if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                        value.push(c);
+                    }
+                }
+                Some((_, c)) => value.push(c),
+                None => break,
+            },
+
+            c => value.push(c),
+        }
+    }
+
+    (value, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::Snippet;
+
+    const JSON: &str = r#"{"language":"python","title":"Hello","files":[{"name":"main.py","content":"print(\"hi\")\n"},{"name":"logo.png","content":"AAEC","encoding":"base64","main":true}],"inputs":[{"name":"Input 1","content":"stdin"}]}"#;
+
+    fn encode(json: &str) -> String {
+        let compressed = snippet::compress(json.as_bytes(), None).unwrap();
+        Envelope::new(base62::encode(&compressed), None).to_string()
+    }
+
+    fn names(snippet: &PartialSnippet) -> Vec<&str> {
+        snippet
+            .files
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_recover_unparsable_snippet() {
+        // Decompresses fine, but the language is unknown
+        let json = JSON.replace("python", "cobol");
+        assert!(Snippet::from_encoded_string(&encode(&json)).is_err());
+
+        let recovered = recover(&encode(&json)).unwrap();
+        assert_eq!(recovered.title.as_deref(), Some("Hello"));
+        assert_eq!(names(&recovered), vec!["main.py", "logo.png"]);
+        assert_eq!(recovered.files[0].content, "print(\"hi\")\n");
+        assert!(!recovered.files[0].is_binary());
+        assert!(recovered.files[1].is_binary());
+    }
+
+    #[test]
+    fn test_inputs_are_not_files() {
+        let recovered = recover(&encode(JSON)).unwrap();
+        assert_eq!(names(&recovered), vec!["main.py", "logo.png"]);
+
+        let without_files =
+            r#"{"title":"Hello","files":[],"inputs":[{"name":"Input 1","content":"stdin"}]}"#;
+        let recovered = recover(&encode(without_files)).unwrap();
+        assert!(recovered.files.is_empty());
+    }
+
+    #[test]
+    fn test_recover_incomplete_json() {
+        let end = JSON.find("n\"}").unwrap();
+        let recovered = recover(&encode(&JSON[..end])).unwrap();
+
+        assert_eq!(names(&recovered), vec!["main.py"]);
+        assert_eq!(recovered.files[0].content, "print(\"hi\")");
+
+        let end = JSON.find("go.png").unwrap();
+        let recovered = recover(&encode(&JSON[..end])).unwrap();
+        assert_eq!(names(&recovered), vec!["main.py", "lo"]);
+        assert_eq!(recovered.files[1].content, "");
+    }
+
+    #[test]
+    fn test_truncated_link_is_not_recovered() {
+        // The payload is one big number, without the last characters it's unrelated bytes
+        let encoded = encode(JSON);
+        assert_eq!(recover(&encoded[..encoded.len() - 5]).map(|_| ()), None);
+    }
+}
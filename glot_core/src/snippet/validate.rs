@@ -0,0 +1,628 @@
+use crate::snippet::expected_output;
+use crate::snippet::path;
+use crate::snippet::path::PathError;
+use crate::snippet::File;
+use crate::snippet::Snippet;
+use crate::util::base64;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const MAX_FILES: usize = 32;
+pub const MAX_FILE_SIZE: usize = 128 * 1024;
+pub const MAX_TOTAL_SIZE: usize = 256 * 1024;
+pub const MAX_TITLE_LENGTH: usize = 50;
+pub const MAX_STDIN_SIZE: usize = 64 * 1024;
+pub const MAX_INPUTS: usize = 16;
+pub const MAX_RUN_OPTIONS_SIZE: usize = 8 * 1024;
+pub const MAX_TIMEOUT: u64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Violation {
+    TooManyFiles {
+        count: usize,
+        max: usize,
+    },
+    FileTooLarge {
+        name: String,
+        size: usize,
+        max: usize,
+    },
+    TotalSizeTooLarge {
+        size: usize,
+        max: usize,
+    },
+    TitleTooLong {
+        length: usize,
+        max: usize,
+    },
+    TitleControlCharacter,
+    StdinTooLarge {
+        size: usize,
+        max: usize,
+    },
+    InvalidBinaryContent {
+        name: String,
+    },
+    InvalidFilename {
+        name: String,
+        error: PathError,
+    },
+    DuplicateFilename {
+        name: String,
+    },
+    DirectoryConflict {
+        name: String,
+        other: String,
+    },
+    MultipleMainFiles {
+        names: Vec<String>,
+    },
+    TooManyInputs {
+        count: usize,
+        max: usize,
+    },
+    DuplicateInputName {
+        name: String,
+    },
+    InvalidExpectedOutput {
+        name: String,
+        error: String,
+    },
+    InvalidEnvName {
+        name: String,
+    },
+    RunOptionsTooLarge {
+        size: usize,
+        max: usize,
+    },
+    InvalidTimeout {
+        timeout: u64,
+        max: u64,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::TooManyFiles { count, max } => {
+                write!(f, "Too many files: {} (max {})", count, max)
+            }
+
+            Violation::FileTooLarge { name, size, max } => {
+                write!(
+                    f,
+                    "{} is too large: {} (max {})",
+                    name,
+                    format_size(*size),
+                    format_size(*max)
+                )
+            }
+
+            Violation::TotalSizeTooLarge { size, max } => {
+                write!(
+                    f,
+                    "Files are too large in total: {} (max {})",
+                    format_size(*size),
+                    format_size(*max)
+                )
+            }
+
+            Violation::TitleTooLong { length, max } => {
+                write!(f, "Title is too long: {} characters (max {})", length, max)
+            }
+
+            Violation::TitleControlCharacter => {
+                write!(f, "Title cannot contain control characters")
+            }
+
+            Violation::StdinTooLarge { size, max } => {
+                write!(
+                    f,
+                    "Stdin is too large: {} (max {})",
+                    format_size(*size),
+                    format_size(*max)
+                )
+            }
+
+            Violation::InvalidBinaryContent { name } => {
+                write!(f, "{} is not valid base64", name)
+            }
+
+            Violation::InvalidFilename { name, error } => {
+                write!(f, "Invalid filename {}: {}", name, error)
+            }
+
+            Violation::DuplicateFilename { name } => {
+                write!(f, "{} is used by more than one file", name)
+            }
+
+            Violation::DirectoryConflict { name, other } => {
+                write!(
+                    f,
+                    "{} conflicts with {}, a file can't also be a directory",
+                    name, other
+                )
+            }
+
+            Violation::MultipleMainFiles { names } => {
+                write!(f, "Only one main file allowed: {}", names.join(", "))
+            }
+
+            Violation::TooManyInputs { count, max } => {
+                write!(f, "Too many stdin inputs: {} (max {})", count, max)
+            }
+
+            Violation::DuplicateInputName { name } => {
+                write!(f, "Stdin input name is used more than once: {}", name)
+            }
+
+            Violation::InvalidExpectedOutput { name, error } => {
+                write!(f, "Invalid expected output pattern for {}: {}", name, error)
+            }
+
+            Violation::InvalidEnvName { name } => {
+                write!(f, "Invalid environment variable name: {}", name)
+            }
+
+            Violation::RunOptionsTooLarge { size, max } => {
+                write!(
+                    f,
+                    "Environment variables and arguments are too large: {} (max {})",
+                    format_size(*size),
+                    format_size(*max)
+                )
+            }
+
+            Violation::InvalidTimeout { timeout, max } => {
+                write!(
+                    f,
+                    "Invalid time limit: {} seconds (expected 1 to {})",
+                    timeout, max
+                )
+            }
+        }
+    }
+}
+
+pub fn validate(snippet: &Snippet) -> Result<(), Vec<Violation>> {
+    let mut violations = file_violations(&snippet.files);
+
+    let title_length = snippet.title.chars().count();
+    if title_length > MAX_TITLE_LENGTH {
+        violations.push(Violation::TitleTooLong {
+            length: title_length,
+            max: MAX_TITLE_LENGTH,
+        });
+    }
+
+    if snippet.title.chars().any(char::is_control) {
+        violations.push(Violation::TitleControlCharacter);
+    }
+
+    // The limit applies to all inputs and expected outputs combined
+    let stdin_size: usize = snippet
+        .inputs
+        .iter()
+        .map(|input| {
+            let expected_size = input
+                .expected_output
+                .as_ref()
+                .map(|expected| expected.stdout.len())
+                .unwrap_or(0);
+
+            input.content.len() + expected_size
+        })
+        .sum();
+    if stdin_size > MAX_STDIN_SIZE {
+        violations.push(Violation::StdinTooLarge {
+            size: stdin_size,
+            max: MAX_STDIN_SIZE,
+        });
+    }
+
+    if snippet.inputs.len() > MAX_INPUTS {
+        violations.push(Violation::TooManyInputs {
+            count: snippet.inputs.len(),
+            max: MAX_INPUTS,
+        });
+    }
+
+    for (index, input) in snippet.inputs.iter().enumerate() {
+        if snippet.inputs[index + 1..]
+            .iter()
+            .any(|other| other.name == input.name)
+        {
+            violations.push(Violation::DuplicateInputName {
+                name: input.name.clone(),
+            });
+        }
+
+        let pattern_result = input
+            .expected_output
+            .as_ref()
+            .map(expected_output::validate_pattern)
+            .unwrap_or(Ok(()));
+
+        if let Err(err) = pattern_result {
+            violations.push(Violation::InvalidExpectedOutput {
+                name: input.name.clone(),
+                error: err.to_string(),
+            });
+        }
+    }
+
+    for name in snippet.env.keys() {
+        if !is_valid_env_name(name) {
+            violations.push(Violation::InvalidEnvName { name: name.clone() });
+        }
+    }
+
+    let run_options_size: usize = snippet
+        .env
+        .iter()
+        .map(|(name, value)| name.len() + value.len())
+        .chain(snippet.args.iter().map(String::len))
+        .sum();
+    if run_options_size > MAX_RUN_OPTIONS_SIZE {
+        violations.push(Violation::RunOptionsTooLarge {
+            size: run_options_size,
+            max: MAX_RUN_OPTIONS_SIZE,
+        });
+    }
+
+    if let Some(timeout) = snippet.timeout {
+        if !is_valid_timeout(timeout) {
+            violations.push(Violation::InvalidTimeout {
+                timeout,
+                max: MAX_TIMEOUT,
+            });
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+pub fn is_valid_timeout(timeout: u64) -> bool {
+    (1..=MAX_TIMEOUT).contains(&timeout)
+}
+
+// Same rules as a shell variable name, so the name can be exported in the local run script
+pub fn is_valid_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+pub fn validate_files(files: &[File]) -> Result<(), Vec<Violation>> {
+    let violations = file_violations(files);
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+fn file_violations(files: &[File]) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    if files.len() > MAX_FILES {
+        violations.push(Violation::TooManyFiles {
+            count: files.len(),
+            max: MAX_FILES,
+        });
+    }
+
+    for file in files {
+        if file.content.len() > MAX_FILE_SIZE {
+            violations.push(Violation::FileTooLarge {
+                name: file.name.clone(),
+                size: file.content.len(),
+                max: MAX_FILE_SIZE,
+            });
+        }
+
+        if file.is_binary() && !base64::is_valid(&file.content) {
+            violations.push(Violation::InvalidBinaryContent {
+                name: file.name.clone(),
+            });
+        }
+
+        if let Err(error) = path::validate(&file.name) {
+            violations.push(Violation::InvalidFilename {
+                name: file.name.clone(),
+                error,
+            });
+        }
+    }
+
+    for (index, file) in files.iter().enumerate() {
+        for other in &files[index + 1..] {
+            if file.name == other.name {
+                violations.push(Violation::DuplicateFilename {
+                    name: file.name.clone(),
+                });
+            } else if path::is_directory_conflict(&file.name, &other.name) {
+                violations.push(Violation::DirectoryConflict {
+                    name: file.name.clone(),
+                    other: other.name.clone(),
+                });
+            }
+        }
+    }
+
+    let main_files: Vec<String> = files
+        .iter()
+        .filter(|file| file.main)
+        .map(|file| file.name.clone())
+        .collect();
+
+    if main_files.len() > 1 {
+        violations.push(Violation::MultipleMainFiles { names: main_files });
+    }
+
+    let total_size: usize = files.iter().map(|file| file.content.len()).sum();
+    if total_size > MAX_TOTAL_SIZE {
+        violations.push(Violation::TotalSizeTooLarge {
+            size: total_size,
+            max: MAX_TOTAL_SIZE,
+        });
+    }
+
+    violations
+}
+
+pub(crate) fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::expected_output::Comparison;
+    use crate::snippet::expected_output::ExpectedOutput;
+    use crate::snippet::StdinInput;
+    use glot_languages::language::Language;
+    use std::collections::BTreeMap;
+
+    fn snippet(files: Vec<File>) -> Snippet {
+        Snippet {
+            language: Language::Python,
+            title: "Hello".to_string(),
+            files,
+            inputs: vec![],
+            instructions: None,
+            env: BTreeMap::new(),
+            args: vec![],
+            timeout: None,
+        }
+    }
+
+    fn text_file(name: &str, content: &str) -> File {
+        File::text(name.to_string(), content.to_string())
+    }
+
+    fn input(name: &str, content: &str) -> StdinInput {
+        StdinInput {
+            name: name.to_string(),
+            content: content.to_string(),
+            expected_output: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_snippet() {
+        let snippet = snippet(vec![
+            text_file("main.py", "print(1)"),
+            text_file("lib/util.py", ""),
+            File::binary("logo.png".to_string(), &[0, 1, 2]),
+        ]);
+
+        assert_eq!(validate(&snippet), Ok(()));
+    }
+
+    #[test]
+    fn test_file_limits() {
+        let files = (0..=MAX_FILES)
+            .map(|n| text_file(&format!("{}.py", n), ""))
+            .collect();
+
+        assert_eq!(
+            validate(&snippet(files)),
+            Err(vec![Violation::TooManyFiles {
+                count: MAX_FILES + 1,
+                max: MAX_FILES
+            }])
+        );
+
+        let content = "a".repeat(MAX_FILE_SIZE + 1);
+        let violations = validate(&snippet(vec![
+            text_file("a.py", &content),
+            text_file("b.py", &content),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(
+            violations,
+            vec![
+                Violation::FileTooLarge {
+                    name: "a.py".to_string(),
+                    size: MAX_FILE_SIZE + 1,
+                    max: MAX_FILE_SIZE
+                },
+                Violation::FileTooLarge {
+                    name: "b.py".to_string(),
+                    size: MAX_FILE_SIZE + 1,
+                    max: MAX_FILE_SIZE
+                },
+                Violation::TotalSizeTooLarge {
+                    size: 2 * (MAX_FILE_SIZE + 1),
+                    max: MAX_TOTAL_SIZE
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filenames() {
+        let mut main = text_file("main.py", "");
+        main.main = true;
+        let mut other_main = text_file("other.py", "");
+        other_main.main = true;
+
+        let violations = validate(&snippet(vec![
+            main,
+            other_main,
+            text_file("main.py", ""),
+            text_file("lib", ""),
+            text_file("lib/util.py", ""),
+            text_file("../escape.py", ""),
+        ]))
+        .unwrap_err();
+
+        assert_eq!(
+            violations,
+            vec![
+                Violation::InvalidFilename {
+                    name: "../escape.py".to_string(),
+                    error: path::validate("../escape.py").unwrap_err(),
+                },
+                Violation::DuplicateFilename {
+                    name: "main.py".to_string()
+                },
+                Violation::DirectoryConflict {
+                    name: "lib".to_string(),
+                    other: "lib/util.py".to_string()
+                },
+                Violation::MultipleMainFiles {
+                    names: vec!["main.py".to_string(), "other.py".to_string()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_binary_content() {
+        let mut file = text_file("logo.png", "not base64!");
+        file.encoding = crate::snippet::FileEncoding::Base64;
+
+        assert_eq!(
+            validate(&snippet(vec![file])),
+            Err(vec![Violation::InvalidBinaryContent {
+                name: "logo.png".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn test_title() {
+        let mut long = snippet(vec![text_file("main.py", "")]);
+        long.title = "a".repeat(MAX_TITLE_LENGTH + 1);
+        assert_eq!(
+            validate(&long),
+            Err(vec![Violation::TitleTooLong {
+                length: MAX_TITLE_LENGTH + 1,
+                max: MAX_TITLE_LENGTH
+            }])
+        );
+
+        // Characters are counted, not bytes
+        let mut multibyte = snippet(vec![text_file("main.py", "")]);
+        multibyte.title = "ä".repeat(MAX_TITLE_LENGTH);
+        assert_eq!(validate(&multibyte), Ok(()));
+
+        let mut control = snippet(vec![text_file("main.py", "")]);
+        control.title = "a\nb".to_string();
+        assert_eq!(
+            validate(&control),
+            Err(vec![Violation::TitleControlCharacter])
+        );
+    }
+
+    #[test]
+    fn test_inputs() {
+        let mut snippet = snippet(vec![text_file("main.py", "")]);
+        let mut with_pattern = input("Regex", "");
+        with_pattern.expected_output = Some(ExpectedOutput {
+            stdout: "(".to_string(),
+            comparison: Comparison::Regex,
+        });
+        snippet.inputs = vec![input("A", ""), input("A", ""), with_pattern];
+
+        let violations = validate(&snippet).unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(
+            violations[0],
+            Violation::DuplicateInputName {
+                name: "A".to_string()
+            }
+        );
+        assert!(matches!(
+            &violations[1],
+            Violation::InvalidExpectedOutput { name, .. } if name == "Regex"
+        ));
+
+        snippet.inputs = vec![input("A", &"a".repeat(MAX_STDIN_SIZE + 1))];
+        assert_eq!(
+            validate(&snippet),
+            Err(vec![Violation::StdinTooLarge {
+                size: MAX_STDIN_SIZE + 1,
+                max: MAX_STDIN_SIZE
+            }])
+        );
+    }
+
+    #[test]
+    fn test_run_options() {
+        let mut snippet = snippet(vec![text_file("main.py", "")]);
+        snippet.env.insert("1ABC".to_string(), "".to_string());
+        snippet.args = vec!["a".repeat(MAX_RUN_OPTIONS_SIZE + 1)];
+        snippet.timeout = Some(0);
+
+        assert_eq!(
+            validate(&snippet),
+            Err(vec![
+                Violation::InvalidEnvName {
+                    name: "1ABC".to_string()
+                },
+                Violation::RunOptionsTooLarge {
+                    size: MAX_RUN_OPTIONS_SIZE + 5,
+                    max: MAX_RUN_OPTIONS_SIZE
+                },
+                Violation::InvalidTimeout {
+                    timeout: 0,
+                    max: MAX_TIMEOUT
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_env_name() {
+        assert!(is_valid_env_name("PATH"));
+        assert!(is_valid_env_name("_my_var2"));
+        assert!(!is_valid_env_name(""));
+        assert!(!is_valid_env_name("2X"));
+        assert!(!is_valid_env_name("MY-VAR"));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+    }
+}
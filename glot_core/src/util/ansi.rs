@@ -0,0 +1,415 @@
+// Terminal output as styled lines. SGR sequences set the style, erase in line and carriage
+// returns overwrite the current line so progress bars show their last state. Other escape
+// sequences are removed.
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+// The first 16 colors, readable on the light background of the output panel
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x31, 0x31),
+    (0x00, 0xbc, 0x00),
+    (0x94, 0x98, 0x00),
+    (0x04, 0x51, 0xa5),
+    (0xbc, 0x05, 0xbc),
+    (0x05, 0x98, 0xbc),
+    (0x55, 0x55, 0x55),
+    (0x66, 0x66, 0x66),
+    (0xcd, 0x31, 0x31),
+    (0x14, 0xce, 0x14),
+    (0xb5, 0xba, 0x00),
+    (0x04, 0x51, 0xa5),
+    (0xbc, 0x05, 0xbc),
+    (0x05, 0x98, 0xbc),
+    (0xa5, 0xa5, 0xa5),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    pub fn to_hex(&self) -> String {
+        let (r, g, b) = self.rgb();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    // Indexes above 15 are the 6x6x6 color cube followed by 24 shades of gray
+    fn rgb(&self) -> (u8, u8, u8) {
+        match *self {
+            Color::Rgb(r, g, b) => (r, g, b),
+
+            Color::Indexed(index @ 0..=15) => PALETTE[index as usize],
+
+            Color::Indexed(index @ 16..=231) => {
+                let index = index - 16;
+                (
+                    CUBE_LEVELS[(index / 36) as usize],
+                    CUBE_LEVELS[(index / 6 % 6) as usize],
+                    CUBE_LEVELS[(index % 6) as usize],
+                )
+            }
+
+            Color::Indexed(index) => {
+                let level = 8 + 10 * (index - 232);
+                (level, level, level)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+}
+
+impl Style {
+    pub fn is_plain(&self) -> bool {
+        *self == Style::default()
+    }
+
+    // Foreground and background with inverse applied, None is the default color
+    pub fn colors(&self) -> (Option<Color>, Option<Color>) {
+        if self.inverse {
+            (
+                Some(self.background.unwrap_or(Color::Rgb(0xff, 0xff, 0xff))),
+                Some(self.foreground.unwrap_or(Color::Indexed(0))),
+            )
+        } else {
+            (self.foreground, self.background)
+        }
+    }
+
+    fn apply_sgr(&mut self, params: &[u16]) {
+        let mut params = params.iter().copied();
+
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.inverse = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.inverse = false,
+                30..=37 => self.foreground = Some(Color::Indexed((param - 30) as u8)),
+                38 => self.foreground = extended_color(&mut params),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(Color::Indexed((param - 40) as u8)),
+                48 => self.background = extended_color(&mut params),
+                49 => self.background = None,
+                90..=97 => self.foreground = Some(Color::Indexed((param - 90 + 8) as u8)),
+                100..=107 => self.background = Some(Color::Indexed((param - 100 + 8) as u8)),
+                _ => {}
+            }
+        }
+    }
+}
+
+// 5;n for the 256 color palette or 2;r;g;b for true color
+fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match params.next() {
+        Some(5) => params.next().map(|index| Color::Indexed(clamp_u8(index))),
+
+        Some(2) => {
+            let r = params.next()?;
+            let g = params.next()?;
+            let b = params.next()?;
+            Some(Color::Rgb(clamp_u8(r), clamp_u8(g), clamp_u8(b)))
+        }
+
+        _ => None,
+    }
+}
+
+fn clamp_u8(n: u16) -> u8 {
+    n.min(u8::MAX as u16) as u8
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+pub type Line = Vec<Span>;
+
+// Control characters are replaced with their control pictures, i.e. ESC with ␛, so the raw
+// escape sequences can be read. Line breaks and tabs are kept.
+pub fn visible_controls(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\n' | '\r' | '\t' => c,
+            '\x00'..='\x1f' => char::from_u32(0x2400 + c as u32).unwrap_or(c),
+            '\x7f' => '\u{2421}',
+            _ => c,
+        })
+        .collect()
+}
+
+pub fn parse(text: &str) -> Vec<Line> {
+    let mut terminal = Terminal::default();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => terminal.new_line(),
+            '\r' => terminal.cursor = 0,
+            '\x08' => terminal.cursor = terminal.cursor.saturating_sub(1),
+
+            ESC => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+
+                    // Parameter and intermediate bytes, followed by the final byte
+                    let final_byte = loop {
+                        match chars.next() {
+                            Some(c @ '\x20'..='\x3f') => params.push(c),
+                            other => break other,
+                        }
+                    };
+
+                    match final_byte {
+                        Some('m') => terminal.style.apply_sgr(&parse_params(&params)),
+                        Some('K') => terminal.erase_in_line(&params),
+                        _ => {}
+                    }
+                }
+
+                // Operating system command, i.e. setting the window title
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+
+                _ => {}
+            },
+
+            c if c.is_control() && c != '\t' => {}
+
+            c => terminal.write(c),
+        }
+    }
+
+    terminal.finish()
+}
+
+fn parse_params(params: &str) -> Vec<u16> {
+    // Colon separated sub parameters are treated the same as semicolon separated parameters
+    params
+        .split([';', ':'])
+        .map(|param| param.parse().unwrap_or(0))
+        .collect()
+}
+
+#[derive(Default)]
+struct Terminal {
+    lines: Vec<Line>,
+    cells: Vec<(char, Style)>,
+    cursor: usize,
+    style: Style,
+}
+
+impl Terminal {
+    fn write(&mut self, c: char) {
+        if let Some(cell) = self.cells.get_mut(self.cursor) {
+            *cell = (c, self.style);
+        } else {
+            self.cells.push((c, self.style));
+        }
+
+        self.cursor += 1;
+    }
+
+    fn erase_in_line(&mut self, params: &str) {
+        match params {
+            "" | "0" => self.cells.truncate(self.cursor),
+
+            // Up to and including the cursor
+            "1" => {
+                let end = (self.cursor + 1).min(self.cells.len());
+
+                for cell in &mut self.cells[..end] {
+                    *cell = (' ', Style::default());
+                }
+            }
+
+            "2" => {
+                let blank_count = self.cursor.min(self.cells.len());
+                self.cells.clear();
+                self.cells.resize(blank_count, (' ', Style::default()));
+            }
+
+            _ => {}
+        }
+    }
+
+    fn new_line(&mut self) {
+        let cells = std::mem::take(&mut self.cells);
+        self.lines.push(to_spans(cells));
+        self.cursor = 0;
+    }
+
+    fn finish(mut self) -> Vec<Line> {
+        if !self.cells.is_empty() {
+            self.new_line();
+        }
+
+        self.lines
+    }
+}
+
+// Neighbouring cells with the same style are merged
+fn to_spans(cells: Vec<(char, Style)>) -> Line {
+    let mut spans: Vec<Span> = vec![];
+
+    for (c, style) in cells {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.text.push(c),
+            _ => spans.push(Span {
+                text: c.to_string(),
+                style,
+            }),
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(text: &str) -> Span {
+        Span {
+            text: text.to_string(),
+            style: Style::default(),
+        }
+    }
+
+    fn texts(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.iter().map(|span| span.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(parse("a\nb\n"), vec![vec![plain("a")], vec![plain("b")]]);
+        assert_eq!(
+            parse("a\n\nb"),
+            vec![vec![plain("a")], vec![], vec![plain("b")]]
+        );
+        assert_eq!(parse(""), Vec::<Line>::new());
+    }
+
+    #[test]
+    fn test_colors() {
+        let lines = parse("\x1b[1;31merror\x1b[0m: done");
+        let red = Style {
+            foreground: Some(Color::Indexed(1)),
+            bold: true,
+            ..Style::default()
+        };
+
+        assert_eq!(
+            lines,
+            vec![vec![
+                Span {
+                    text: "error".to_string(),
+                    style: red
+                },
+                plain(": done"),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_extended_colors() {
+        let mut style = Style::default();
+        style.apply_sgr(&[38, 5, 196, 48, 2, 10, 20, 300]);
+
+        assert_eq!(style.foreground, Some(Color::Indexed(196)));
+        assert_eq!(style.background, Some(Color::Rgb(10, 20, 255)));
+
+        style.apply_sgr(&[39, 49]);
+        assert!(style.is_plain());
+    }
+
+    #[test]
+    fn test_color_to_hex() {
+        assert_eq!(Color::Indexed(1).to_hex(), "#cd3131");
+        assert_eq!(Color::Indexed(16).to_hex(), "#000000");
+        assert_eq!(Color::Indexed(231).to_hex(), "#ffffff");
+        assert_eq!(Color::Indexed(232).to_hex(), "#080808");
+        assert_eq!(Color::Indexed(255).to_hex(), "#eeeeee");
+        assert_eq!(Color::Rgb(1, 2, 3).to_hex(), "#010203");
+    }
+
+    #[test]
+    fn test_inverse() {
+        let style = Style {
+            foreground: Some(Color::Indexed(2)),
+            inverse: true,
+            ..Style::default()
+        };
+
+        assert_eq!(
+            style.colors(),
+            (Some(Color::Rgb(0xff, 0xff, 0xff)), Some(Color::Indexed(2)))
+        );
+    }
+
+    #[test]
+    fn test_carriage_return() {
+        // Progress bars redraw the line, only the last state is kept
+        let lines = parse("10%\r50%\r100%\ndone");
+        assert_eq!(texts(&lines), vec!["100%", "done"]);
+
+        let lines = parse("abcdef\rxy");
+        assert_eq!(texts(&lines), vec!["xycdef"]);
+
+        // Windows line endings
+        let lines = parse("a\r\nb\r\n");
+        assert_eq!(texts(&lines), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_erase_in_line() {
+        assert_eq!(texts(&parse("abcdef\r\x1b[Kxy")), vec!["xy"]);
+        assert_eq!(texts(&parse("abc\x08\x08\x1b[1K")), vec!["  c"]);
+        assert_eq!(texts(&parse("abc\x08\x1b[2K")), vec!["  "]);
+    }
+
+    #[test]
+    fn test_other_sequences_are_removed() {
+        let lines = parse("\x1b]0;title\x07a\x1b]0;title\x1b\\b\x1b[?25lc\x00");
+        assert_eq!(texts(&lines), vec!["abc"]);
+    }
+
+    #[test]
+    fn test_visible_controls() {
+        assert_eq!(visible_controls("\x1b[31mred\n\t\x7f"), "␛[31mred\n\t␡");
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+// Standard alphabet with padding, as described in RFC 4648
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PADDING: u8 = b'=';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidLength(usize),
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidLength(length) => write!(f, "Invalid length: {}", length),
+            DecodeError::InvalidCharacter(c) => write!(f, "Invalid character: {:?}", c),
+        }
+    }
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+
+        let indexes = [
+            b[0] >> 2,
+            ((b[0] & 0x03) << 4) | (b[1] >> 4),
+            ((b[1] & 0x0f) << 2) | (b[2] >> 6),
+            b[2] & 0x3f,
+        ];
+
+        for (i, index) in indexes.iter().enumerate() {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[*index as usize] as char);
+            } else {
+                encoded.push(PADDING as char);
+            }
+        }
+    }
+
+    encoded
+}
+
+// Whitespace is ignored so that line wrapped input can be decoded
+pub fn decode(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let input: Vec<u8> = encoded
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+
+    let chunks = input.chunks_exact(4);
+
+    if !chunks.remainder().is_empty() {
+        return Err(DecodeError::InvalidLength(input.len()));
+    }
+
+    let chunk_count = chunks.len();
+    let mut bytes = Vec::with_capacity(chunk_count * 3);
+
+    for (chunk_index, chunk) in chunks.enumerate() {
+        let is_last = chunk_index + 1 == chunk_count;
+        let padding = chunk
+            .iter()
+            .rev()
+            .take_while(|byte| **byte == PADDING)
+            .count();
+
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err(DecodeError::InvalidCharacter(PADDING as char));
+        }
+
+        let mut values = [0u8; 4];
+        for (value, byte) in values.iter_mut().zip(&chunk[..4 - padding]) {
+            *value = decode_byte(*byte)?;
+        }
+
+        let decoded = [
+            (values[0] << 2) | (values[1] >> 4),
+            (values[1] << 4) | (values[2] >> 2),
+            (values[2] << 6) | values[3],
+        ];
+
+        bytes.extend_from_slice(&decoded[..3 - padding]);
+    }
+
+    Ok(bytes)
+}
+
+pub fn is_valid(encoded: &str) -> bool {
+    decode(encoded).is_ok()
+}
+
+fn decode_byte(byte: u8) -> Result<u8, DecodeError> {
+    match byte {
+        b'A'..=b'Z' => Ok(byte - b'A'),
+        b'a'..=b'z' => Ok(byte - b'a' + 26),
+        b'0'..=b'9' => Ok(byte - b'0' + 52),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(DecodeError::InvalidCharacter(byte as char)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from RFC 4648
+    const VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn test_encode() {
+        for (decoded, encoded) in VECTORS {
+            assert_eq!(encode(decoded.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        for (decoded, encoded) in VECTORS {
+            assert_eq!(decode(encoded), Ok(decoded.as_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(&encode(&bytes)), Ok(bytes));
+    }
+
+    #[test]
+    fn test_decode_ignores_whitespace() {
+        assert_eq!(decode("Zm9v\nYmFy\r\n"), Ok(b"foobar".to_vec()));
+        assert_eq!(decode(" Zm 9v "), Ok(b"foo".to_vec()));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode("Zm9"), Err(DecodeError::InvalidLength(3)));
+        assert_eq!(decode("Zm9!"), Err(DecodeError::InvalidCharacter('!')));
+        assert_eq!(decode("Zm-_"), Err(DecodeError::InvalidCharacter('-')));
+        assert_eq!(decode("Z==="), Err(DecodeError::InvalidCharacter('=')));
+        assert_eq!(decode("Zg==Zm9v"), Err(DecodeError::InvalidCharacter('=')));
+        assert_eq!(decode("Z=g="), Err(DecodeError::InvalidCharacter('=')));
+        assert!(!is_valid("not base64"));
+        assert!(is_valid("Zm9vYmFy"));
+    }
+}
@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+// Bounds the memory used by the diff, texts that differ more than this are shown as fully replaced
+const MAX_EDIT_DISTANCE: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Line {
+    Context(String),
+    Added(String),
+    Removed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    pub lines: Vec<Line>,
+}
+
+impl fmt::Display for Hunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_count, self.new_start, self.new_count
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+pub fn unified(old: &str, new: &str) -> Vec<Hunk> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let edits =
+        myers(&old_lines, &new_lines).unwrap_or_else(|| replace_all(&old_lines, &new_lines));
+
+    to_hunks(&edits, &old_lines, &new_lines)
+}
+
+fn replace_all(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let deletes = (0..old.len()).map(Edit::Delete);
+    let inserts = (0..new.len()).map(Edit::Insert);
+    deletes.chain(inserts).collect()
+}
+
+// Myers' O(ND) algorithm, keeps the frontier of each step for backtracking
+fn myers(old: &[&str], new: &[&str]) -> Option<Vec<Edit>> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = vec![];
+
+    for d in 0..=max.min(MAX_EDIT_DISTANCE) as isize {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let index = (offset + k) as usize;
+
+            let mut x = if k == -d || (k != d && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+
+            let mut y = x - k;
+
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[index] = x;
+
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<Edit> {
+    let mut edits = vec![];
+    let mut x = n;
+    let mut y = m;
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+
+        if d == 0 {
+            while x > 0 && y > 0 {
+                edits.push(Edit::Equal(x as usize - 1, y as usize - 1));
+                x -= 1;
+                y -= 1;
+            }
+
+            break;
+        }
+
+        // The frontier of step d covers diagonals -d..=d
+        let get = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal(x as usize - 1, y as usize - 1));
+            x -= 1;
+            y -= 1;
+        }
+
+        if x == prev_x {
+            edits.push(Edit::Insert(y as usize - 1));
+        } else {
+            edits.push(Edit::Delete(x as usize - 1));
+        }
+
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    edits
+}
+
+fn to_hunks(edits: &[Edit], old: &[&str], new: &[&str]) -> Vec<Hunk> {
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Equal(_, _)))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Group changes that are close enough to share context lines
+    let mut groups: Vec<(usize, usize)> = vec![];
+
+    for index in changes {
+        match groups.last_mut() {
+            Some((_, end)) if index - *end <= CONTEXT_LINES * 2 => *end = index,
+            _ => groups.push((index, index)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(first, last)| {
+            let start = first.saturating_sub(CONTEXT_LINES);
+            let end = (last + CONTEXT_LINES + 1).min(edits.len());
+            to_hunk(&edits[start..end], old, new)
+        })
+        .collect()
+}
+
+fn to_hunk(edits: &[Edit], old: &[&str], new: &[&str]) -> Hunk {
+    let (old_start, new_start) = start_position(edits);
+    let mut hunk = Hunk {
+        old_start,
+        old_count: 0,
+        new_start,
+        new_count: 0,
+        lines: vec![],
+    };
+
+    for edit in edits {
+        match *edit {
+            Edit::Equal(old_index, _) => {
+                hunk.old_count += 1;
+                hunk.new_count += 1;
+                hunk.lines.push(Line::Context(old[old_index].to_string()));
+            }
+
+            Edit::Delete(old_index) => {
+                hunk.old_count += 1;
+                hunk.lines.push(Line::Removed(old[old_index].to_string()));
+            }
+
+            Edit::Insert(new_index) => {
+                hunk.new_count += 1;
+                hunk.lines.push(Line::Added(new[new_index].to_string()));
+            }
+        }
+    }
+
+    // Unified diff uses the line before the hunk as start when a side is empty
+    if hunk.old_count == 0 {
+        hunk.old_start -= 1;
+    }
+
+    if hunk.new_count == 0 {
+        hunk.new_start -= 1;
+    }
+
+    hunk
+}
+
+// 1-based line numbers of the first line on each side of the hunk
+fn start_position(edits: &[Edit]) -> (usize, usize) {
+    let old_start = edits.iter().find_map(|edit| match edit {
+        Edit::Equal(old_index, _) | Edit::Delete(old_index) => Some(*old_index),
+        Edit::Insert(_) => None,
+    });
+
+    let new_start = edits.iter().find_map(|edit| match edit {
+        Edit::Equal(_, new_index) | Edit::Insert(new_index) => Some(*new_index),
+        Edit::Delete(_) => None,
+    });
+
+    // Hunks with only inserts or deletes start where the other side's edits would be
+    let fallback = |edit: &Edit| match *edit {
+        Edit::Delete(old_index) => (old_index, 0),
+        Edit::Insert(new_index) => (0, new_index),
+        Edit::Equal(old_index, new_index) => (old_index, new_index),
+    };
+
+    let (old_fallback, new_fallback) = edits.first().map(fallback).unwrap_or((0, 0));
+
+    (
+        old_start.unwrap_or(old_fallback) + 1,
+        new_start.unwrap_or(new_fallback) + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(s: &str) -> Line {
+        Line::Context(s.to_string())
+    }
+
+    fn added(s: &str) -> Line {
+        Line::Added(s.to_string())
+    }
+
+    fn removed(s: &str) -> Line {
+        Line::Removed(s.to_string())
+    }
+
+    fn numbered_lines(range: std::ops::Range<usize>) -> String {
+        range.map(|n| format!("{}\n", n)).collect()
+    }
+
+    // Replaces the given 0-based lines
+    fn change_lines(text: &str, changes: &[(usize, &str)]) -> String {
+        text.lines()
+            .enumerate()
+            .map(|(index, line)| {
+                let changed = changes.iter().find(|(n, _)| *n == index);
+                format!("{}\n", changed.map(|(_, line)| *line).unwrap_or(line))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_identical() {
+        assert_eq!(unified("", ""), vec![]);
+        assert_eq!(unified("a\nb\n", "a\nb\n"), vec![]);
+    }
+
+    #[test]
+    fn test_changed_line() {
+        let hunks = unified("a\nb\nc\n", "a\nx\nc\n");
+
+        assert_eq!(
+            hunks,
+            vec![Hunk {
+                old_start: 1,
+                old_count: 3,
+                new_start: 1,
+                new_count: 3,
+                lines: vec![context("a"), removed("b"), added("x"), context("c")],
+            }]
+        );
+        assert_eq!(hunks[0].to_string(), "@@ -1,3 +1,3 @@");
+    }
+
+    #[test]
+    fn test_added_and_removed_files() {
+        assert_eq!(
+            unified("", "a\nb\n"),
+            vec![Hunk {
+                old_start: 0,
+                old_count: 0,
+                new_start: 1,
+                new_count: 2,
+                lines: vec![added("a"), added("b")],
+            }]
+        );
+
+        assert_eq!(
+            unified("a\n", ""),
+            vec![Hunk {
+                old_start: 1,
+                old_count: 1,
+                new_start: 0,
+                new_count: 0,
+                lines: vec![removed("a")],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_context_lines() {
+        let old = numbered_lines(0..20);
+        let new = change_lines(&old, &[(2, "two"), (17, "seventeen")]);
+
+        let hunks = unified(&old, &new);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].to_string(), "@@ -1,6 +1,6 @@");
+        assert_eq!(hunks[1].to_string(), "@@ -15,6 +15,6 @@");
+        assert_eq!(hunks[1].lines[3], removed("17"));
+        assert_eq!(hunks[1].lines[4], added("seventeen"));
+
+        // Changes with less than twice the context between them share a hunk
+        let new = change_lines(&old, &[(2, "two"), (8, "eight")]);
+        assert_eq!(unified(&old, &new).len(), 1);
+    }
+
+    #[test]
+    fn test_insert_in_middle() {
+        let hunks = unified("a\nb\nc\nd\ne\n", "a\nb\nc\nx\nd\ne\n");
+
+        assert_eq!(hunks[0].to_string(), "@@ -1,5 +1,6 @@");
+        assert_eq!(hunks[0].lines[3], added("x"));
+    }
+
+    #[test]
+    fn test_large_difference() {
+        // Beyond the edit distance limit everything is shown as replaced
+        let old = numbered_lines(0..MAX_EDIT_DISTANCE);
+        let new = numbered_lines(MAX_EDIT_DISTANCE..2 * MAX_EDIT_DISTANCE);
+
+        let hunks = unified(&old, &new);
+
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].old_count, MAX_EDIT_DISTANCE);
+        assert_eq!(hunks[0].new_count, MAX_EDIT_DISTANCE);
+        assert_eq!(hunks[0].lines.len(), 2 * MAX_EDIT_DISTANCE);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod ansi;
+pub mod base64;
+pub mod diff;
+pub mod regex;
 pub mod remote_data;
 pub mod select_list;
+pub mod sha256;
 pub mod user_agent;
@@ -0,0 +1,692 @@
+use std::fmt;
+
+// Limits the size of the compiled program, counted repetitions are expanded
+const MAX_PROGRAM_SIZE: usize = 10_000;
+const MAX_REPETITION: u32 = 1000;
+
+// Groups and repetitions are parsed and compiled recursively, deeper nesting could overflow the stack
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnexpectedEnd,
+    UnbalancedParenthesis,
+    NothingToRepeat,
+    InvalidRepetition,
+    InvalidClass,
+    UnsupportedEscape(char),
+    TooLarge,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnexpectedEnd => write!(f, "Unexpected end of pattern"),
+            Error::UnbalancedParenthesis => write!(f, "Unbalanced parenthesis"),
+            Error::NothingToRepeat => write!(f, "Nothing to repeat"),
+            Error::InvalidRepetition => write!(f, "Invalid repetition"),
+            Error::InvalidClass => write!(f, "Invalid character class"),
+            Error::UnsupportedEscape(c) => write!(f, "Unsupported escape: \\{}", c),
+            Error::TooLarge => write!(f, "Pattern is too large"),
+        }
+    }
+}
+
+// A small regular expression engine supporting the common syntax: literals, '.', classes,
+// \d \w \s, anchors, groups, alternation and repetition. Matching is done with a pike vm,
+// which runs in linear time so user supplied patterns can't hang the page.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    program: Vec<Inst>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, Error> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+
+        let node = parser.parse_alternation()?;
+
+        if parser.pos < parser.chars.len() {
+            return Err(Error::UnbalancedParenthesis);
+        }
+
+        let mut compiler = Compiler { program: vec![] };
+        compiler.compile(&node)?;
+        compiler.push(Inst::Match)?;
+
+        Ok(Regex {
+            program: compiler.program,
+        })
+    }
+
+    // True if the pattern matches the whole text
+    pub fn is_full_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let mut current = vec![];
+        let mut visited = vec![usize::MAX; self.program.len()];
+
+        self.add_thread(&mut current, &mut visited, 0, 0, chars.len());
+
+        for (index, c) in chars.iter().enumerate() {
+            let mut next = vec![];
+
+            for pc in &current {
+                let matches = match &self.program[*pc] {
+                    Inst::Char(expected) => expected == c,
+                    Inst::Any => *c != '\n',
+                    Inst::Class(class) => class.matches(*c),
+                    _ => false,
+                };
+
+                if matches {
+                    self.add_thread(&mut next, &mut visited, pc + 1, index + 1, chars.len());
+                }
+            }
+
+            if next.is_empty() {
+                return false;
+            }
+
+            current = next;
+        }
+
+        current
+            .iter()
+            .any(|pc| matches!(self.program[*pc], Inst::Match))
+    }
+
+    // Follows jumps and assertions, only instructions that consume a char or match are added
+    fn add_thread(
+        &self,
+        list: &mut Vec<usize>,
+        visited: &mut [usize],
+        pc: usize,
+        position: usize,
+        length: usize,
+    ) {
+        let mut stack = vec![pc];
+
+        while let Some(pc) = stack.pop() {
+            if visited[pc] == position {
+                continue;
+            }
+
+            visited[pc] = position;
+
+            match &self.program[pc] {
+                Inst::Jmp(target) => stack.push(*target),
+
+                Inst::Split(first, second) => {
+                    stack.push(*second);
+                    stack.push(*first);
+                }
+
+                Inst::Start => {
+                    if position == 0 {
+                        stack.push(pc + 1);
+                    }
+                }
+
+                Inst::End => {
+                    if position == length {
+                        stack.push(pc + 1);
+                    }
+                }
+
+                Inst::Char(_) | Inst::Any | Inst::Class(_) | Inst::Match => list.push(pc),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl Class {
+    fn new(ranges: &[(char, char)], negated: bool) -> Class {
+        Class {
+            ranges: ranges.to_vec(),
+            negated,
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        let in_ranges = self
+            .ranges
+            .iter()
+            .any(|(start, end)| *start <= c && c <= *end);
+
+        in_ranges != self.negated
+    }
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn enter(&mut self) -> Result<(), Error> {
+        self.depth += 1;
+
+        if self.depth > MAX_NESTING {
+            Err(Error::TooLarge)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<char, Error> {
+        let c = self.peek().ok_or(Error::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, Error> {
+        let mut alternatives = vec![self.parse_concat()?];
+
+        while self.eat('|') {
+            alternatives.push(self.parse_concat()?);
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives.remove(0))
+        } else {
+            Ok(Node::Alternate(alternatives))
+        }
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, Error> {
+        let mut nodes = vec![];
+
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+
+            nodes.push(self.parse_repeat()?);
+        }
+
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.remove(0)),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn parse_repeat(&mut self) -> Result<Node, Error> {
+        let depth = self.depth;
+        let mut node = self.parse_atom()?;
+
+        loop {
+            let (min, max) = if self.eat('*') {
+                (0, None)
+            } else if self.eat('+') {
+                (1, None)
+            } else if self.eat('?') {
+                (0, Some(1))
+            } else if self.eat('{') {
+                self.parse_counted()?
+            } else {
+                self.depth = depth;
+                return Ok(node);
+            };
+
+            // Lazy quantifiers give the same result when only checking for a match
+            self.eat('?');
+
+            // Stacked quantifiers like a** nest the same way as groups
+            self.enter()?;
+
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+            };
+        }
+    }
+
+    // Parses n}, n,} or n,m} after an opening brace
+    fn parse_counted(&mut self) -> Result<(u32, Option<u32>), Error> {
+        let min = self.parse_number()?.ok_or(Error::InvalidRepetition)?;
+
+        let max = if self.eat(',') {
+            self.parse_number()?
+        } else {
+            Some(min)
+        };
+
+        if !self.eat('}') {
+            return Err(Error::InvalidRepetition);
+        }
+
+        match max {
+            Some(max) if max < min => Err(Error::InvalidRepetition),
+            _ if min > MAX_REPETITION || max.unwrap_or(0) > MAX_REPETITION => Err(Error::TooLarge),
+            _ => Ok((min, max)),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Option<u32>, Error> {
+        let start = self.pos;
+
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Ok(None);
+        }
+
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().map(Some).map_err(|_| Error::TooLarge)
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, Error> {
+        match self.next()? {
+            '(' => {
+                // Non-capturing groups behave the same, nothing is captured
+                if self.peek() == Some('?') {
+                    self.pos += 1;
+
+                    if !self.eat(':') {
+                        return Err(Error::UnsupportedEscape('?'));
+                    }
+                }
+
+                self.enter()?;
+                let node = self.parse_alternation()?;
+                self.depth -= 1;
+
+                if self.eat(')') {
+                    Ok(node)
+                } else {
+                    Err(Error::UnbalancedParenthesis)
+                }
+            }
+
+            '[' => self.parse_class(),
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '\\' => self.parse_escape().map(|escape| match escape {
+                Escape::Char(c) => Node::Char(c),
+                Escape::Class(class) => Node::Class(class),
+            }),
+            '*' | '+' | '?' | '{' => Err(Error::NothingToRepeat),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<Escape, Error> {
+        match self.next()? {
+            'd' => Ok(Escape::Class(Class::new(DIGIT, false))),
+            'D' => Ok(Escape::Class(Class::new(DIGIT, true))),
+            'w' => Ok(Escape::Class(Class::new(WORD, false))),
+            'W' => Ok(Escape::Class(Class::new(WORD, true))),
+            's' => Ok(Escape::Class(Class::new(SPACE, false))),
+            'S' => Ok(Escape::Class(Class::new(SPACE, true))),
+            'n' => Ok(Escape::Char('\n')),
+            't' => Ok(Escape::Char('\t')),
+            'r' => Ok(Escape::Char('\r')),
+            c if c.is_alphanumeric() => Err(Error::UnsupportedEscape(c)),
+            c => Ok(Escape::Char(c)),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node, Error> {
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        let mut negated_classes = vec![];
+        let mut is_first = true;
+
+        loop {
+            let c = self.next()?;
+
+            if c == ']' && !is_first {
+                break;
+            }
+
+            is_first = false;
+
+            let start = if c == '\\' {
+                match self.parse_escape()? {
+                    Escape::Char(c) => c,
+                    Escape::Class(class) if class.negated => {
+                        negated_classes.push(class);
+                        continue;
+                    }
+                    Escape::Class(class) => {
+                        ranges.extend(class.ranges);
+                        continue;
+                    }
+                }
+            } else {
+                c
+            };
+
+            let is_range = self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']');
+
+            if is_range {
+                self.pos += 1;
+
+                let end = match self.next()? {
+                    '\\' => match self.parse_escape()? {
+                        Escape::Char(c) => c,
+                        Escape::Class(_) => return Err(Error::InvalidClass),
+                    },
+                    c => c,
+                };
+
+                if end < start {
+                    return Err(Error::InvalidClass);
+                }
+
+                ranges.push((start, end));
+            } else {
+                ranges.push((start, start));
+            }
+        }
+
+        let class = Class::new(&ranges, false);
+
+        if negated_classes.is_empty() {
+            Ok(Node::Class(Class { negated, ..class }))
+        } else {
+            // Classes like [\D_] are a union of a positive and negated classes
+            let alternatives = std::iter::once(class)
+                .chain(negated_classes)
+                .map(Node::Class)
+                .collect();
+
+            if negated {
+                Err(Error::InvalidClass)
+            } else {
+                Ok(Node::Alternate(alternatives))
+            }
+        }
+    }
+}
+
+enum Escape {
+    Char(char),
+    Class(Class),
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    Split(usize, usize),
+    Jmp(usize),
+    Match,
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> Result<usize, Error> {
+        if self.program.len() >= MAX_PROGRAM_SIZE {
+            return Err(Error::TooLarge);
+        }
+
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+
+    fn compile(&mut self, node: &Node) -> Result<(), Error> {
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => {
+                self.push(Inst::Char(*c))?;
+            }
+            Node::Any => {
+                self.push(Inst::Any)?;
+            }
+            Node::Class(class) => {
+                self.push(Inst::Class(class.clone()))?;
+            }
+            Node::Start => {
+                self.push(Inst::Start)?;
+            }
+            Node::End => {
+                self.push(Inst::End)?;
+            }
+
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node)?;
+                }
+            }
+
+            Node::Alternate(nodes) => {
+                let mut jumps = vec![];
+
+                for (index, node) in nodes.iter().enumerate() {
+                    if index + 1 < nodes.len() {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.compile(node)?;
+                        jumps.push(self.push(Inst::Jmp(0))?);
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    } else {
+                        self.compile(node)?;
+                    }
+                }
+
+                let end = self.program.len();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jmp(end);
+                }
+            }
+
+            Node::Repeat { node, min, max } => {
+                for _ in 0..*min {
+                    self.compile(node)?;
+                }
+
+                match max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.compile(node)?;
+                        self.push(Inst::Jmp(split))?;
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+
+                    Some(max) => {
+                        let mut splits = vec![];
+
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0))?);
+                            self.compile(node)?;
+                        }
+
+                        let end = self.program.len();
+                        for split in splits {
+                            self.program[split] = Inst::Split(split + 1, end);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern)
+            .expect("Invalid pattern")
+            .is_full_match(text)
+    }
+
+    #[test]
+    fn test_literal() {
+        assert!(is_match("hello", "hello"));
+        assert!(!is_match("hello", "hello world"));
+        assert!(!is_match("hello", "hell"));
+        assert!(is_match(r"a\.b", "a.b"));
+        assert!(!is_match(r"a\.b", "axb"));
+        assert!(is_match("", ""));
+    }
+
+    #[test]
+    fn test_any() {
+        assert!(is_match("a.c", "abc"));
+        assert!(!is_match("a.c", "a\nc"));
+    }
+
+    #[test]
+    fn test_class() {
+        assert!(is_match("[abc]+", "cab"));
+        assert!(!is_match("[abc]+", "abd"));
+        assert!(is_match("[a-z0-9]+", "abc123"));
+        assert!(is_match("[^0-9]+", "abc"));
+        assert!(!is_match("[^0-9]+", "ab1"));
+        assert!(is_match("[]a]+", "]a"));
+        assert!(is_match("[a-]+", "a-a"));
+        assert!(is_match(r"[\d_]+", "1_2"));
+        assert!(is_match(r"[\D_]", "x"));
+        assert!(!is_match(r"[\D_]", "1"));
+    }
+
+    #[test]
+    fn test_escape_class() {
+        assert!(is_match(r"\d+", "2024"));
+        assert!(!is_match(r"\d+", "20x4"));
+        assert!(is_match(r"\w+", "snake_case1"));
+        assert!(is_match(r"\s*x\s*", " \t x \n"));
+        assert!(is_match(r"\S\W\D", "a-b"));
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert!(is_match("^abc$", "abc"));
+        assert!(!is_match("a^", "a"));
+        assert!(!is_match("$a", "a"));
+    }
+
+    #[test]
+    fn test_repetition() {
+        assert!(is_match("a*", ""));
+        assert!(is_match("a*", "aaaa"));
+        assert!(!is_match("a+", ""));
+        assert!(is_match("a+", "aaa"));
+        assert!(is_match("ab?c", "ac"));
+        assert!(is_match("ab?c", "abc"));
+        assert!(!is_match("ab?c", "abbc"));
+        assert!(is_match("a{3}", "aaa"));
+        assert!(!is_match("a{3}", "aa"));
+        assert!(is_match("a{2,}", "aaaaa"));
+        assert!(!is_match("a{2,}", "a"));
+        assert!(is_match("a{1,2}", "aa"));
+        assert!(!is_match("a{1,2}", "aaa"));
+        assert!(is_match("a+?b", "aab"));
+    }
+
+    #[test]
+    fn test_alternation() {
+        assert!(is_match("cat|dog", "cat"));
+        assert!(is_match("cat|dog", "dog"));
+        assert!(!is_match("cat|dog", "cow"));
+        assert!(is_match("(a|bc)+", "abcbca"));
+        assert!(is_match("(?:x|y)z", "yz"));
+        assert!(is_match("a|", ""));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert_eq!(Regex::new("(a").unwrap_err(), Error::UnbalancedParenthesis);
+        assert_eq!(Regex::new("a)").unwrap_err(), Error::UnbalancedParenthesis);
+        assert_eq!(Regex::new("*a").unwrap_err(), Error::NothingToRepeat);
+        assert_eq!(Regex::new("a{2,1}").unwrap_err(), Error::InvalidRepetition);
+        assert_eq!(Regex::new("[z-a]").unwrap_err(), Error::InvalidClass);
+        assert_eq!(
+            Regex::new(r"\b").unwrap_err(),
+            Error::UnsupportedEscape('b')
+        );
+        assert_eq!(Regex::new("[abc").unwrap_err(), Error::UnexpectedEnd);
+        assert_eq!(Regex::new("a{1001}").unwrap_err(), Error::TooLarge);
+        assert_eq!(Regex::new("(a{1000}){1000}").unwrap_err(), Error::TooLarge);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+
+        assert!(Regex::new(&nested(MAX_NESTING)).is_ok());
+        assert_eq!(
+            Regex::new(&nested(MAX_NESTING + 1)).unwrap_err(),
+            Error::TooLarge
+        );
+        assert_eq!(Regex::new(&nested(2000)).unwrap_err(), Error::TooLarge);
+
+        let stacked = format!("a{}", "?".repeat(2000));
+        assert_eq!(Regex::new(&stacked).unwrap_err(), Error::TooLarge);
+
+        // Sibling groups don't count towards the nesting
+        assert!(Regex::new(&"(a)".repeat(1000)).is_ok());
+    }
+
+    #[test]
+    fn test_pathological_backtracking() {
+        // Exponential for a backtracking engine, linear for the pike vm
+        let text = "a".repeat(10_000);
+        assert!(!is_match("(a*)*b", &text));
+        assert!(!is_match("(a|aa)+b", &text));
+        assert!(is_match("(a|aa)+", &text));
+
+        let pattern = format!("{}{}", "a?".repeat(30), "a".repeat(30));
+        assert!(is_match(&pattern, &"a".repeat(30)));
+    }
+}
@@ -10,3 +10,12 @@ pub enum RemoteData<E, A> {
     Failure(E),
     Success(A),
 }
+
+impl<E, A> RemoteData<E, A> {
+    pub fn success(&self) -> Option<&A> {
+        match self {
+            RemoteData::Success(a) => Some(a),
+            _ => None,
+        }
+    }
+}
@@ -110,8 +110,165 @@ where
         self.after.push(item);
     }
 
+    pub fn selected_index(&self) -> usize {
+        self.before.len()
+    }
+
+    // Moves the item at `from` to `to`, the selected item stays selected
+    pub fn move_item(&mut self, from: usize, to: usize) {
+        let mut items = self.to_vec();
+
+        if from >= items.len() || from == to {
+            return;
+        }
+
+        let selected_index = self.selected_index();
+        let item = items.remove(from);
+        let capped_to = min(to, items.len());
+        items.insert(capped_to, item);
+
+        let new_selected_index = if selected_index == from {
+            capped_to
+        } else {
+            let index = if from < selected_index {
+                selected_index - 1
+            } else {
+                selected_index
+            };
+
+            if capped_to <= index {
+                index + 1
+            } else {
+                index
+            }
+        };
+
+        if let Some(zip_list) = Self::from_vec(items) {
+            *self = zip_list;
+            self.select_index(new_selected_index);
+        }
+    }
+
+    pub fn move_selected_left(&mut self) {
+        let index = self.selected_index();
+
+        if index > 0 {
+            self.move_item(index, index - 1);
+        }
+    }
+
+    pub fn move_selected_right(&mut self) {
+        let index = self.selected_index();
+        self.move_item(index, index + 1);
+    }
+
     pub fn select_last(&mut self) {
         let count = self.to_vec().len();
         self.select_index(count - 1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The letters a to e with the given index selected
+    fn letters(selected: usize) -> SelectList<char> {
+        let mut list = SelectList::from_vec(vec!['a', 'b', 'c', 'd', 'e']).unwrap();
+        list.select_index(selected);
+        list
+    }
+
+    fn items(list: &SelectList<char>) -> String {
+        list.to_vec().into_iter().collect()
+    }
+
+    #[test]
+    fn test_select_index() {
+        let mut list = letters(2);
+        assert_eq!(list.selected(), 'c');
+        assert_eq!(list.selected_index(), 2);
+
+        list.select_index(10);
+        assert_eq!(list.selected(), 'e');
+        assert_eq!(items(&list), "abcde");
+    }
+
+    #[test]
+    fn test_move_selected_item() {
+        let mut list = letters(1);
+        list.move_item(1, 3);
+        assert_eq!(items(&list), "acdbe");
+        assert_eq!(list.selected(), 'b');
+
+        list.move_item(3, 0);
+        assert_eq!(items(&list), "bacde");
+        assert_eq!(list.selected_index(), 0);
+    }
+
+    #[test]
+    fn test_move_other_item() {
+        // From before the selected item to after it
+        let mut list = letters(2);
+        list.move_item(0, 4);
+        assert_eq!(items(&list), "bcdea");
+        assert_eq!(list.selected(), 'c');
+
+        // From after the selected item to before it
+        let mut list = letters(2);
+        list.move_item(4, 0);
+        assert_eq!(items(&list), "eabcd");
+        assert_eq!(list.selected(), 'c');
+
+        // Onto the selected item's position
+        let mut list = letters(2);
+        list.move_item(3, 2);
+        assert_eq!(items(&list), "abdce");
+        assert_eq!(list.selected(), 'c');
+
+        // Both on the same side of the selected item
+        let mut list = letters(2);
+        list.move_item(3, 4);
+        assert_eq!(items(&list), "abced");
+        assert_eq!(list.selected(), 'c');
+    }
+
+    #[test]
+    fn test_move_item_noop_and_out_of_range() {
+        let mut list = letters(2);
+
+        list.move_item(1, 1);
+        assert_eq!(items(&list), "abcde");
+        assert_eq!(list.selected(), 'c');
+
+        list.move_item(5, 0);
+        assert_eq!(items(&list), "abcde");
+        assert_eq!(list.selected(), 'c');
+
+        // A target past the end moves the item last
+        list.move_item(0, 100);
+        assert_eq!(items(&list), "bcdea");
+        assert_eq!(list.selected(), 'c');
+    }
+
+    #[test]
+    fn test_move_selected_left_right() {
+        let mut list = letters(0);
+
+        list.move_selected_left();
+        assert_eq!(items(&list), "abcde");
+
+        list.move_selected_right();
+        assert_eq!(items(&list), "bacde");
+        assert_eq!(list.selected_index(), 1);
+
+        list.select_last();
+        list.move_selected_right();
+        assert_eq!(items(&list), "bacde");
+        assert_eq!(list.selected(), 'e');
+
+        list.move_selected_left();
+        assert_eq!(items(&list), "baced");
+        assert_eq!(list.selected_index(), 3);
+    }
+}
@@ -0,0 +1,21 @@
+use glot_core::common::browser_context::JsBrowserContext;
+use glot_core::page::diff_page;
+use poly::page::wasm;
+use poly::page::Page;
+use poly_macro::impl_wasm_page;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct DiffPage(diff_page::DiffPage);
+
+impl_wasm_page!(DiffPage);
+
+#[wasm_bindgen(js_name = diffPage)]
+pub fn new(js_browser_ctx: JsValue) -> Result<DiffPage, JsValue> {
+    let browser_ctx: JsBrowserContext = wasm::decode_js_value(js_browser_ctx)
+        .map_err(|err| format!("Failed to decode browser context: {}", err))?;
+
+    Ok(DiffPage(diff_page::DiffPage {
+        browser_ctx: browser_ctx.into_browser_context(),
+    }))
+}
@@ -1,13 +1,118 @@
-export { run };
+export { run, runStream };
+export type { RunEvent };
 
-async function run(data: any): Promise<unknown> {
+const NDJSON_CONTENT_TYPE = "application/x-ndjson";
+
+type RunEvent =
+  | { seq: number; type: "stdout"; text: string }
+  | { seq: number; type: "stderr"; text: string }
+  | { seq: number; type: "finished"; duration: number; error: string } & RunStatus
+  | { seq: number; type: "failed"; message: string }
+  | { seq: number; type: "cancelled" };
+
+// Only sent by newer backends
+type RunStatus = {
+  exitCode?: number;
+  signal?: string;
+  peakMemory?: number;
+  truncated?: boolean;
+};
+
+async function run(data: any, signal?: AbortSignal): Promise<unknown> {
   const response = await fetch("/internal-api/run", {
     method: "POST",
     headers: {
       "Content-Type": "application/json",
     },
     body: JSON.stringify(data),
+    signal,
   });
 
   return response.json();
-}
\ No newline at end of file
+}
+
+// Reads the output as newline delimited json events when the backend streams it,
+// a regular json response is converted to the same events. Rejects when the request fails,
+// is aborted or the output ends before the final event and every event before it arrived.
+async function runStream(data: any, onEvent: (event: RunEvent) => void, signal?: AbortSignal): Promise<void> {
+  const seen = new Set<number>();
+  let finalSeq = -1;
+
+  const emit = (event: RunEvent) => {
+    seen.add(event.seq);
+
+    if (isFinalEvent(event)) {
+      finalSeq = event.seq;
+    }
+
+    onEvent(event);
+  };
+
+  const response = await fetch("/internal-api/run", {
+    method: "POST",
+    headers: {
+      "Content-Type": "application/json",
+      "Accept": `${NDJSON_CONTENT_TYPE}, application/json`,
+    },
+    body: JSON.stringify(data),
+    signal,
+  });
+
+  const contentType = response.headers.get("Content-Type") || "";
+
+  if (contentType.startsWith(NDJSON_CONTENT_TYPE) && response.body) {
+    await readLines(response.body, line => emit(JSON.parse(line)));
+  } else {
+    resultToEvents(await response.json()).forEach(emit);
+  }
+
+  if (finalSeq < 0 || seen.size <= finalSeq) {
+    throw new Error("The run output ended before the run finished");
+  }
+}
+
+function isFinalEvent(event: RunEvent): boolean {
+  return event.type === "finished" || event.type === "failed" || event.type === "cancelled";
+}
+
+function resultToEvents(result: any): RunEvent[] {
+  if (typeof result.stdout !== "string") {
+    return [{ seq: 0, type: "failed", message: result.message || "Unexpected response" }];
+  }
+
+  return [
+    { seq: 0, type: "stdout", text: result.stdout },
+    { seq: 1, type: "stderr", text: result.stderr || "" },
+    {
+      seq: 2,
+      type: "finished",
+      duration: result.duration || 0,
+      error: result.error || "",
+      exitCode: result.exitCode,
+      signal: result.signal,
+      peakMemory: result.peakMemory,
+      truncated: result.truncated,
+    },
+  ];
+}
+
+async function readLines(body: ReadableStream<Uint8Array>, onLine: (line: string) => void): Promise<void> {
+  const reader = body.pipeThrough(new TextDecoderStream()).getReader();
+  let buffer = "";
+
+  while (true) {
+    const { done, value } = await reader.read();
+    if (done) {
+      break;
+    }
+
+    buffer += value;
+    const lines = buffer.split("\n");
+    buffer = lines.pop() || "";
+    lines.filter(line => line.trim() !== "").forEach(onLine);
+  }
+
+  if (buffer.trim() !== "") {
+    onLine(buffer);
+  }
+}
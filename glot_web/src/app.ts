@@ -1,7 +1,8 @@
-import init, { getRouteName, notFoundPage, homePage, snippetPage } from "../wasm/glot";
+import init, { getRouteName, notFoundPage, homePage, snippetPage, diffPage } from "../wasm/glot";
 import { BrowserWindow, Page, Poly } from "poly";
 import { AceEditorElement } from "poly-ace-editor";
-import { run } from "./api";
+import { run, runStream } from "./api";
+import { mockRunStream } from "./mock_run";
 import { defaultDebugConfig } from "poly/src/logger";
 
 AceEditorElement.register();
@@ -20,36 +21,93 @@ AceEditorElement.register();
 
     poly.onCustomEffect(async (msg) => {
         switch (msg.type) {
-            case "run":
+            case "run": {
+                const stream = hasMockRunFlag() ? mockRunStream : runStream;
                 try {
-                    const runResponse = await run(msg.config);
-                    poly.sendMessage("GotRunResponse", runResponse);
+                    const signal = startRun(msg.config.runId);
+                    await stream(msg.config.request, event => poly.sendMessage("GotRunEvent", {
+                        runId: msg.config.runId,
+                        event,
+                    }), signal);
                 } catch (err: any) {
-                    poly.sendMessage("GotRunResponse", {
+                    // Finishes the run regardless of which events have arrived
+                    poly.sendMessage("GotRunAborted", {
+                        runId: msg.config.runId,
+                        cancelled: err.name === "AbortError",
                         message: err.message,
                     });
                 }
                 break;
+            }
+
+            case "runInput":
+                try {
+                    const signal = startRun(msg.config.runId);
+                    const runResponse = await run(msg.config.request, signal);
+                    poly.sendMessage("GotInputRunResponse", {
+                        runId: msg.config.runId,
+                        index: msg.config.index,
+                        response: runResponse,
+                    });
+                } catch (err: any) {
+                    const response = err.name === "AbortError"
+                        ? { cancelled: true }
+                        : { message: err.message };
+
+                    poly.sendMessage("GotInputRunResponse", {
+                        runId: msg.config.runId,
+                        index: msg.config.index,
+                        response,
+                    });
+                }
+                break;
+
+            case "cancelRun":
+                if (activeRun !== null && activeRun.runId === msg.config.runId) {
+                    activeRun.controller.abort();
+                }
+                break;
 
             case "getLanguageVersion":
                 try {
                     const runResponse = await run(msg.config);
                     poly.sendMessage("GotLanguageVersionResponse", runResponse);
                 } catch (err: any) {
-                    poly.sendMessage("LanguageVersionResponse", {
+                    poly.sendMessage("GotLanguageVersionResponse", {
                         message: err.message,
                     });
                 }
                 break;
 
+            case "download":
+                downloadFile(msg.config);
+                break;
+
             default:
                 console.warn(`Unhandled app effect: ${msg.type}`);
         }
     });
 
+    if (route === "NewSnippet" || route === "EditSnippet") {
+        registerFileImport(poly);
+        registerTabReordering(poly);
+    }
+
     poly.init();
 })();
 
+// All requests of a run share the controller, a new run aborts the previous one
+let activeRun: { runId: number; controller: AbortController } | null = null;
+
+function startRun(runId: number): AbortSignal {
+    if (activeRun === null || activeRun.runId !== runId) {
+        activeRun?.controller.abort();
+        activeRun = { runId, controller: new AbortController() };
+    }
+
+    return activeRun.controller.signal;
+}
+
 function pageFromRoute(route: string): Page {
     const browserWindow = new BrowserWindow();
     const browserContext = {
@@ -70,6 +128,9 @@ function pageFromRoute(route: string): Page {
 
         case "EditSnippet":
             return snippetPage(browserContext)
+
+        case "DiffSnippet":
+            return diffPage(browserContext)
     }
 
     throw new Error(`Unhandled route: ${route}`);
@@ -108,6 +169,169 @@ function waitForIdle(): Promise<void> {
     }
 }
 
+interface DownloadRequest {
+    filename: string;
+    mimeType: string;
+    bytes: number[];
+}
+
+function downloadFile(req: DownloadRequest) {
+    const blob = new Blob([new Uint8Array(req.bytes)], { type: req.mimeType });
+    const url = URL.createObjectURL(blob);
+
+    const link = document.createElement("a");
+    link.href = url;
+    link.download = req.filename;
+    document.body.appendChild(link);
+    link.click();
+    link.remove();
+
+    URL.revokeObjectURL(url);
+}
+
+// Same as MAX_UPLOAD_SIZE in the import module, larger files are sent without their content
+const MAX_UPLOAD_SIZE = 4 * 1024 * 1024;
+
+interface Upload {
+    name: string;
+    size: number;
+    bytes: number[];
+}
+
+function registerFileImport(poly: Poly) {
+    document.addEventListener("change", async (event) => {
+        const input = event.target;
+        if (!(input instanceof HTMLInputElement) || input.dataset.importFiles === undefined || !input.files) {
+            return;
+        }
+
+        const files = Array.from(input.files);
+        const uploads = await Promise.all(files.map(file => readUpload(file, file.webkitRelativePath || file.name)));
+        poly.sendMessage("GotImportFiles", uploads);
+        input.value = "";
+    });
+
+    document.addEventListener("dragover", (event) => {
+        event.preventDefault();
+    });
+
+    document.addEventListener("drop", async (event) => {
+        event.preventDefault();
+
+        // Dropped tabs are handled by the tab reordering, dropped text and links are ignored
+        if (!event.dataTransfer?.types.includes("Files")) {
+            return;
+        }
+
+        const items = Array.from(event.dataTransfer?.items || []);
+        const entries = items
+            .map(item => item.webkitGetAsEntry())
+            .filter((entry): entry is FileSystemEntry => entry !== null);
+
+        const files = (await Promise.all(entries.map(readEntry))).flat();
+        const uploads = await Promise.all(files.map(([path, file]) => readUpload(file, path)));
+        poly.sendMessage("GotImportFiles", uploads);
+    });
+}
+
+const TAB_DRAG_TYPE = "application/x-glot-tab";
+
+function registerTabReordering(poly: Poly) {
+    const fileTab = (target: EventTarget | null): HTMLElement | null => {
+        if (!(target instanceof Element)) {
+            return null;
+        }
+
+        return target.closest<HTMLElement>("[data-filename][draggable]");
+    };
+
+    const tabIndex = (tab: HTMLElement): number => {
+        const tabs = Array.from(tab.parentElement?.querySelectorAll("[data-filename]") || []);
+        return tabs.indexOf(tab);
+    };
+
+    document.addEventListener("dragstart", (event) => {
+        const tab = fileTab(event.target);
+        if (!tab || !event.dataTransfer) {
+            return;
+        }
+
+        event.dataTransfer.effectAllowed = "move";
+        event.dataTransfer.setData(TAB_DRAG_TYPE, tab.dataset.filename || "");
+    });
+
+    document.addEventListener("drop", (event) => {
+        const filename = event.dataTransfer?.getData(TAB_DRAG_TYPE);
+        const tab = fileTab(event.target);
+        if (!filename || !tab) {
+            return;
+        }
+
+        poly.sendMessage("GotFileMoved", { filename, index: tabIndex(tab) });
+    });
+
+    document.addEventListener("keydown", (event) => {
+        const tab = fileTab(event.target);
+        if (!tab || !event.altKey || (event.key !== "ArrowLeft" && event.key !== "ArrowRight")) {
+            return;
+        }
+
+        event.preventDefault();
+
+        const offset = event.key === "ArrowLeft" ? -1 : 1;
+        const index = Math.max(tabIndex(tab) + offset, 0);
+        poly.sendMessage("GotFileMoved", { filename: tab.dataset.filename, index });
+    });
+}
+
+async function readEntry(entry: FileSystemEntry): Promise<[string, File][]> {
+    const path = entry.fullPath.replace(/^\//, "");
+
+    if (entry.isFile) {
+        const file = await new Promise<File>((resolve, reject) => {
+            (entry as FileSystemFileEntry).file(resolve, reject);
+        });
+
+        return [[path, file]];
+    }
+
+    const reader = (entry as FileSystemDirectoryEntry).createReader();
+    const children: FileSystemEntry[] = [];
+
+    // readEntries returns results in batches until an empty batch is returned
+    while (true) {
+        const batch = await new Promise<FileSystemEntry[]>((resolve, reject) => {
+            reader.readEntries(resolve, reject);
+        });
+
+        if (batch.length === 0) {
+            break;
+        }
+
+        children.push(...batch);
+    }
+
+    return (await Promise.all(children.map(readEntry))).flat();
+}
+
+async function readUpload(file: File, name: string): Promise<Upload> {
+    if (file.size > MAX_UPLOAD_SIZE) {
+        return { name, size: file.size, bytes: [] };
+    }
+
+    const buffer = await file.arrayBuffer();
+
+    return {
+        name,
+        size: file.size,
+        bytes: Array.from(new Uint8Array(buffer)),
+    };
+}
+
+function hasMockRunFlag(): boolean {
+    return document.location.search.includes("mockRun")
+}
+
 function hasDebugFlag(): boolean {
     return document.location.search.includes("debug")
 }
\ No newline at end of file
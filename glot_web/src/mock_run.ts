@@ -0,0 +1,50 @@
+import type { RunEvent } from "./api";
+
+export { mockRunStream };
+
+const LINE_COUNT = 20;
+const LINE_DELAY_MS = 150;
+
+// Streams made up output without calling the backend, enabled with ?mockRun in the url.
+// Every few events are delivered out of order to exercise the reordering by sequence number.
+// Rejects with an AbortError when cancelled, the same as a fetch.
+async function mockRunStream(data: any, onEvent: (event: RunEvent) => void, signal?: AbortSignal): Promise<void> {
+  const startedAt = performance.now();
+  const events: RunEvent[] = [];
+  let seq = 0;
+
+  events.push({ seq: seq++, type: "stdout", text: `$ ${data.payload.runInstructions.runCommand}\n` });
+
+  for (let i = 1; i <= LINE_COUNT; i++) {
+    events.push({ seq: seq++, type: "stdout", text: `line ${i} of ${LINE_COUNT}\n` });
+
+    if (i % 5 === 0) {
+      events.push({ seq: seq++, type: "stderr", text: `warning: reached line ${i}\n` });
+    }
+  }
+
+  if (data.payload.stdin) {
+    events.push({ seq: seq++, type: "stdout", text: `stdin: ${data.payload.stdin}\n` });
+  }
+
+  for (let i = 0; i + 1 < events.length; i += 4) {
+    [events[i], events[i + 1]] = [events[i + 1], events[i]];
+  }
+
+  for (const event of events) {
+    await sleep(LINE_DELAY_MS);
+
+    if (signal?.aborted) {
+      throw new DOMException("The run was cancelled", "AbortError");
+    }
+
+    onEvent(event);
+  }
+
+  const duration = Math.round((performance.now() - startedAt) * 1e6);
+  onEvent({ seq, type: "finished", duration, error: "", exitCode: 0, peakMemory: 4 * 1024 * 1024 });
+}
+
+function sleep(ms: number): Promise<void> {
+  return new Promise(resolve => setTimeout(resolve, ms));
+}